@@ -0,0 +1,260 @@
+//! Keygen, proving and verification times of the zkG circuits, with IPA over
+//! Vesta and with KZG over BN254.
+//!
+//! The verify benchmarks report the proof size as their throughput, so a chip
+//! change that grows the proof shows up next to the timings. The sizes are also
+//! printed once per configuration.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use halo2_proofs::{arithmetic::Field, circuit::Value, plonk::Circuit};
+use zkG::{
+    batch::{batch_verify, ProofWithInstances},
+    fibo_circuit, fibo_circuit2, fibo_circuit3,
+    instance::NamedInstances,
+    pasta::{vesta, Fp},
+    proof::{keygen, prove, verify, Ipa, Kzg, Scalar, Scheme},
+    range_check::{eg1_simple, eg2_lookup, eg3_numbits},
+};
+
+fn bench_scheme<S: Scheme, C: Circuit<Scalar<S>> + NamedInstances>(
+    c: &mut Criterion,
+    name: &str,
+    k: u32,
+    size: usize,
+    circuit: impl Fn() -> C,
+    instances: &[&[Scalar<S>]],
+) {
+    let parameter = format!("k={}/size={}", k, size);
+    let mut group = c.benchmark_group(name);
+    group.sample_size(10);
+
+    group.bench_with_input(BenchmarkId::new("keygen", &parameter), &k, |b, &k| {
+        b.iter(|| keygen::<S, _>(&circuit(), Some(k)).unwrap())
+    });
+
+    let (params, pk) = keygen::<S, _>(&circuit(), Some(k)).unwrap();
+    group.bench_with_input(BenchmarkId::new("prove", &parameter), &k, |b, _| {
+        b.iter(|| prove::<S, _>(&params, &pk, circuit(), instances).unwrap())
+    });
+
+    let proof = prove::<S, _>(&params, &pk, circuit(), instances).unwrap();
+    println!("{}/{}: proof size {} bytes", name, parameter, proof.len());
+
+    group.throughput(Throughput::Bytes(proof.len() as u64));
+    group.bench_with_input(BenchmarkId::new("verify", &parameter), &k, |b, _| {
+        b.iter(|| verify::<S>(&params, pk.get_vk(), &proof, instances).unwrap())
+    });
+
+    group.finish();
+}
+
+/// The `n`-th term of the sequence starting at 1, 1.
+fn fib<F: Field>(n: usize) -> F {
+    let (mut a, mut b) = (F::ONE, F::ONE);
+    for _ in 2..n {
+        let c = a + b;
+        a = b;
+        b = c;
+    }
+    b
+}
+
+fn fibo_1<S: Scheme, const N: usize>(c: &mut Criterion, scheme: &str, k: u32) {
+    let public_input = [Scalar::<S>::ONE, Scalar::<S>::ONE, fib(N)];
+    bench_scheme::<S, _>(
+        c,
+        &format!("fibo_circuit/{}", scheme),
+        k,
+        N,
+        || fibo_circuit::FiboCircuit::<Scalar<S>, N> {
+            a: Value::known(Scalar::<S>::ONE),
+            b: Value::known(Scalar::<S>::ONE),
+        },
+        &[&public_input],
+    );
+}
+
+fn fibo_2<S: Scheme, const N: usize>(c: &mut Criterion, scheme: &str, k: u32) {
+    let public_input = [Scalar::<S>::ONE, Scalar::<S>::ONE, fib(N)];
+    bench_scheme::<S, _>(
+        c,
+        &format!("fibo_circuit2/{}", scheme),
+        k,
+        N,
+        || fibo_circuit2::FiboCircuit::<Scalar<S>, N> {
+            a: Value::known(Scalar::<S>::ONE),
+            b: Value::known(Scalar::<S>::ONE),
+        },
+        &[&public_input],
+    );
+}
+
+fn fibo_3<S: Scheme, const N: usize, const W: usize>(c: &mut Criterion, scheme: &str, k: u32) {
+    let public_input = [Scalar::<S>::ONE, Scalar::<S>::ONE, fib(N)];
+    println!(
+        "fibo_circuit3/width={}: {} rows, {} advice columns",
+        W,
+        N.div_ceil(W),
+        W
+    );
+    bench_scheme::<S, _>(
+        c,
+        &format!("fibo_circuit3/width={}/{}", W, scheme),
+        k,
+        N,
+        || fibo_circuit3::FiboCircuit::<Scalar<S>, N, W> {
+            a: Value::known(Scalar::<S>::ONE),
+            b: Value::known(Scalar::<S>::ONE),
+        },
+        &[&public_input],
+    );
+}
+
+fn range_check_1<S: Scheme, const RANGE: usize>(c: &mut Criterion, scheme: &str, k: u32) {
+    bench_scheme::<S, _>(
+        c,
+        &format!("range_check/eg1_simple/{}", scheme),
+        k,
+        RANGE,
+        || eg1_simple::RangeCheckCircuit::<Scalar<S>, RANGE> {
+            value: Value::known(Scalar::<S>::from(RANGE as u64 - 1)),
+        },
+        &[],
+    );
+}
+
+fn range_check_2<S: Scheme, const RANGE: usize>(c: &mut Criterion, scheme: &str, k: u32) {
+    bench_scheme::<S, _>(
+        c,
+        &format!("range_check/eg2_lookup/{}", scheme),
+        k,
+        RANGE,
+        || eg2_lookup::RangeCheckCircuit::<Scalar<S>, RANGE> {
+            value: Value::known(Scalar::<S>::from(RANGE as u64 - 1)),
+        },
+        &[],
+    );
+}
+
+fn range_check_3<S: Scheme>(c: &mut Criterion, scheme: &str, k: u32) {
+    // the bit length table is fixed at 3 bits
+    bench_scheme::<S, _>(
+        c,
+        &format!("range_check/eg3_numbits/{}", scheme),
+        k,
+        8,
+        || eg3_numbits::RangeCheckCircuit::<Scalar<S>, 8> {
+            value: Scalar::<S>::from(7),
+            num_bits: 3,
+        },
+        &[],
+    );
+}
+
+fn fibo<S: Scheme>(c: &mut Criterion, scheme: &str) {
+    fibo_1::<S, 10>(c, scheme, 4);
+    fibo_1::<S, 10>(c, scheme, 6);
+    fibo_1::<S, 20>(c, scheme, 5);
+    fibo_1::<S, 40>(c, scheme, 6);
+
+    fibo_2::<S, 10>(c, scheme, 5);
+    fibo_2::<S, 10>(c, scheme, 7);
+    fibo_2::<S, 20>(c, scheme, 5);
+    fibo_2::<S, 40>(c, scheme, 6);
+}
+
+fn fibo_benchmark(c: &mut Criterion) {
+    fibo::<Ipa<vesta::Affine>>(c, "ipa");
+    fibo::<Kzg>(c, "kzg");
+}
+
+/// The same 64 terms with more terms per row: fewer rows and a smaller `k`,
+/// against more advice columns to commit to.
+fn rows_vs_columns<S: Scheme>(c: &mut Criterion, scheme: &str) {
+    fibo_3::<S, 64, 2>(c, scheme, 6);
+    fibo_3::<S, 64, 4>(c, scheme, 5);
+    fibo_3::<S, 64, 8>(c, scheme, 4);
+    fibo_3::<S, 64, 16>(c, scheme, 4);
+}
+
+fn rows_vs_columns_benchmark(c: &mut Criterion) {
+    rows_vs_columns::<Ipa<vesta::Affine>>(c, "ipa");
+    rows_vs_columns::<Kzg>(c, "kzg");
+}
+
+fn range_check<S: Scheme>(c: &mut Criterion, scheme: &str) {
+    range_check_1::<S, 4>(c, scheme, 4);
+    range_check_1::<S, 8>(c, scheme, 4);
+    range_check_1::<S, 8>(c, scheme, 6);
+    range_check_1::<S, 16>(c, scheme, 4);
+
+    range_check_2::<S, 8>(c, scheme, 9);
+    range_check_2::<S, 8>(c, scheme, 11);
+
+    range_check_3::<S>(c, scheme, 4);
+    range_check_3::<S>(c, scheme, 6);
+}
+
+fn range_check_benchmark(c: &mut Criterion) {
+    range_check::<Ipa<vesta::Affine>>(c, "ipa");
+    range_check::<Kzg>(c, "kzg");
+}
+
+fn batch_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("batch_verify");
+    group.sample_size(10);
+
+    let (params, pk) =
+        keygen::<Ipa<vesta::Affine>, _>(&fibo_circuit::FiboCircuit::<Fp>::default(), None).unwrap();
+    let public_inputs: Vec<[Fp; 3]> = (1..=64)
+        .map(|i| {
+            [
+                Fp::from(i),
+                Fp::ONE,
+                fibo_circuit::fibo(Fp::from(i), Fp::ONE, 10),
+            ]
+        })
+        .collect();
+    let proofs: Vec<Vec<u8>> = public_inputs
+        .iter()
+        .map(|public_input| {
+            let circuit = fibo_circuit::FiboCircuit::<Fp> {
+                a: Value::known(public_input[0]),
+                b: Value::known(public_input[1]),
+            };
+            prove::<Ipa<vesta::Affine>, _>(&params, &pk, circuit, &[public_input]).unwrap()
+        })
+        .collect();
+    let instances: Vec<[&[Fp]; 1]> = public_inputs.iter().map(|i| [&i[..]]).collect();
+
+    for n in [4, 16, 64] {
+        group.bench_with_input(BenchmarkId::new("one_by_one", n), &n, |b, &n| {
+            b.iter(|| {
+                for (proof, instances) in proofs.iter().zip(&instances).take(n) {
+                    verify::<Ipa<vesta::Affine>>(&params, pk.get_vk(), proof, instances).unwrap();
+                }
+            })
+        });
+
+        let batch: Vec<ProofWithInstances<Fp>> = proofs
+            .iter()
+            .zip(&instances)
+            .take(n)
+            .map(|(proof, instances)| (proof.as_slice(), &instances[..]))
+            .collect();
+        group.bench_with_input(BenchmarkId::new("batched", n), &n, |b, _| {
+            b.iter(|| batch_verify(&params, pk.get_vk(), &batch).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    fibo_benchmark,
+    rows_vs_columns_benchmark,
+    range_check_benchmark,
+    batch_benchmark
+);
+criterion_main!(benches);
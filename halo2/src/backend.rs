@@ -0,0 +1,179 @@
+//! Runtime choice of the curve and commitment scheme a circuit is proven with.
+//!
+//! The backends are IPA, with a transparent setup, over either curve of the
+//! Pasta cycle, or KZG over BN254, whose pairing-based proofs are the ones an
+//! EVM verifier can check. KZG parameters come from the test setup of
+//! [`proof::Kzg`].
+
+use std::fmt;
+use std::str::FromStr;
+
+use halo2_proofs::plonk::{keygen_vk, Circuit, Error};
+
+use crate::field::FieldExt;
+use crate::instance::NamedInstances;
+use crate::pasta::{pallas, vesta};
+use crate::planner;
+use crate::proof::{self, Ipa, Kzg, ProofError, Scalar, Scheme};
+
+/// A circuit that can be built over the scalar field of every backend.
+pub trait GenericCircuit {
+    type Circuit<F: FieldExt>: Circuit<F> + NamedInstances;
+
+    fn circuit<F: FieldExt>(&self) -> Self::Circuit<F>;
+
+    /// The public inputs, one vector per instance column.
+    fn instances<F: FieldExt>(&self) -> Vec<Vec<F>>;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+    /// IPA over Vesta, for circuits over the Pallas base field `Fp`.
+    IpaVesta,
+    /// IPA over Pallas, for circuits over the Vesta base field `Fq`.
+    IpaPallas,
+    /// KZG over BN254, with SHPLONK openings.
+    KzgBn254,
+}
+
+impl Backend {
+    pub const ALL: [Backend; 3] = [Backend::IpaVesta, Backend::IpaPallas, Backend::KzgBn254];
+
+    /// The smallest `k` that fits `circuit` over this backend's scalar field, see
+    /// [`planner::min_k`].
+    pub fn min_k(&self, circuit: &impl GenericCircuit) -> Result<u32, Error> {
+        match self {
+            Backend::IpaVesta => planner::min_k(&circuit.circuit::<vesta::Scalar>()),
+            Backend::IpaPallas => planner::min_k(&circuit.circuit::<pallas::Scalar>()),
+            Backend::KzgBn254 => planner::min_k(&circuit.circuit::<Scalar<Kzg>>()),
+        }
+    }
+
+    /// Generates keys and proves `circuit`, in `2^k` rows with `k` from
+    /// [`Backend::min_k`] unless one is given.
+    pub fn prove(
+        &self,
+        circuit: &impl GenericCircuit,
+        k: Option<u32>,
+    ) -> Result<Vec<u8>, ProofError> {
+        match self {
+            Backend::IpaVesta => prove_with::<Ipa<vesta::Affine>, _>(circuit, k),
+            Backend::IpaPallas => prove_with::<Ipa<pallas::Affine>, _>(circuit, k),
+            Backend::KzgBn254 => prove_with::<Kzg, _>(circuit, k),
+        }
+    }
+
+    /// Checks a proof created by [`Backend::prove`] for the same circuit and `k`.
+    pub fn verify(
+        &self,
+        circuit: &impl GenericCircuit,
+        proof: &[u8],
+        k: Option<u32>,
+    ) -> Result<(), Error> {
+        match self {
+            Backend::IpaVesta => verify_with::<Ipa<vesta::Affine>, _>(circuit, proof, k),
+            Backend::IpaPallas => verify_with::<Ipa<pallas::Affine>, _>(circuit, proof, k),
+            Backend::KzgBn254 => verify_with::<Kzg, _>(circuit, proof, k),
+        }
+    }
+}
+
+fn prove_with<S: Scheme, G: GenericCircuit>(
+    circuit: &G,
+    k: Option<u32>,
+) -> Result<Vec<u8>, ProofError> {
+    let instances = circuit.instances::<Scalar<S>>();
+    let instances: Vec<&[Scalar<S>]> = instances.iter().map(Vec::as_slice).collect();
+
+    let circuit = circuit.circuit::<Scalar<S>>();
+    let (params, pk) = proof::keygen::<S, _>(&circuit, k)?;
+
+    proof::prove::<S, _>(&params, &pk, circuit, &instances)
+}
+
+fn verify_with<S: Scheme, G: GenericCircuit>(
+    circuit: &G,
+    proof: &[u8],
+    k: Option<u32>,
+) -> Result<(), Error> {
+    let instances = circuit.instances::<Scalar<S>>();
+    let instances: Vec<&[Scalar<S>]> = instances.iter().map(Vec::as_slice).collect();
+
+    // both setups are deterministic, so the verifier can rebuild the parameters and
+    // the key from the circuit shape
+    let circuit = circuit.circuit::<Scalar<S>>();
+    let k = match k {
+        Some(k) => k,
+        None => planner::min_k(&circuit)?,
+    };
+    let params = S::setup(k);
+    let vk = keygen_vk(&params, &circuit.without_witnesses())?;
+
+    proof::verify::<S>(&params, &vk, proof, &instances)
+}
+
+impl FromStr for Backend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ipa-vesta" => Ok(Backend::IpaVesta),
+            "ipa-pallas" => Ok(Backend::IpaPallas),
+            "kzg-bn254" => Ok(Backend::KzgBn254),
+            _ => Err(format!(
+                "unknown backend `{}`, expected `ipa-vesta`, `ipa-pallas` or `kzg-bn254`",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Backend::IpaVesta => write!(f, "ipa-vesta"),
+            Backend::IpaPallas => write!(f, "ipa-pallas"),
+            Backend::KzgBn254 => write!(f, "kzg-bn254"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{fibo_circuit, fibo_circuit2, range_check::eg3_numbits};
+
+    use super::*;
+
+    #[test]
+    fn prove_with_every_backend_test() {
+        let fibo = fibo_circuit::FiboInput::<10> { a: 1, b: 1 };
+        let fibo2 = fibo_circuit2::FiboInput::<10> { a: 2, b: 3 };
+        let range = eg3_numbits::RangeCheckInput {
+            value: 5,
+            num_bits: 3,
+        };
+
+        for backend in Backend::ALL {
+            let proof = backend.prove(&fibo, None).unwrap();
+            assert!(backend.verify(&fibo, &proof, None).is_ok());
+
+            let proof = backend.prove(&fibo2, None).unwrap();
+            assert!(backend.verify(&fibo2, &proof, None).is_ok());
+            assert!(backend.verify(&fibo, &proof, None).is_err());
+
+            // a larger k than needed still works, but only with the same k
+            let k = backend.min_k(&range).unwrap() + 1;
+            let proof = backend.prove(&range, Some(k)).unwrap();
+            assert!(backend.verify(&range, &proof, Some(k)).is_ok());
+            assert!(backend.verify(&range, &proof, None).is_err());
+        }
+    }
+
+    #[test]
+    fn parse_backend_test() {
+        for backend in Backend::ALL {
+            assert_eq!(backend.to_string().parse(), Ok(backend));
+        }
+        assert!("ipa-bn254".parse::<Backend>().is_err());
+    }
+}
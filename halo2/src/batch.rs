@@ -0,0 +1,281 @@
+//! Checking many proofs at the cost of one final MSM.
+//!
+//! Verifying an IPA proof ends in a multi-scalar multiplication over all `2^k`
+//! generators of the parameters. An [`Accumulator`] runs every check of a proof
+//! except that MSM, and folds the MSM into a random linear combination of the
+//! ones folded before, so a batch pays for it only once.
+
+use std::fmt;
+
+use halo2_proofs::{
+    arithmetic::{CurveAffine, Field},
+    halo2curves::ff::WithSmallOrderMulGroup,
+    plonk::{verify_proof, Error, VerifyingKey},
+    poly::{
+        commitment::{Params, ParamsProver, MSM},
+        ipa::{
+            commitment::{IPACommitmentScheme, ParamsIPA},
+            msm::MSMIPA,
+            multiopen::VerifierIPA,
+            strategy::GuardIPA,
+        },
+        VerificationStrategy,
+    },
+    transcript::{Blake2bRead, Challenge255, TranscriptReadBuffer},
+};
+use rand_core::OsRng;
+
+use crate::field::FieldExt;
+
+/// A proof together with its public inputs, one slice per instance column.
+pub type ProofWithInstances<'a, F> = (&'a [u8], &'a [&'a [F]]);
+
+/// [`Accumulator::merge`] was given an accumulator over other parameters, whose
+/// MSM runs over other generators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParamsMismatch;
+
+impl fmt::Display for ParamsMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the accumulators were built from different parameters")
+    }
+}
+
+impl std::error::Error for ParamsMismatch {}
+
+/// Proofs whose final MSM has been deferred.
+///
+/// Proofs for different verifying keys can be folded together, as long as the
+/// keys were generated from the same `params`.
+#[derive(Debug, Clone)]
+pub struct Accumulator<'params, C: CurveAffine> {
+    params: &'params ParamsIPA<C>,
+    msm: MSMIPA<'params, C>,
+    proofs: usize,
+}
+
+impl<'params, C: CurveAffine<ScalarExt: FieldExt + WithSmallOrderMulGroup<3>>>
+    Accumulator<'params, C>
+{
+    pub fn new(params: &'params ParamsIPA<C>) -> Self {
+        Self {
+            params,
+            msm: MSMIPA::new(params),
+            proofs: 0,
+        }
+    }
+
+    /// Folds `proof` into the accumulator. An error means the proof is malformed,
+    /// and leaves the accumulator as it was.
+    pub fn fold(
+        &mut self,
+        vk: &VerifyingKey<C>,
+        proof: &[u8],
+        instances: &[&[C::Scalar]],
+    ) -> Result<(), Error> {
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+        let msm = verify_proof::<IPACommitmentScheme<C>, VerifierIPA<_>, _, _, _>(
+            self.params,
+            vk,
+            DeferredMsm(self.params),
+            &[instances],
+            &mut transcript,
+        )?;
+
+        // a random factor keeps a bad proof from cancelling out against the others
+        self.msm.scale(C::Scalar::random(OsRng));
+        self.msm.add_msm(&msm);
+        self.proofs += 1;
+
+        Ok(())
+    }
+
+    /// Folds every proof of `other` into this accumulator. Both must have been
+    /// built from the same parameters; otherwise this one is left as it was.
+    pub fn merge(&mut self, other: &Self) -> Result<(), ParamsMismatch> {
+        let same_params = std::ptr::eq(self.params, other.params)
+            || (self.params.k() == other.params.k() && self.params.get_g() == other.params.get_g());
+        if !same_params {
+            return Err(ParamsMismatch);
+        }
+
+        self.msm.scale(C::Scalar::random(OsRng));
+        self.msm.add_msm(&other.msm);
+        self.proofs += other.proofs;
+        Ok(())
+    }
+
+    /// The number of proofs folded so far.
+    pub fn len(&self) -> usize {
+        self.proofs
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.proofs == 0
+    }
+
+    /// Runs the deferred MSM, which succeeds only if every folded proof is valid.
+    pub fn check(self) -> Result<(), Error> {
+        if self.msm.check() {
+            Ok(())
+        } else {
+            Err(Error::ConstraintSystemFailure)
+        }
+    }
+}
+
+/// A strategy that stops before the final MSM and hands it back.
+struct DeferredMsm<'params, C: CurveAffine>(&'params ParamsIPA<C>);
+
+impl<'params, C: CurveAffine>
+    VerificationStrategy<'params, IPACommitmentScheme<C>, VerifierIPA<'params, C>>
+    for DeferredMsm<'params, C>
+{
+    type Output = MSMIPA<'params, C>;
+
+    fn new(params: &'params ParamsIPA<C>) -> Self {
+        DeferredMsm(params)
+    }
+
+    fn process(
+        self,
+        f: impl FnOnce(MSMIPA<'params, C>) -> Result<GuardIPA<'params, C>, Error>,
+    ) -> Result<Self::Output, Error> {
+        let guard = f(MSMIPA::new(self.0))?;
+        Ok(guard.use_challenges())
+    }
+
+    fn finalize(self) -> bool {
+        unreachable!("the MSM is handed back by process")
+    }
+}
+
+/// Checks `proofs`, each with its public inputs, against `vk` with a single MSM.
+///
+/// Fails if any proof is invalid, without saying which one; re-check them one by
+/// one with [`crate::proof::verify`] to find it.
+pub fn batch_verify<C: CurveAffine<ScalarExt: FieldExt + WithSmallOrderMulGroup<3>>>(
+    params: &ParamsIPA<C>,
+    vk: &VerifyingKey<C>,
+    proofs: &[ProofWithInstances<C::Scalar>],
+) -> Result<(), Error> {
+    let mut accumulator = Accumulator::new(params);
+    for (proof, instances) in proofs {
+        accumulator.fold(vk, proof, instances)?;
+    }
+    accumulator.check()
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::{circuit::Value, poly::commitment::ParamsProver};
+
+    use crate::fibo_circuit::{fibo, FiboCircuit};
+    use crate::pasta::{vesta, Fp};
+    use crate::proof::{keygen, prove, Ipa};
+
+    use super::*;
+
+    type Proofs = Vec<(Vec<u8>, Vec<Fp>)>;
+
+    fn proofs(params: &ParamsIPA<vesta::Affine>, n: u64) -> (VerifyingKey<vesta::Affine>, Proofs) {
+        let (_, pk) =
+            keygen::<Ipa<vesta::Affine>, _>(&FiboCircuit::<Fp>::default(), Some(4)).unwrap();
+
+        let proofs = (1..=n)
+            .map(|i| {
+                let (a, b) = (Fp::from(i), Fp::from(i + 1));
+                let circuit = FiboCircuit::<Fp> {
+                    a: Value::known(a),
+                    b: Value::known(b),
+                };
+                let public_input = vec![a, b, fibo(a, b, 10)];
+                let proof =
+                    prove::<Ipa<vesta::Affine>, _>(params, &pk, circuit, &[&public_input]).unwrap();
+                (proof, public_input)
+            })
+            .collect();
+
+        (pk.get_vk().clone(), proofs)
+    }
+
+    fn check_batch(
+        params: &ParamsIPA<vesta::Affine>,
+        vk: &VerifyingKey<vesta::Affine>,
+        proofs: &[(Vec<u8>, Vec<Fp>)],
+    ) -> Result<(), Error> {
+        let instances: Vec<[&[Fp]; 1]> = proofs.iter().map(|(_, i)| [i.as_slice()]).collect();
+        let proofs: Vec<ProofWithInstances<Fp>> = proofs
+            .iter()
+            .zip(&instances)
+            .map(|((proof, _), instances)| (proof.as_slice(), &instances[..]))
+            .collect();
+
+        batch_verify(params, vk, &proofs)
+    }
+
+    #[test]
+    fn batch_verify_test() {
+        let params = ParamsIPA::<vesta::Affine>::new(4);
+        let (vk, mut proofs) = proofs(&params, 8);
+        assert!(check_batch(&params, &vk, &proofs).is_ok());
+
+        // one wrong public input spoils the whole batch
+        proofs[3].1[2] += Fp::ONE;
+        assert!(check_batch(&params, &vk, &proofs).is_err());
+    }
+
+    #[test]
+    fn accumulator_test() {
+        let params = ParamsIPA::<vesta::Affine>::new(4);
+        let (vk, proofs) = proofs(&params, 4);
+
+        let mut first = Accumulator::new(&params);
+        let mut second = Accumulator::new(&params);
+        for (i, (proof, public_input)) in proofs.iter().enumerate() {
+            let accumulator = if i % 2 == 0 { &mut first } else { &mut second };
+            accumulator.fold(&vk, proof, &[public_input]).unwrap();
+        }
+        assert!(second.clone().check().is_ok());
+
+        first.merge(&second).unwrap();
+        assert_eq!(first.len(), 4);
+        assert!(first.clone().check().is_ok());
+
+        // a proof for other public inputs still folds, but fails the check
+        let (proof, public_input) = &proofs[0];
+        let wrong_input = [public_input[0], public_input[1], public_input[1]];
+        first.fold(&vk, proof, &[&wrong_input]).unwrap();
+        assert!(first.check().is_err());
+
+        // a truncated proof is rejected right away
+        let mut accumulator = Accumulator::new(&params);
+        assert!(accumulator
+            .fold(&vk, &proof[..proof.len() / 2], &[public_input])
+            .is_err());
+        assert!(accumulator.is_empty());
+    }
+
+    #[test]
+    fn merge_params_test() {
+        let params = ParamsIPA::<vesta::Affine>::new(4);
+        let (vk, proofs) = proofs(&params, 2);
+        let (proof, public_input) = &proofs[0];
+
+        // equal parameters need not be the same value
+        let copy = params.clone();
+        let mut first = Accumulator::new(&params);
+        let mut second = Accumulator::new(&copy);
+        first.fold(&vk, proof, &[public_input]).unwrap();
+        second.fold(&vk, proof, &[public_input]).unwrap();
+        assert_eq!(first.merge(&second), Ok(()));
+        assert_eq!(first.len(), 2);
+
+        // an accumulator over more generators cannot be merged in
+        let larger = ParamsIPA::<vesta::Affine>::new(5);
+        let other = Accumulator::new(&larger);
+        assert_eq!(first.merge(&other), Err(ParamsMismatch));
+        assert_eq!(first.len(), 2);
+        assert!(first.check().is_ok());
+    }
+}
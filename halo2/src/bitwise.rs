@@ -0,0 +1,756 @@
+//! Bitwise XOR, AND and OR through lookup tables.
+//!
+//! A gate can only add and multiply, so a bitwise operation on arbitrary field
+//! elements would need every bit as a separate cell. Instead each word is split
+//! into `BITS`-bit chunks, every pair of chunks is looked up in an
+//! `(a, b, a op b)` table, and the result chunks are recombined into a word.
+//!
+//! The tables have `2^(2 * BITS)` rows, so the default 8-bit chunks need `k >= 17`.
+//!
+//! ```text
+//!  a chunk | b chunk | c chunk | a word      | b word      | c word      | q_first | q_step | q_op
+//!  a_{n-1}   b_{n-1}   c_{n-1}   a_{n-1}       b_{n-1}       c_{n-1}        1         0       1
+//!  a_{n-2}   b_{n-2}   c_{n-2}   a_{n-1}·2^B   ...                          0         1       1
+//!                                + a_{n-2}
+//!  ...
+//!  a_0       b_0       c_0       a             b             a op b         0         1       1
+//! ```
+//!
+//! A rotation splits its word at every multiple of `BITS` and where the rotated
+//! word wraps around, so a piece may be shorter than a chunk. Every piece `x` of
+//! `s` bits is looked up in the AND table next to `x·2^(BITS - s)`, which only
+//! fits in `BITS` bits if `x` fits in `s`, and the word and its rotation are both
+//! sums of the pieces, shifted by fixed powers of two.
+//!
+//! ```text
+//!  a chunk | b chunk          | c chunk | a word      | b word         | shift a | shift rot | scale
+//!  x_n       x_n·2^(B - s_n)    ...       x_n·2^l_n     x_n·2^l'_n       2^l_n     2^l'_n      2^(B - s_n)
+//!  ...
+//!  x_0       x_0·2^(B - s_0)    ...       a             rotl(a, r)       2^l_0     2^l'_0      2^(B - s_0)
+//! ```
+
+use std::fmt;
+
+use halo2_proofs::{
+    circuit::{Chip, Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+
+use crate::cell::{AssignedCell, RegionExt};
+use crate::field::FieldExt;
+
+pub use self::table::BitwiseTable;
+
+mod table;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BitwiseOp {
+    Xor,
+    And,
+    Or,
+}
+
+impl BitwiseOp {
+    pub const ALL: [BitwiseOp; 3] = [BitwiseOp::Xor, BitwiseOp::And, BitwiseOp::Or];
+
+    /// The operation on native integers.
+    pub fn apply(&self, a: u128, b: u128) -> u128 {
+        match self {
+            BitwiseOp::Xor => a ^ b,
+            BitwiseOp::And => a & b,
+            BitwiseOp::Or => a | b,
+        }
+    }
+
+    fn index(&self) -> usize {
+        match self {
+            BitwiseOp::Xor => 0,
+            BitwiseOp::And => 1,
+            BitwiseOp::Or => 2,
+        }
+    }
+}
+
+impl fmt::Display for BitwiseOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BitwiseOp::Xor => write!(f, "xor"),
+            BitwiseOp::And => write!(f, "and"),
+            BitwiseOp::Or => write!(f, "or"),
+        }
+    }
+}
+
+/// Bitwise operations and rotations of words, checked chunk by chunk.
+pub trait BitwiseInstructions<F: FieldExt>: Chip<F> {
+    /// Loads the three tables; call once per circuit.
+    fn load_tables(&self, layouter: impl Layouter<F>) -> Result<(), Error>;
+
+    fn xor(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F>,
+        b: &AssignedCell<F>,
+        num_bits: usize,
+    ) -> Result<AssignedCell<F>, Error>;
+
+    fn and(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F>,
+        b: &AssignedCell<F>,
+        num_bits: usize,
+    ) -> Result<AssignedCell<F>, Error>;
+
+    fn or(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F>,
+        b: &AssignedCell<F>,
+        num_bits: usize,
+    ) -> Result<AssignedCell<F>, Error>;
+
+    /// Computes `a op b` on `num_bits`-bit words, which also constrains `a` and
+    /// `b` to `num_bits` bits. `num_bits` must be a multiple of `BITS` and at
+    /// most 128.
+    fn op(
+        &self,
+        layouter: impl Layouter<F>,
+        op: BitwiseOp,
+        a: &AssignedCell<F>,
+        b: &AssignedCell<F>,
+        num_bits: usize,
+    ) -> Result<AssignedCell<F>, Error>;
+
+    /// Splits a `num_bits`-bit word into `BITS`-bit chunks, most significant
+    /// first, which also constrains it to `num_bits` bits. `num_bits` must be a
+    /// multiple of `BITS` and at most 128.
+    fn decompose(
+        &self,
+        layouter: impl Layouter<F>,
+        word: &AssignedCell<F>,
+        num_bits: usize,
+    ) -> Result<Vec<AssignedCell<F>>, Error>;
+
+    /// Recombines `BITS`-bit chunks, most significant first, into a word,
+    /// constraining every chunk to `BITS` bits.
+    fn compose(
+        &self,
+        layouter: impl Layouter<F>,
+        chunks: &[AssignedCell<F>],
+    ) -> Result<AssignedCell<F>, Error>;
+
+    /// Rotates a `num_bits`-bit word left by `r` bits, which also constrains it
+    /// to `num_bits` bits. `num_bits` is at most 128, and need not be a multiple
+    /// of `BITS`.
+    fn rotate_left(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F>,
+        r: usize,
+        num_bits: usize,
+    ) -> Result<AssignedCell<F>, Error>;
+}
+
+#[derive(Clone, Debug)]
+pub struct BitwiseConfig<F: FieldExt, const BITS: usize> {
+    /// the `a`, `b` and `c` chunks
+    chunks: [Column<Advice>; 3],
+    /// the `a`, `b` and `c` words, recombined up to the current chunk
+    words: [Column<Advice>; 3],
+    q_first: Selector,
+    q_step: Selector,
+    /// one lookup selector per operation, in the order of [`BitwiseOp::ALL`]
+    q_ops: [Selector; 3],
+    q_rot_first: Selector,
+    q_rot_step: Selector,
+    /// the shifts of a rotation piece in the word and in its rotation, and the
+    /// scale that range checks it
+    rot_coeffs: [Column<Fixed>; 3],
+    tables: [BitwiseTable<F, BITS>; 3],
+}
+
+pub struct BitwiseChip<F: FieldExt, const BITS: usize = 8> {
+    config: BitwiseConfig<F, BITS>,
+}
+
+impl<F: FieldExt, const BITS: usize> Chip<F> for BitwiseChip<F, BITS> {
+    type Config = BitwiseConfig<F, BITS>;
+    type Loaded = [BitwiseTable<F, BITS>; 3];
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &self.config.tables
+    }
+}
+
+impl<F: FieldExt, const BITS: usize> BitwiseChip<F, BITS> {
+    pub fn construct(config: BitwiseConfig<F, BITS>) -> Self {
+        Self { config }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        chunks: [Column<Advice>; 3],
+        words: [Column<Advice>; 3],
+    ) -> BitwiseConfig<F, BITS> {
+        let q_first = meta.selector();
+        let q_step = meta.selector();
+        let q_ops = [(); 3].map(|_| meta.complex_selector());
+        let q_rot_first = meta.selector();
+        let q_rot_step = meta.selector();
+        let rot_coeffs = [(); 3].map(|_| meta.fixed_column());
+        let tables = BitwiseOp::ALL.map(|op| BitwiseTable::configure(meta, op));
+
+        // chunks are copied in and out by `compose` and `decompose`
+        meta.enable_equality(chunks[0]);
+        for column in words {
+            meta.enable_equality(column);
+        }
+
+        meta.create_gate("first chunk", |meta| {
+            let q_first = meta.query_selector(q_first);
+            (0..3)
+                .map(|i| {
+                    let chunk = meta.query_advice(chunks[i], Rotation::cur());
+                    let word = meta.query_advice(words[i], Rotation::cur());
+                    q_first.clone() * (word - chunk)
+                })
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("next chunk", |meta| {
+            let q_step = meta.query_selector(q_step);
+            let shift = Expression::Constant(F::from_u128(1 << BITS));
+            (0..3)
+                .map(|i| {
+                    let chunk = meta.query_advice(chunks[i], Rotation::cur());
+                    let word = meta.query_advice(words[i], Rotation::cur());
+                    let prev = meta.query_advice(words[i], Rotation::prev());
+                    q_step.clone() * (word - (prev * shift.clone() + chunk))
+                })
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("first rotation piece", |meta| {
+            let q_rot_first = meta.query_selector(q_rot_first);
+            let piece = meta.query_advice(chunks[0], Rotation::cur());
+            let scaled = meta.query_advice(chunks[1], Rotation::cur());
+            let [shift_a, shift_rot, scale] =
+                rot_coeffs.map(|column| meta.query_fixed(column, Rotation::cur()));
+            let word = meta.query_advice(words[0], Rotation::cur());
+            let rot = meta.query_advice(words[1], Rotation::cur());
+            vec![
+                q_rot_first.clone() * (word - piece.clone() * shift_a),
+                q_rot_first.clone() * (rot - piece.clone() * shift_rot),
+                q_rot_first * (scaled - piece * scale),
+            ]
+        });
+
+        meta.create_gate("next rotation piece", |meta| {
+            let q_rot_step = meta.query_selector(q_rot_step);
+            let piece = meta.query_advice(chunks[0], Rotation::cur());
+            let scaled = meta.query_advice(chunks[1], Rotation::cur());
+            let [shift_a, shift_rot, scale] =
+                rot_coeffs.map(|column| meta.query_fixed(column, Rotation::cur()));
+            let word = meta.query_advice(words[0], Rotation::cur());
+            let prev_word = meta.query_advice(words[0], Rotation::prev());
+            let rot = meta.query_advice(words[1], Rotation::cur());
+            let prev_rot = meta.query_advice(words[1], Rotation::prev());
+            vec![
+                q_rot_step.clone() * (word - (prev_word + piece.clone() * shift_a)),
+                q_rot_step.clone() * (rot - (prev_rot + piece.clone() * shift_rot)),
+                q_rot_step * (scaled - piece * scale),
+            ]
+        });
+
+        // a disabled row looks up (0, 0, 0), which is in every table
+        for (q_op, table) in q_ops.iter().zip(&tables) {
+            meta.lookup("bitwise", |meta| {
+                let q_op = meta.query_selector(*q_op);
+                [table.a, table.b, table.c]
+                    .iter()
+                    .zip(chunks)
+                    .map(|(table_column, chunk)| {
+                        let chunk = meta.query_advice(chunk, Rotation::cur());
+                        (q_op.clone() * chunk, *table_column)
+                    })
+                    .collect()
+            });
+        }
+
+        BitwiseConfig {
+            chunks,
+            words,
+            q_first,
+            q_step,
+            q_ops,
+            q_rot_first,
+            q_rot_step,
+            rot_coeffs,
+            tables,
+        }
+    }
+
+    /// The `BITS`-bit chunks of a `num_bits`-bit word, most significant first.
+    fn chunks(word: Value<F>, num_bits: usize) -> Vec<Value<u128>> {
+        assert_eq!(
+            num_bits % BITS,
+            0,
+            "words are split into {}-bit chunks",
+            BITS
+        );
+        assert!(num_bits <= 128, "words are at most 128 bits");
+
+        let mask = (1u128 << BITS) - 1;
+        (0..num_bits / BITS)
+            .rev()
+            .map(|i| word.map(|w| (w.get_lower_128() >> (i * BITS)) & mask))
+            .collect()
+    }
+
+    /// Lays out one row per pair of chunks, looked up in the table of `op`, and
+    /// returns the `a` chunk cells and the three recombined words.
+    #[allow(clippy::type_complexity)]
+    fn assign_chunks(
+        &self,
+        region: &mut Region<'_, F>,
+        op: BitwiseOp,
+        a_chunks: &[Value<u128>],
+        b_chunks: &[Value<u128>],
+    ) -> Result<(Vec<AssignedCell<F>>, [AssignedCell<F>; 3]), Error> {
+        let c_chunks: Vec<Value<u128>> = a_chunks
+            .iter()
+            .zip(b_chunks)
+            .map(|(a, b)| a.zip(*b).map(|(a, b)| op.apply(a, b)))
+            .collect();
+
+        let mut words: [Value<F>; 3] = [Value::known(F::ZERO); 3];
+        let mut chunk_cells = vec![];
+        let mut word_cells = vec![];
+
+        for row in 0..a_chunks.len() {
+            if row == 0 {
+                self.config.q_first.enable(region, row)?;
+            } else {
+                self.config.q_step.enable(region, row)?;
+            }
+            self.config.q_ops[op.index()].enable(region, row)?;
+
+            let row_chunks = [a_chunks[row], b_chunks[row], c_chunks[row]];
+            word_cells.clear();
+            for i in 0..3 {
+                let chunk = row_chunks[i].map(F::from_u128);
+                let cell = region.assign(self.config.chunks[i], row, chunk);
+                if i == 0 {
+                    chunk_cells.push(cell);
+                }
+
+                words[i] = words[i]
+                    .zip(chunk)
+                    .map(|(w, c)| w * F::from_u128(1 << BITS) + c);
+                let word = words[i];
+                word_cells.push(region.assign(self.config.words[i], row, word));
+            }
+        }
+
+        let word_cells: [AssignedCell<F>; 3] = word_cells.try_into().unwrap();
+        Ok((chunk_cells, word_cells))
+    }
+}
+
+impl<F: FieldExt, const BITS: usize> BitwiseInstructions<F> for BitwiseChip<F, BITS> {
+    fn load_tables(&self, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        for table in &self.config.tables {
+            table.load(&mut layouter)?;
+        }
+        Ok(())
+    }
+
+    fn xor(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F>,
+        b: &AssignedCell<F>,
+        num_bits: usize,
+    ) -> Result<AssignedCell<F>, Error> {
+        self.op(layouter, BitwiseOp::Xor, a, b, num_bits)
+    }
+
+    fn and(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F>,
+        b: &AssignedCell<F>,
+        num_bits: usize,
+    ) -> Result<AssignedCell<F>, Error> {
+        self.op(layouter, BitwiseOp::And, a, b, num_bits)
+    }
+
+    fn or(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F>,
+        b: &AssignedCell<F>,
+        num_bits: usize,
+    ) -> Result<AssignedCell<F>, Error> {
+        self.op(layouter, BitwiseOp::Or, a, b, num_bits)
+    }
+
+    fn op(
+        &self,
+        mut layouter: impl Layouter<F>,
+        op: BitwiseOp,
+        a: &AssignedCell<F>,
+        b: &AssignedCell<F>,
+        num_bits: usize,
+    ) -> Result<AssignedCell<F>, Error> {
+        let a_chunks = Self::chunks(a.field_value(), num_bits);
+        let b_chunks = Self::chunks(b.field_value(), num_bits);
+
+        layouter.assign_region(
+            || format!("{} {} bits", op, num_bits),
+            |mut region| {
+                let (_, [a_word, b_word, c_word]) =
+                    self.assign_chunks(&mut region, op, &a_chunks, &b_chunks)?;
+
+                // the recombined inputs must be the words we were given
+                region.constrain_equal(a.cell(), a_word.cell());
+                region.constrain_equal(b.cell(), b_word.cell());
+
+                Ok(c_word)
+            },
+        )
+    }
+
+    fn decompose(
+        &self,
+        mut layouter: impl Layouter<F>,
+        word: &AssignedCell<F>,
+        num_bits: usize,
+    ) -> Result<Vec<AssignedCell<F>>, Error> {
+        let chunks = Self::chunks(word.field_value(), num_bits);
+        let zeros = vec![Value::known(0); chunks.len()];
+
+        layouter.assign_region(
+            || format!("decompose {} bits", num_bits),
+            |mut region| {
+                // any chunk ANDed with zero is in the table, so only the range is checked
+                let (chunk_cells, [a_word, _, _]) =
+                    self.assign_chunks(&mut region, BitwiseOp::And, &chunks, &zeros)?;
+                region.constrain_equal(word.cell(), a_word.cell());
+                Ok(chunk_cells)
+            },
+        )
+    }
+
+    fn compose(
+        &self,
+        mut layouter: impl Layouter<F>,
+        chunks: &[AssignedCell<F>],
+    ) -> Result<AssignedCell<F>, Error> {
+        assert!(chunks.len() * BITS <= 128, "words are at most 128 bits");
+        let values: Vec<Value<u128>> = chunks
+            .iter()
+            .map(|chunk| chunk.field_value().map(|v| v.get_lower_128()))
+            .collect();
+        let zeros = vec![Value::known(0); chunks.len()];
+
+        layouter.assign_region(
+            || format!("compose {} bits", chunks.len() * BITS),
+            |mut region| {
+                let (chunk_cells, [a_word, _, _]) =
+                    self.assign_chunks(&mut region, BitwiseOp::And, &values, &zeros)?;
+                for (chunk, cell) in chunks.iter().zip(&chunk_cells) {
+                    region.constrain_equal(chunk.cell(), cell.cell());
+                }
+                Ok(a_word)
+            },
+        )
+    }
+
+    fn rotate_left(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<F>,
+        r: usize,
+        num_bits: usize,
+    ) -> Result<AssignedCell<F>, Error> {
+        assert!(num_bits <= 128, "words are at most 128 bits");
+        assert!(r < num_bits, "rotations are by less than the word size");
+
+        // the pieces as (lowest bit, length), most significant first
+        let mut bounds: Vec<usize> = (0..num_bits).step_by(BITS).collect();
+        bounds.extend([num_bits - r, num_bits]);
+        bounds.sort_unstable();
+        bounds.dedup();
+        let pieces: Vec<(usize, usize)> = bounds
+            .windows(2)
+            .rev()
+            .map(|pair| (pair[0], pair[1] - pair[0]))
+            .collect();
+
+        let a_value = a.field_value().map(|a| a.get_lower_128());
+
+        layouter.assign_region(
+            || format!("rotate {} bits by {}", num_bits, r),
+            |mut region| {
+                let mut words = [Value::known(F::ZERO); 2];
+                let mut word_cells = vec![];
+
+                for (row, &(low, len)) in pieces.iter().enumerate() {
+                    if row == 0 {
+                        self.config.q_rot_first.enable(&mut region, row)?;
+                    } else {
+                        self.config.q_rot_step.enable(&mut region, row)?;
+                    }
+                    self.config.q_ops[BitwiseOp::And.index()].enable(&mut region, row)?;
+
+                    // the top r bits of the word are the bottom r bits of its rotation
+                    let rot_low = if low >= num_bits - r {
+                        low + r - num_bits
+                    } else {
+                        low + r
+                    };
+                    let coeffs = [1u128 << low, 1 << rot_low, 1 << (BITS - len)];
+                    for (column, coeff) in self.config.rot_coeffs.iter().zip(coeffs) {
+                        region.assign_fixed(*column, row, F::from_u128(coeff));
+                    }
+
+                    let piece = a_value.map(|a| (a >> low) & ((1 << len) - 1));
+                    let scaled = piece.map(|x| x << (BITS - len));
+                    let row_chunks = [piece, scaled, piece.zip(scaled).map(|(x, y)| x & y)];
+                    for (column, chunk) in self.config.chunks.iter().zip(row_chunks) {
+                        region.assign(*column, row, chunk.map(F::from_u128));
+                    }
+
+                    word_cells.clear();
+                    for i in 0..2 {
+                        words[i] = words[i]
+                            .zip(piece)
+                            .map(|(w, x)| w + F::from_u128(x) * F::from_u128(coeffs[i]));
+                        let word = words[i];
+                        word_cells.push(region.assign(self.config.words[i], row, word));
+                    }
+                }
+
+                region.constrain_equal(a.cell(), word_cells[0].cell());
+                Ok(word_cells[1].clone())
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use group::ff::PrimeField;
+    use halo2_proofs::{
+        arithmetic::Field,
+        dev::MockProver,
+        halo2curves::pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    use super::*;
+
+    use crate::planner::SimpleFloorPlanner;
+
+    /// Proves `a op b == c` for a public `c`.
+    #[derive(Default)]
+    struct BitwiseCircuit<const BITS: usize, const NUM_BITS: usize> {
+        op: Option<BitwiseOp>,
+        a: Value<u128>,
+        b: Value<u128>,
+    }
+
+    impl<const BITS: usize, const NUM_BITS: usize> Circuit<Fp> for BitwiseCircuit<BITS, NUM_BITS> {
+        type Config = (BitwiseConfig<Fp, BITS>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                op: self.op,
+                ..Self::default()
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let chunks = [(); 3].map(|_| meta.advice_column());
+            let words = [(); 3].map(|_| meta.advice_column());
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (BitwiseChip::configure(meta, chunks, words), instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let words = config.words;
+            let chip = BitwiseChip::construct(config);
+            chip.load_tables(layouter.namespace(|| "tables"))?;
+
+            let (a, b) = layouter.assign_region(
+                || "inputs",
+                |mut region| {
+                    let a = region.assign(words[0], 0, self.a.map(Fp::from_u128));
+                    let b = region.assign(words[1], 0, self.b.map(Fp::from_u128));
+                    Ok((a, b))
+                },
+            )?;
+
+            let op = self.op.ok_or(Error::Synthesis)?;
+            let c = chip.op(layouter.namespace(|| "op"), op, &a, &b, NUM_BITS)?;
+            layouter.constrain_instance(c.cell(), instance, 0);
+            Ok(())
+        }
+    }
+
+    fn check<const BITS: usize, const NUM_BITS: usize>(k: u32, op: BitwiseOp, a: u128, b: u128) {
+        let circuit = BitwiseCircuit::<BITS, NUM_BITS> {
+            op: Some(op),
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+
+        let c = Fp::from_u128(op.apply(a, b));
+        MockProver::run(k, &circuit, vec![vec![c]])
+            .unwrap()
+            .assert_satisfied();
+
+        assert!(MockProver::run(k, &circuit, vec![vec![c + Fp::ONE]])
+            .unwrap()
+            .verify()
+            .is_err());
+    }
+
+    #[test]
+    fn word_ops_test() {
+        for op in BitwiseOp::ALL {
+            check::<4, 32>(10, op, 0xdead_beef, 0x1234_5678);
+            check::<4, 64>(10, op, u64::MAX as u128, 0x0f0f_0f0f_0f0f_0f0f);
+            check::<4, 4>(10, op, 0b1010, 0b0110);
+            check::<4, 128>(10, op, u128::MAX - 1, 1 << 100);
+        }
+    }
+
+    #[test]
+    fn byte_tables_test() {
+        check::<8, 32>(17, BitwiseOp::Xor, 0xdead_beef, 0xcafe_babe);
+    }
+
+    /// Proves `rotl(a, r) == c` for a public `c`, and that recombining the
+    /// chunks of `c` gives `c` back.
+    #[derive(Default)]
+    struct RotateCircuit<const BITS: usize, const NUM_BITS: usize> {
+        a: Value<u128>,
+        r: usize,
+    }
+
+    impl<const BITS: usize, const NUM_BITS: usize> Circuit<Fp> for RotateCircuit<BITS, NUM_BITS> {
+        type Config = (BitwiseConfig<Fp, BITS>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                r: self.r,
+                ..Self::default()
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            BitwiseCircuit::<BITS, NUM_BITS>::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let words = config.words;
+            let chip = BitwiseChip::construct(config);
+            chip.load_tables(layouter.namespace(|| "tables"))?;
+
+            let a = layouter.assign_region(
+                || "input",
+                |mut region| Ok(region.assign(words[0], 0, self.a.map(Fp::from_u128))),
+            )?;
+
+            let c = chip.rotate_left(layouter.namespace(|| "rotate"), &a, self.r, NUM_BITS)?;
+            layouter.constrain_instance(c.cell(), instance, 0);
+
+            if NUM_BITS.is_multiple_of(BITS) {
+                let chunks = chip.decompose(layouter.namespace(|| "decompose"), &c, NUM_BITS)?;
+                let c = chip.compose(layouter.namespace(|| "compose"), &chunks)?;
+                layouter.constrain_instance(c.cell(), instance, 0);
+            }
+            Ok(())
+        }
+    }
+
+    fn check_rotate<const BITS: usize, const NUM_BITS: usize>(a: u128, r: usize) {
+        let circuit = RotateCircuit::<BITS, NUM_BITS> {
+            a: Value::known(a),
+            r,
+        };
+        let mask = u128::MAX >> (128 - NUM_BITS);
+        let c = ((a << r) | (a >> ((NUM_BITS - r) % NUM_BITS))) & mask;
+
+        MockProver::run(10, &circuit, vec![vec![Fp::from_u128(c)]])
+            .unwrap()
+            .assert_satisfied();
+
+        assert!(
+            MockProver::run(10, &circuit, vec![vec![Fp::from_u128(c ^ 1)]])
+                .unwrap()
+                .verify()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn rotate_test() {
+        for r in [0, 1, 4, 7, 36, 62, 63] {
+            check_rotate::<4, 64>(0x0123_4567_89ab_cdef, r);
+        }
+
+        check_rotate::<4, 10>(0b10_1100_0111, 3);
+        check_rotate::<4, 128>(u128::MAX - 2, 100);
+    }
+
+    #[test]
+    fn rotate_out_of_range_test() {
+        // 2^10 rotated within 10 bits is 2^r, if the range were not checked
+        let circuit = RotateCircuit::<4, 10> {
+            a: Value::known(1 << 10),
+            r: 3,
+        };
+        for c in [1 << 3, 1 << 13] {
+            assert!(MockProver::run(10, &circuit, vec![vec![Fp::from(c)]])
+                .unwrap()
+                .verify()
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn input_out_of_range_test() {
+        // a 9-bit input does not decompose into two 4-bit chunks
+        let circuit = BitwiseCircuit::<4, 8> {
+            op: Some(BitwiseOp::Xor),
+            a: Value::known(0x1ff),
+            b: Value::known(0),
+        };
+        assert!(MockProver::run(10, &circuit, vec![vec![Fp::from(0xff)]])
+            .unwrap()
+            .verify()
+            .is_err());
+    }
+}
@@ -0,0 +1,55 @@
+use halo2_proofs::{
+    circuit::{Layouter, Value},
+    plonk::{ConstraintSystem, Error, TableColumn},
+};
+
+use super::BitwiseOp;
+use crate::field::FieldExt;
+
+/// A lookup table of `(a, b, a op b)` for every pair of `BITS`-bit values.
+/// BITS = 8 => 65536 rows
+#[derive(Clone, Debug)]
+pub struct BitwiseTable<F: FieldExt, const BITS: usize> {
+    pub(super) op: BitwiseOp,
+    pub(super) a: TableColumn,
+    pub(super) b: TableColumn,
+    pub(super) c: TableColumn,
+    _marker: std::marker::PhantomData<F>,
+}
+
+impl<F: FieldExt, const BITS: usize> BitwiseTable<F, BITS> {
+    pub(super) fn configure(meta: &mut ConstraintSystem<F>, op: BitwiseOp) -> Self {
+        Self {
+            op,
+            a: meta.lookup_table_column(),
+            b: meta.lookup_table_column(),
+            c: meta.lookup_table_column(),
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    pub(super) fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_table(
+            || format!("{} table", self.op),
+            |mut table| {
+                let mut offset = 0;
+                for a in 0..(1u64 << BITS) {
+                    for b in 0..(1u64 << BITS) {
+                        let c = self.op.apply(a as u128, b as u128) as u64;
+                        table.assign_cell(|| "a", self.a, offset, || Value::known(F::from(a)))?;
+                        table.assign_cell(|| "b", self.b, offset, || Value::known(F::from(b)))?;
+                        table.assign_cell(
+                            || "a op b",
+                            self.c,
+                            offset,
+                            || Value::known(F::from(c)),
+                        )?;
+                        offset += 1;
+                    }
+                }
+
+                Ok(())
+            },
+        )
+    }
+}
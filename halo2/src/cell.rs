@@ -0,0 +1,108 @@
+//! Assigned cells.
+//!
+//! halo2-axiom's `Region::assign_advice` returns a cell holding a reference to
+//! the value it stored, with a lifetime the caller picks. [`AssignedCell`] keeps
+//! its own copy of the value instead, so a cell never outlives what it points
+//! to, and [`RegionExt`] assigns cells of that type.
+
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::{Cell, Region, Value},
+    plonk::{Advice, Column, Error, Instance},
+};
+
+/// An advice cell with its value.
+#[derive(Clone, Debug)]
+pub struct AssignedCell<F: Field> {
+    value: Value<F>,
+    cell: Cell,
+}
+
+impl<F: Field> AssignedCell<F> {
+    /// The value of the cell, unknown when synthesizing without a witness.
+    pub fn value(&self) -> Value<&F> {
+        self.value.as_ref()
+    }
+
+    /// The value of the cell, by copy.
+    pub fn field_value(&self) -> Value<F> {
+        self.value
+    }
+
+    pub fn cell(&self) -> Cell {
+        self.cell
+    }
+
+    /// Copies the cell to `column` at `offset` and constrains both to be equal.
+    pub fn copy_to(
+        &self,
+        region: &mut Region<'_, F>,
+        column: Column<Advice>,
+        offset: usize,
+    ) -> AssignedCell<F> {
+        let cell = region.assign(column, offset, self.value);
+        region.constrain_equal(cell.cell(), self.cell());
+        cell
+    }
+}
+
+pub trait RegionExt<F: Field> {
+    /// Assigns `value` to `column` at `offset`.
+    fn assign(&mut self, column: Column<Advice>, offset: usize, value: Value<F>)
+        -> AssignedCell<F>;
+
+    /// Assigns `constant` to `column` at `offset`, constrained to the circuit
+    /// constant.
+    fn assign_constant(
+        &mut self,
+        column: Column<Advice>,
+        offset: usize,
+        constant: F,
+    ) -> Result<AssignedCell<F>, Error>;
+
+    /// Copies the public input at `row` of `instance` to `column` at `offset`.
+    fn assign_from_instance(
+        &mut self,
+        column: Column<Advice>,
+        offset: usize,
+        instance: Column<Instance>,
+        row: usize,
+    ) -> Result<AssignedCell<F>, Error>;
+}
+
+impl<F: Field> RegionExt<F> for Region<'_, F> {
+    fn assign(
+        &mut self,
+        column: Column<Advice>,
+        offset: usize,
+        value: Value<F>,
+    ) -> AssignedCell<F> {
+        let cell = self.assign_advice(column, offset, value).cell();
+        AssignedCell { value, cell }
+    }
+
+    fn assign_constant(
+        &mut self,
+        column: Column<Advice>,
+        offset: usize,
+        constant: F,
+    ) -> Result<AssignedCell<F>, Error> {
+        let cell = self.assign(column, offset, Value::known(constant));
+        self.constrain_constant(cell.cell(), constant)?;
+        Ok(cell)
+    }
+
+    fn assign_from_instance(
+        &mut self,
+        column: Column<Advice>,
+        offset: usize,
+        instance: Column<Instance>,
+        row: usize,
+    ) -> Result<AssignedCell<F>, Error> {
+        let cell = self.assign_advice_from_instance(|| "", instance, row, column, offset)?;
+        Ok(AssignedCell {
+            value: cell.value().copied(),
+            cell: cell.cell(),
+        })
+    }
+}
@@ -1,14 +1,38 @@
 use std::marker::PhantomData;
 
 use halo2_proofs::{
-    arithmetic::FieldExt,
-    circuit::{AssignedCell, Layouter},
+    circuit::{Chip, Layouter},
     plonk::*,
     poly::Rotation,
 };
 
-mod table;
-use table::*;
+use crate::cell::{AssignedCell, RegionExt};
+use crate::field::FieldExt;
+use crate::table::{TableChip, TableConfig, TableInstructions};
+
+/// Range checks cells by decomposing them into chunks of the table's width.
+pub trait DecomposeInstructions<F: FieldExt>: Chip<F> {
+    /// Loads the table of chunks; call once per circuit.
+    fn load_table(&self, layouter: impl Layouter<F>) -> Result<(), Error>;
+
+    /// Constrains `value` to `num_bits` bits, a multiple of the chunk width.
+    fn decompose(
+        &self,
+        layouter: impl Layouter<F>,
+        value: &AssignedCell<F>,
+        num_bits: usize,
+    ) -> Result<(), Error>;
+
+    /// Splits off the low `num_bits` bits of `value`, a multiple of the chunk
+    /// width, and returns the rest, `value >> num_bits`. Only the low bits are
+    /// range checked; the caller constrains the rest.
+    fn decompose_low(
+        &self,
+        layouter: impl Layouter<F>,
+        value: &AssignedCell<F>,
+        num_bits: usize,
+    ) -> Result<AssignedCell<F>, Error>;
+}
 
 /// This gadget range-constrains an element witnessed in the circuit to be N bits.
 ///
@@ -18,23 +42,30 @@ use table::*;
 /// Given an element `value`, we use a running sum to break it into K-bit chunks.
 /// Assume for now that N | K, and define C = N / K.
 ///
+/// ```text
 ///     value = [b_0, b_1, ..., b_{N-1}]   (little-endian)
 ///           = c_0 + 2^K * c_1  + 2^{2K} * c_2 + ... + 2^{(C-1)K} * c_{C-1}
+/// ```
 ///
 /// Initialise the running sum at
+/// ```text
 ///                                 value = z_0.
+/// ```
 ///
 /// Consequent terms of the running sum are z_{i+1} = (z_i - c_i) * 2^{-K}:
 ///
+/// ```text
 ///                           z_1 = (z_0 - c_0) * 2^{-K}
 ///                           z_2 = (z_1 - c_1) * 2^{-K}
 ///                              ...
 ///                       z_{C-1} = c_{C-1}
 ///                           z_C = (z_{C-1} - c_{C-1}) * 2^{-K}
 ///                               = 0
+/// ```
 ///
 /// One configuration for this gadget could look like:
 ///
+/// ```text
 ///     | running_sum |  q_decompose  |  table_value  |
 ///     -----------------------------------------------
 ///     |     z_0     |       1       |       0       |
@@ -42,26 +73,43 @@ use table::*;
 ///     |     ...     |      ...      |      ...      |
 ///     |   z_{C-1}   |       1       |      ...      |
 ///     |     z_C     |       0       |      ...      |
+/// ```
 ///
 /// Stretch task: use the tagged lookup table to constrain arbitrary bitlengths
 /// (even non-multiples of K)
 
 #[derive(Debug, Clone)]
-struct DecomposeConfig<F: FieldExt, const LOOKUP_NUM_BITS: usize> {
+pub struct DecomposeConfig<F: FieldExt, const LOOKUP_NUM_BITS: usize> {
     running_sum: Column<Advice>,
     q_decompose: Selector,
-    table: RangeCheckTable<F, LOOKUP_NUM_BITS>,
+    table: TableConfig<F, 1>,
     _marker: PhantomData<F>,
 }
 
-impl<F: FieldExt, const LOOKUP_NUM_BITS: usize> DecomposeConfig<F, LOOKUP_NUM_BITS> {
-    fn configure(meta: &mut ConstraintSystem<F>) -> Self {
-        let table = RangeCheckTable::configure(meta);
+pub struct DecomposeChip<F: FieldExt, const LOOKUP_NUM_BITS: usize> {
+    config: DecomposeConfig<F, LOOKUP_NUM_BITS>,
+}
+
+impl<F: FieldExt, const LOOKUP_NUM_BITS: usize> DecomposeChip<F, LOOKUP_NUM_BITS> {
+    pub fn construct(config: DecomposeConfig<F, LOOKUP_NUM_BITS>) -> Self {
+        Self { config }
+    }
+
+    /// `constants` must be a fixed column, which holds the final zero of the
+    /// running sums.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        running_sum: Column<Advice>,
+        constants: Column<Fixed>,
+    ) -> DecomposeConfig<F, LOOKUP_NUM_BITS> {
+        let table = TableChip::configure(meta);
         let q_decompose = meta.complex_selector();
-        let running_sum = meta.advice_column();
+
+        meta.enable_equality(running_sum);
+        meta.enable_constant(constants);
 
         // Range-constrain each K-bit chunk `c_i = z_i - z_{i+1} * 2^K` derived from the running sum.
-        meta.lookup(|meta| {
+        meta.lookup("decompose", |meta| {
             let q_decompose = meta.query_selector(q_decompose);
             let z = meta.query_advice(running_sum, Rotation::cur());
             let next_z = meta.query_advice(running_sum, Rotation::next());
@@ -69,44 +117,194 @@ impl<F: FieldExt, const LOOKUP_NUM_BITS: usize> DecomposeConfig<F, LOOKUP_NUM_BI
             let mut c = next_z * F::from(1 << LOOKUP_NUM_BITS);
             c = z - c;
 
-            vec![(q_decompose.clone() * c, table.value)]
+            table.lookup(q_decompose, &[c])
         });
-        Self {
+        DecomposeConfig {
             running_sum,
             q_decompose,
             table,
             _marker: PhantomData,
         }
     }
+}
+
+impl<F: FieldExt, const LOOKUP_NUM_BITS: usize> Chip<F> for DecomposeChip<F, LOOKUP_NUM_BITS> {
+    type Config = DecomposeConfig<F, LOOKUP_NUM_BITS>;
+    type Loaded = TableConfig<F, 1>;
 
-    fn assign(
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &self.config.table
+    }
+}
+
+impl<F: FieldExt, const LOOKUP_NUM_BITS: usize> DecomposeInstructions<F>
+    for DecomposeChip<F, LOOKUP_NUM_BITS>
+{
+    fn load_table(&self, layouter: impl Layouter<F>) -> Result<(), Error> {
+        TableChip::construct(self.config.table.clone()).load_fn(
+            layouter,
+            1 << LOOKUP_NUM_BITS,
+            |i| [F::from(i as u64)],
+        )
+    }
+
+    fn decompose(
         &self,
-        mut layouter: impl Layouter<F>,
-        value: AssignedCell<Assigned<F>, F>,
+        layouter: impl Layouter<F>,
+        value: &AssignedCell<F>,
         num_bits: usize,
     ) -> Result<(), Error> {
-        let mut z = value.clone();
+        self.running_sum(layouter, value, num_bits, true)?;
+        Ok(())
+    }
+
+    fn decompose_low(
+        &self,
+        layouter: impl Layouter<F>,
+        value: &AssignedCell<F>,
+        num_bits: usize,
+    ) -> Result<AssignedCell<F>, Error> {
+        self.running_sum(layouter, value, num_bits, false)
+    }
+}
+
+impl<F: FieldExt, const LOOKUP_NUM_BITS: usize> DecomposeChip<F, LOOKUP_NUM_BITS> {
+    /// The running sum of `value` over `num_bits` bits, returning its last
+    /// term, which is constrained to 0 if `strict`.
+    fn running_sum(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: &AssignedCell<F>,
+        num_bits: usize,
+        strict: bool,
+    ) -> Result<AssignedCell<F>, Error> {
+        assert_eq!(num_bits % LOOKUP_NUM_BITS, 0);
+        let num_chunks = num_bits / LOOKUP_NUM_BITS;
+        let running_sum = self.config.running_sum;
 
         layouter.assign_region(
-            || "assign z",
-            |mut r| {
-                let mut offset = 0;
-                let value = value.copy_advice(|| "z0", &mut r, self.running_sum, offset);
+            || "decompose value",
+            |mut region| {
+                // 0. Copy in the witnessed `value`
+                let mut z = value.copy_to(&mut region, running_sum, 0);
 
-                offset = offset + 1;
+                // 1. Compute the interstitial running sum values {z_0, ..., z_C}}
+                let chunks = value
+                    .field_value()
+                    .map(|v| chunks::<F, LOOKUP_NUM_BITS>(v, num_chunks));
+                let two_pow_k_inv = F::from(1 << LOOKUP_NUM_BITS).invert().unwrap();
 
-                let value = z.value().unwrap().evaluate().to_le_bytes();
+                for i in 0..num_chunks {
+                    // 3. Make sure to enable the relevant selector on each row of the running sum
+                    self.config.q_decompose.enable(&mut region, i)?;
 
-                // let z = (z.value().unwrap() - )
+                    // 2. Assign the running sum values
+                    let next_z = z
+                        .field_value()
+                        .zip(chunks.as_ref())
+                        .map(|(z, chunks)| (z - F::from(chunks[i])) * two_pow_k_inv);
+                    z = region.assign(running_sum, i + 1, next_z);
+                }
 
-                value
+                // 4. Constrain the final running sum `z_C` to be 0.
+                if strict {
+                    region.constrain_constant(z.cell(), F::ZERO)?;
+                }
+                Ok(z)
             },
-        )?;
-        // 0. Copy in the witnessed `value`
-        // 1. Compute the interstitial running sum values {z_0, ..., z_C}}
-        // 2. Assign the running sum values
-        // 3. Make sure to enable the relevant selector on each row of the running sum
-        // 4. Constrain the final running sum `z_C` to be 0.
-        todo!()
+        )
+    }
+}
+
+/// Splits the low `num_chunks * K` bits of `value` into little-endian K-bit chunks.
+fn chunks<F: FieldExt, const K: usize>(value: F, num_chunks: usize) -> Vec<u64> {
+    let repr = value.to_repr();
+    let bits: Vec<bool> = repr
+        .as_ref()
+        .iter()
+        .flat_map(|byte| (0..8).map(move |i| (byte >> i) & 1 == 1))
+        .collect();
+
+    bits.chunks(K)
+        .take(num_chunks)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .rev()
+                .fold(0u64, |acc, bit| (acc << 1) | *bit as u64)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::{circuit::Value, dev::MockProver, halo2curves::pasta::Fp};
+
+    use super::*;
+
+    use crate::planner::SimpleFloorPlanner;
+
+    #[derive(Default)]
+    struct DecomposeCircuit<F: FieldExt, const NUM_BITS: usize, const LOOKUP_NUM_BITS: usize> {
+        value: Value<F>,
+    }
+
+    impl<F: FieldExt, const NUM_BITS: usize, const LOOKUP_NUM_BITS: usize> Circuit<F>
+        for DecomposeCircuit<F, NUM_BITS, LOOKUP_NUM_BITS>
+    {
+        type Config = DecomposeConfig<F, LOOKUP_NUM_BITS>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let running_sum = meta.advice_column();
+            let constants = meta.fixed_column();
+            DecomposeChip::configure(meta, running_sum, constants)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let running_sum = config.running_sum;
+            let chip = DecomposeChip::construct(config);
+            chip.load_table(layouter.namespace(|| "load table"))?;
+
+            let value = layouter.assign_region(
+                || "witness value",
+                |mut region| Ok(region.assign(running_sum, 0, self.value)),
+            )?;
+
+            chip.decompose(layouter.namespace(|| "decompose"), &value, NUM_BITS)
+        }
+    }
+
+    #[test]
+    fn decompose_test() {
+        for value in [0u64, 1, 0xabc, (1 << 12) - 1] {
+            let circuit = DecomposeCircuit::<Fp, 12, 3> {
+                value: Value::known(Fp::from(value)),
+            };
+            let prover = MockProver::run(8, &circuit, vec![]).unwrap();
+            prover.assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn decompose_out_of_range_test() {
+        let circuit = DecomposeCircuit::<Fp, 12, 3> {
+            value: Value::known(Fp::from(1 << 12)),
+        };
+        let prover = MockProver::run(8, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
     }
 }
@@ -0,0 +1,477 @@
+//! Field division and `u64` division with remainder.
+//!
+//! circom's `division.circom` checks `y1 === y2 * x3`, which any `y2`
+//! satisfies when `x3` and `y1` are zero. [`DivisionChip::div`] witnesses the
+//! inverse of the divisor as well, and a zero divisor has none:
+//!
+//! ```text
+//!  a | b | q | inv | t | selector
+//!  a   b   q   1/b       q_div:    b·inv = 1, q = a·inv
+//!  a   b   q   r     t   q_divmod: a = q·b + r, t = b - 1 - r
+//! ```
+//!
+//! [`DivisionChip::divmod`] range checks `q`, `r` and `t` to 64 bits on top of
+//! an [`IntegerChip`], so `r < b`, and `q·b + r` is far smaller than `p`: the
+//! equation holds over the integers and `q` and `r` are unique. A zero divisor
+//! leaves `t = -1 - r`, which is not 64 bits long.
+//!
+//! Either way an honest prover finds out about a zero divisor while
+//! synthesizing, from a [`DivisionError`], not from a failing proof.
+
+use std::fmt;
+
+use halo2_proofs::{
+    circuit::{Chip, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+
+use crate::cell::{AssignedCell, RegionExt};
+use crate::field::FieldExt;
+use crate::integer::{AssignedU64, ByteTable, IntegerChip, IntegerConfig, IntegerInstructions};
+
+#[derive(Debug)]
+pub enum DivisionError {
+    DivisionByZero,
+    Synthesis(Error),
+}
+
+impl fmt::Display for DivisionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DivisionError::DivisionByZero => write!(f, "division by zero"),
+            DivisionError::Synthesis(e) => write!(f, "synthesis failed: {:?}", e),
+        }
+    }
+}
+
+impl std::error::Error for DivisionError {}
+
+impl From<Error> for DivisionError {
+    fn from(e: Error) -> Self {
+        DivisionError::Synthesis(e)
+    }
+}
+
+/// For `?` in `Circuit::synthesize`, which can only fail with halo2's errors.
+impl From<DivisionError> for Error {
+    fn from(e: DivisionError) -> Self {
+        match e {
+            DivisionError::DivisionByZero => Error::Synthesis,
+            DivisionError::Synthesis(e) => e,
+        }
+    }
+}
+
+/// `a / b` in the field, as the circuit computes it.
+pub fn div<F: FieldExt>(a: F, b: F) -> Result<F, DivisionError> {
+    Option::from(b.invert())
+        .map(|inv: F| a * inv)
+        .ok_or(DivisionError::DivisionByZero)
+}
+
+/// The quotient and remainder of `a / b`, as the circuit computes them.
+pub fn divmod(a: u64, b: u64) -> Result<(u64, u64), DivisionError> {
+    match b {
+        0 => Err(DivisionError::DivisionByZero),
+        _ => Ok((a / b, a % b)),
+    }
+}
+
+/// Field division and integer division with remainder.
+pub trait DivisionInstructions<F: FieldExt>: Chip<F> {
+    /// Loads the byte table of the range checks; call once per circuit.
+    fn load_table(&self, layouter: impl Layouter<F>) -> Result<(), Error>;
+
+    /// `a / b` in the field. The circuit is unsatisfiable if `b` is zero.
+    fn div(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F>,
+        b: &AssignedCell<F>,
+    ) -> Result<AssignedCell<F>, DivisionError>;
+
+    /// The quotient and remainder of `a / b`. The circuit is unsatisfiable if
+    /// `b` is zero.
+    fn divmod(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedU64<F>,
+        b: &AssignedU64<F>,
+    ) -> Result<(AssignedU64<F>, AssignedU64<F>), DivisionError>;
+}
+
+#[derive(Clone, Debug)]
+pub struct DivisionConfig<F: FieldExt> {
+    integer: IntegerConfig<F>,
+    /// `a, b, q, inv or r, t` as in the module documentation
+    advice: [Column<Advice>; 5],
+    q_div: Selector,
+    q_divmod: Selector,
+}
+
+pub struct DivisionChip<F: FieldExt> {
+    config: DivisionConfig<F>,
+    integer: IntegerChip<F>,
+}
+
+impl<F: FieldExt> Chip<F> for DivisionChip<F> {
+    type Config = DivisionConfig<F>;
+    type Loaded = ByteTable<F>;
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        self.integer.loaded()
+    }
+}
+
+impl<F: FieldExt> DivisionChip<F> {
+    pub fn construct(config: DivisionConfig<F>) -> Self {
+        Self {
+            integer: IntegerChip::construct(config.integer.clone()),
+            config,
+        }
+    }
+
+    /// Configures an [`IntegerChip`] for the range checks on the same columns.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 6],
+        constants: Column<Fixed>,
+    ) -> DivisionConfig<F> {
+        let integer = IntegerChip::configure(meta, advice, constants);
+        let advice = [advice[0], advice[1], advice[2], advice[3], advice[4]];
+        let q_div = meta.selector();
+        let q_divmod = meta.selector();
+
+        meta.create_gate("field div", |meta| {
+            let q_div = meta.query_selector(q_div);
+            let [a, b, q, inv] =
+                [0, 1, 2, 3].map(|i| meta.query_advice(advice[i], Rotation::cur()));
+            vec![
+                q_div.clone() * (b * inv.clone() - Expression::Constant(F::ONE)),
+                q_div * (q - a * inv),
+            ]
+        });
+
+        meta.create_gate("divmod", |meta| {
+            let q_divmod = meta.query_selector(q_divmod);
+            let [a, b, q, r, t] = advice.map(|column| meta.query_advice(column, Rotation::cur()));
+            vec![
+                q_divmod.clone() * (a - (q * b.clone() + r.clone())),
+                q_divmod * (t - (b - Expression::Constant(F::ONE) - r)),
+            ]
+        });
+
+        DivisionConfig {
+            integer,
+            advice,
+            q_div,
+            q_divmod,
+        }
+    }
+
+    /// The [`IntegerChip`] the range checks run on, e.g. to witness the
+    /// operands of [`DivisionChip::divmod`].
+    pub fn integer(&self) -> &IntegerChip<F> {
+        &self.integer
+    }
+
+    fn assign_div(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<F>,
+        b: &AssignedCell<F>,
+        q: Value<F>,
+        inv: Value<F>,
+    ) -> Result<AssignedCell<F>, Error> {
+        let advice = self.config.advice;
+        layouter.assign_region(
+            || "field div",
+            |mut region| {
+                self.config.q_div.enable(&mut region, 0)?;
+                a.copy_to(&mut region, advice[0], 0);
+                b.copy_to(&mut region, advice[1], 0);
+                region.assign(advice[3], 0, inv);
+                Ok(region.assign(advice[2], 0, q))
+            },
+        )
+    }
+
+    fn assign_divmod(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedU64<F>,
+        b: &AssignedU64<F>,
+        q: Value<u64>,
+        r: Value<u64>,
+    ) -> Result<(AssignedU64<F>, AssignedU64<F>), Error> {
+        let advice = self.config.advice;
+        let t = b
+            .value()
+            .zip(r)
+            .map(|(b, r)| F::from(b) - F::ONE - F::from(r));
+
+        let [q, r, t] = layouter.assign_region(
+            || "divmod",
+            |mut region| {
+                self.config.q_divmod.enable(&mut region, 0)?;
+                a.0.copy_to(&mut region, advice[0], 0);
+                b.0.copy_to(&mut region, advice[1], 0);
+                let q = region.assign(advice[2], 0, q.map(F::from));
+                let r = region.assign(advice[3], 0, r.map(F::from));
+                let t = region.assign(advice[4], 0, t);
+                Ok([q, r, t])
+            },
+        )?;
+
+        let q = self.integer.range_check(layouter.namespace(|| "q"), &q)?;
+        let r = self.integer.range_check(layouter.namespace(|| "r"), &r)?;
+        self.integer
+            .range_check::<64>(layouter.namespace(|| "b - 1 - r"), &t)?;
+        Ok((q, r))
+    }
+}
+
+impl<F: FieldExt> DivisionInstructions<F> for DivisionChip<F> {
+    fn load_table(&self, layouter: impl Layouter<F>) -> Result<(), Error> {
+        self.integer.load_table(layouter)
+    }
+
+    fn div(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F>,
+        b: &AssignedCell<F>,
+    ) -> Result<AssignedCell<F>, DivisionError> {
+        let b_value = b.field_value();
+        b_value
+            .error_if_known_and(|b| bool::from(b.is_zero()))
+            .map_err(|_| DivisionError::DivisionByZero)?;
+        let q = a
+            .field_value()
+            .zip(b_value)
+            .map(|(a, b)| div(a, b).unwrap());
+        let inv = b_value.map(|b| b.invert().unwrap());
+        Ok(self.assign_div(layouter, a, b, q, inv)?)
+    }
+
+    fn divmod(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedU64<F>,
+        b: &AssignedU64<F>,
+    ) -> Result<(AssignedU64<F>, AssignedU64<F>), DivisionError> {
+        let values = a.value().zip(b.value());
+        values
+            .error_if_known_and(|(_, b)| *b == 0)
+            .map_err(|_| DivisionError::DivisionByZero)?;
+        let values = values.map(|(a, b)| divmod(a, b).unwrap());
+        Ok(self.assign_divmod(
+            layouter,
+            a,
+            b,
+            values.map(|(q, _)| q),
+            values.map(|(_, r)| r),
+        )?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::{
+        arithmetic::Field,
+        dev::MockProver,
+        halo2curves::pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    use super::*;
+
+    use crate::planner::SimpleFloorPlanner;
+
+    /// Divides `a` by `b` in the field and as integers, exposing the quotient,
+    /// the integer quotient and the remainder. `forged` replaces the witnesses
+    /// the chip would compute.
+    #[derive(Clone, Default)]
+    struct DivisionCircuit {
+        a: Value<u64>,
+        b: Value<u64>,
+        forged: Option<Forged>,
+    }
+
+    #[derive(Clone, Copy)]
+    struct Forged {
+        q: Fp,
+        inv: Fp,
+        int_q: u64,
+        r: u64,
+    }
+
+    impl Circuit<Fp> for DivisionCircuit {
+        type Config = (DivisionConfig<Fp>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 6].map(|_| meta.advice_column());
+            let constants = meta.fixed_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (DivisionChip::configure(meta, advice, constants), instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = DivisionChip::construct(config);
+            chip.load_table(layouter.namespace(|| "bytes"))?;
+            let a = chip.integer().witness(layouter.namespace(|| "a"), self.a)?;
+            let b = chip.integer().witness(layouter.namespace(|| "b"), self.b)?;
+
+            let (q, (int_q, r)) = match self.forged {
+                Some(f) => (
+                    chip.assign_div(
+                        layouter.namespace(|| "div"),
+                        &a.0,
+                        &b.0,
+                        Value::known(f.q),
+                        Value::known(f.inv),
+                    )?,
+                    chip.assign_divmod(
+                        layouter.namespace(|| "divmod"),
+                        &a,
+                        &b,
+                        Value::known(f.int_q),
+                        Value::known(f.r),
+                    )?,
+                ),
+                None => (
+                    chip.div(layouter.namespace(|| "div"), &a.0, &b.0)?,
+                    chip.divmod(layouter.namespace(|| "divmod"), &a, &b)?,
+                ),
+            };
+
+            layouter.constrain_instance(q.cell(), instance, 0);
+            layouter.constrain_instance(int_q.0.cell(), instance, 1);
+            layouter.constrain_instance(r.0.cell(), instance, 2);
+            Ok(())
+        }
+    }
+
+    fn instances(a: u64, b: u64) -> Vec<Vec<Fp>> {
+        let q = div(Fp::from(a), Fp::from(b)).unwrap();
+        let (int_q, r) = divmod(a, b).unwrap();
+        vec![vec![q, Fp::from(int_q), Fp::from(r)]]
+    }
+
+    fn prove(circuit: &DivisionCircuit, instances: Vec<Vec<Fp>>) -> Result<(), ()> {
+        MockProver::run(10, circuit, instances)
+            .unwrap()
+            .verify()
+            .map_err(|_| ())
+    }
+
+    #[test]
+    fn native_test() {
+        assert_eq!(divmod(7, 2).unwrap(), (3, 1));
+        assert_eq!(divmod(0, 5).unwrap(), (0, 0));
+        assert!(matches!(divmod(7, 0), Err(DivisionError::DivisionByZero)));
+
+        let q = div(Fp::from(7), Fp::from(2)).unwrap();
+        assert_eq!(q * Fp::from(2), Fp::from(7));
+        assert!(matches!(
+            div(Fp::from(7), Fp::ZERO),
+            Err(DivisionError::DivisionByZero)
+        ));
+    }
+
+    #[test]
+    fn division_test() {
+        for (a, b) in [(7, 2), (0, 5), (6, 3), (1, u64::MAX), (u64::MAX, 1)] {
+            let circuit = DivisionCircuit {
+                a: Value::known(a),
+                b: Value::known(b),
+                forged: None,
+            };
+            assert_eq!(prove(&circuit, instances(a, b)), Ok(()));
+        }
+
+        // the quotient and remainder swapped
+        let circuit = DivisionCircuit {
+            a: Value::known(7),
+            b: Value::known(2),
+            forged: None,
+        };
+        let mut swapped = instances(7, 2);
+        swapped[0].swap(1, 2);
+        assert!(prove(&circuit, swapped).is_err());
+    }
+
+    #[test]
+    fn division_by_zero_test() {
+        // the prover finds out while synthesizing
+        let circuit = DivisionCircuit {
+            a: Value::known(7),
+            b: Value::known(0),
+            forged: None,
+        };
+        assert!(matches!(
+            MockProver::run(10, &circuit, vec![vec![Fp::ZERO; 3]]),
+            Err(Error::Synthesis)
+        ));
+
+        // and cannot forge a witness: 0·inv is never 1, and b - 1 - r is negative
+        for (q, int_q, r) in [(Fp::ZERO, 0, 7), (Fp::from(5), 5, 0)] {
+            let circuit = DivisionCircuit {
+                forged: Some(Forged {
+                    q,
+                    inv: Fp::from(3),
+                    int_q,
+                    r,
+                }),
+                ..circuit.clone()
+            };
+            let instances = vec![vec![q, Fp::from(int_q), Fp::from(r)]];
+            assert!(prove(&circuit, instances).is_err());
+        }
+    }
+
+    #[test]
+    fn forged_remainder_test() {
+        // 7 = 2·2 + 3, but 3 is not smaller than 2
+        let q = div(Fp::from(7), Fp::from(2)).unwrap();
+        let circuit = DivisionCircuit {
+            a: Value::known(7),
+            b: Value::known(2),
+            forged: Some(Forged {
+                q,
+                inv: Fp::from(2).invert().unwrap(),
+                int_q: 2,
+                r: 3,
+            }),
+        };
+        assert!(prove(&circuit, vec![vec![q, Fp::from(2), Fp::from(3)]]).is_err());
+
+        // the honest quotient and remainder pass the same way
+        let circuit = DivisionCircuit {
+            forged: Some(Forged {
+                q,
+                inv: Fp::from(2).invert().unwrap(),
+                int_q: 3,
+                r: 1,
+            }),
+            ..circuit
+        };
+        assert_eq!(prove(&circuit, instances(7, 2)), Ok(()));
+    }
+}
@@ -0,0 +1,737 @@
+//! Pallas points and scalar multiplication in a circuit over its base field.
+//!
+//! A circuit over `Fp` works natively with Pallas coordinates, so a point is two
+//! cells and every doubling or addition is one row of affine formulas with the
+//! slope as a witness. The formulas break down when adding `q = ±p`, so every
+//! addition also witnesses the inverse of `x_q - x_p`, making such a row
+//! unsatisfiable instead of letting the prover pick any result. An honest prover
+//! only runs into that case with negligible probability.
+//!
+//! Scalars are given as bits, most significant first. A variable-base
+//! multiplication doubles and conditionally adds the base once per bit:
+//!
+//! ```text
+//!  x, y  | x2, y2 | x_q, y_q | λ_double | λ_add | inv | bit | scalar | q_double | q_add
+//!  A_0     2·A_0    P          ...        ...     ...   b_0   1          1          1
+//!  A_1     2·A_1    P                                   b_1   2 + b_0    1          1
+//!  ...
+//!  A_n                                                        scalar
+//! ```
+//!
+//! with `A_{i+1} = 2·A_i + b_i·P`. A fixed-base multiplication instead adds the
+//! precomputed multiples `2^i·G` from fixed columns, starting from an offset
+//! point that is subtracted again at the end.
+
+use group::ff::PrimeField;
+use group::Curve;
+use halo2_proofs::{
+    arithmetic::{CurveAffine, CurveExt, Field},
+    circuit::{Chip, Layouter, Region, Value},
+    halo2curves::{pasta::pallas, Coordinates},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+
+use crate::cell::{AssignedCell, RegionExt};
+use crate::pasta::Fp;
+
+/// A point other than the identity, which has no affine coordinates.
+#[derive(Clone, Debug)]
+pub struct EccPoint {
+    pub x: AssignedCell<Fp>,
+    pub y: AssignedCell<Fp>,
+}
+
+impl EccPoint {
+    /// The point, or the identity if the cells are not on the curve.
+    pub fn value(&self) -> Value<pallas::Affine> {
+        self.x
+            .field_value()
+            .zip(self.y.field_value())
+            .map(|(x, y)| Option::from(pallas::Affine::from_xy(x.0, y.0)).unwrap_or_default())
+    }
+}
+
+/// The bits of `scalar`, most significant first.
+pub fn scalar_bits(scalar: &pallas::Scalar) -> Vec<bool> {
+    let repr = scalar.to_repr();
+    (0..pallas::Scalar::NUM_BITS as usize)
+        .rev()
+        .map(|i| (repr.as_ref()[i / 8] >> (i % 8)) & 1 == 1)
+        .collect()
+}
+
+/// The point a fixed-base multiplication starts from, so the accumulator is
+/// never the identity.
+fn offset() -> pallas::Affine {
+    pallas::Point::hash_to_curve("zkG:ecc")(b"fixed-base offset").to_affine()
+}
+
+/// `2^(n - 1)·base, ..., 2·base, base`, the multiples a fixed-base
+/// multiplication adds for `n` bits.
+fn multiples(base: pallas::Affine, n: usize) -> Vec<pallas::Affine> {
+    let mut multiples = vec![base];
+    for _ in 1..n {
+        let last = multiples[multiples.len() - 1];
+        multiples.push((last + last).to_affine());
+    }
+    multiples.reverse();
+    multiples
+}
+
+fn coordinates(point: pallas::Affine) -> Option<(Fp, Fp)> {
+    let coordinates: Option<Coordinates<_>> = point.coordinates().into();
+    coordinates.map(|c| (Fp(*c.x()), Fp(*c.y())))
+}
+
+/// The coordinates of a witnessed point, with the identity as `(0, 0)`, which
+/// fails the constraints of every row it is used in.
+fn witness_coordinates(point: Value<pallas::Affine>) -> Value<(Fp, Fp)> {
+    point.map(|point| coordinates(point).unwrap_or((Fp::ZERO, Fp::ZERO)))
+}
+
+/// Pallas points: addition and scalar multiplication.
+pub trait EccInstructions: Chip<Fp> {
+    /// Assigns a private point, constrained to be on the curve.
+    fn witness_point(
+        &self,
+        layouter: impl Layouter<Fp>,
+        value: Value<pallas::Affine>,
+    ) -> Result<EccPoint, Error>;
+
+    /// Assigns a point fixed by the circuit.
+    fn constant_point(
+        &self,
+        layouter: impl Layouter<Fp>,
+        value: pallas::Affine,
+    ) -> Result<EccPoint, Error>;
+
+    /// `p + q`, for `q != ±p`.
+    fn add(
+        &self,
+        layouter: impl Layouter<Fp>,
+        p: &EccPoint,
+        q: &EccPoint,
+    ) -> Result<EccPoint, Error>;
+
+    /// Constrains `p == q`.
+    fn constrain_equal(
+        &self,
+        layouter: impl Layouter<Fp>,
+        p: &EccPoint,
+        q: &EccPoint,
+    ) -> Result<(), Error>;
+
+    /// `[k]base` for the scalar `k` whose binary representation is a one
+    /// followed by `bits`, so `k = 2^n + Σ bits[i]·2^(n - 1 - i)` for `n` bits.
+    /// Also returns `k` as a cell, which is exact as long as `k < p`.
+    fn mul(
+        &self,
+        layouter: impl Layouter<Fp>,
+        base: &EccPoint,
+        bits: &[Value<bool>],
+    ) -> Result<(EccPoint, AssignedCell<Fp>), Error>;
+
+    /// `[k]base` for the scalar `k = Σ bits[i]·2^(n - 1 - i)` of `n` bits, with
+    /// the multiples of `base` in fixed columns. Also returns `k` as a cell,
+    /// which is exact as long as `k < p`.
+    fn mul_fixed(
+        &self,
+        layouter: impl Layouter<Fp>,
+        base: pallas::Affine,
+        bits: &[Value<bool>],
+    ) -> Result<(EccPoint, AssignedCell<Fp>), Error>;
+
+    /// `Σ [k_j]base_j` for fixed bases, with every scalar given as for
+    /// [`EccChip::mul_fixed`], and the scalars as cells. The terms share one
+    /// accumulator, so only the sum must not be the identity.
+    fn mul_fixed_sum(
+        &self,
+        layouter: impl Layouter<Fp>,
+        terms: &[(pallas::Affine, &[Value<bool>])],
+    ) -> Result<(EccPoint, Vec<AssignedCell<Fp>>), Error>;
+}
+
+#[derive(Clone, Debug)]
+pub struct EccConfig {
+    /// the accumulator, or a single point
+    acc: [Column<Advice>; 2],
+    /// the accumulator after doubling, or the first operand of an addition
+    double: [Column<Advice>; 2],
+    /// the point added to it
+    addend: [Column<Advice>; 2],
+    lambda_double: Column<Advice>,
+    lambda_add: Column<Advice>,
+    /// `1 / (x_q - x_p)`
+    inv: Column<Advice>,
+    bit: Column<Advice>,
+    /// the scalar, recombined up to the current bit
+    scalar: Column<Advice>,
+    /// the multiple of the fixed base added in this row
+    fixed_base: [Column<Fixed>; 2],
+    q_on_curve: Selector,
+    q_double: Selector,
+    q_add: Selector,
+    q_add_fixed: Selector,
+    q_scalar: Selector,
+}
+
+pub struct EccChip {
+    config: EccConfig,
+}
+
+impl Chip<Fp> for EccChip {
+    type Config = EccConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl EccChip {
+    pub fn construct(config: EccConfig) -> Self {
+        Self { config }
+    }
+
+    /// `advice` holds the columns in the order of the table in the module
+    /// documentation: `x, y, x2, y2, x_q, y_q, λ_double, λ_add, inv, bit, scalar`.
+    pub fn configure(
+        meta: &mut ConstraintSystem<Fp>,
+        advice: [Column<Advice>; 11],
+        constants: Column<Fixed>,
+    ) -> EccConfig {
+        let [x, y, x2, y2, xq, yq, lambda_double, lambda_add, inv, bit, scalar] = advice;
+        let fixed_base = [meta.fixed_column(), meta.fixed_column()];
+        let q_on_curve = meta.selector();
+        let q_double = meta.selector();
+        let q_add = meta.selector();
+        let q_add_fixed = meta.selector();
+        let q_scalar = meta.selector();
+
+        meta.enable_constant(constants);
+        for column in [x, y, x2, y2, xq, yq, bit, scalar] {
+            meta.enable_equality(column);
+        }
+
+        meta.create_gate("on curve", |meta| {
+            let q_on_curve = meta.query_selector(q_on_curve);
+            let x = meta.query_advice(x, Rotation::cur());
+            let y = meta.query_advice(y, Rotation::cur());
+            let b = Expression::Constant(Fp(pallas::Affine::b()));
+            vec![q_on_curve * (y.square() - (x.clone().square() * x + b))]
+        });
+
+        meta.create_gate("double", |meta| {
+            let q_double = meta.query_selector(q_double);
+            let x = meta.query_advice(x, Rotation::cur());
+            let y = meta.query_advice(y, Rotation::cur());
+            let x2 = meta.query_advice(x2, Rotation::cur());
+            let y2 = meta.query_advice(y2, Rotation::cur());
+            let lambda = meta.query_advice(lambda_double, Rotation::cur());
+            let two = Expression::Constant(Fp::from(2));
+            let three = Expression::Constant(Fp::from(3));
+            vec![
+                q_double.clone()
+                    * (lambda.clone() * two.clone() * y.clone() - three * x.clone().square()),
+                q_double.clone() * (x2.clone() - (lambda.clone().square() - two * x.clone())),
+                q_double * (y2 - (lambda * (x - x2) - y)),
+            ]
+        });
+
+        meta.create_gate("add", |meta| {
+            let q_add = meta.query_selector(q_add);
+            let p = [x2, y2].map(|column| meta.query_advice(column, Rotation::cur()));
+            let q = [xq, yq].map(|column| meta.query_advice(column, Rotation::cur()));
+            let out = [x, y].map(|column| meta.query_advice(column, Rotation::next()));
+            let bit = meta.query_advice(bit, Rotation::cur());
+            let lambda = meta.query_advice(lambda_add, Rotation::cur());
+            let inv = meta.query_advice(inv, Rotation::cur());
+            conditional_add(p, q, bit, lambda, inv, out)
+                .into_iter()
+                .map(|poly| q_add.clone() * poly)
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("add fixed", |meta| {
+            let q_add_fixed = meta.query_selector(q_add_fixed);
+            let p = [x, y].map(|column| meta.query_advice(column, Rotation::cur()));
+            let q = fixed_base.map(|column| meta.query_fixed(column, Rotation::cur()));
+            let out = [x, y].map(|column| meta.query_advice(column, Rotation::next()));
+            let bit = meta.query_advice(bit, Rotation::cur());
+            let lambda = meta.query_advice(lambda_add, Rotation::cur());
+            let inv = meta.query_advice(inv, Rotation::cur());
+            conditional_add(p, q, bit, lambda, inv, out)
+                .into_iter()
+                .map(|poly| q_add_fixed.clone() * poly)
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("scalar", |meta| {
+            let q_scalar = meta.query_selector(q_scalar);
+            let bit = meta.query_advice(bit, Rotation::cur());
+            let cur = meta.query_advice(scalar, Rotation::cur());
+            let next = meta.query_advice(scalar, Rotation::next());
+            vec![q_scalar * (next - (cur * Expression::Constant(Fp::from(2)) + bit))]
+        });
+
+        EccConfig {
+            acc: [x, y],
+            double: [x2, y2],
+            addend: [xq, yq],
+            lambda_double,
+            lambda_add,
+            inv,
+            bit,
+            scalar,
+            fixed_base,
+            q_on_curve,
+            q_double,
+            q_add,
+            q_add_fixed,
+            q_scalar,
+        }
+    }
+
+    fn assign_acc(
+        &self,
+        region: &mut Region<'_, Fp>,
+        row: usize,
+        value: Value<pallas::Affine>,
+    ) -> Result<EccPoint, Error> {
+        let xy = witness_coordinates(value);
+        let [x, y] =
+            [0, 1].map(|i| region.assign(self.config.acc[i], row, xy.map(|xy| [xy.0, xy.1][i])));
+        Ok(EccPoint { x, y })
+    }
+
+    /// Assigns the slope of `p + q` and the inverse of `x_q - x_p`, and returns
+    /// `p + bit·q`. Both are only constrained if `bit` is set.
+    fn assign_slope(
+        &self,
+        region: &mut Region<'_, Fp>,
+        row: usize,
+        p: Value<pallas::Affine>,
+        q: Value<pallas::Affine>,
+        bit: Value<bool>,
+    ) -> Result<Value<pallas::Affine>, Error> {
+        let pq = witness_coordinates(p).zip(witness_coordinates(q));
+        let inv = pq.map(|((xp, _), (xq, _))| (xq - xp).invert().unwrap_or(Fp::ZERO));
+        let lambda = pq.zip(inv).map(|(((_, yp), (_, yq)), inv)| (yq - yp) * inv);
+
+        region.assign(self.config.lambda_add, row, lambda);
+        region.assign(self.config.inv, row, inv);
+
+        Ok(p.zip(q)
+            .zip(bit)
+            .map(|((p, q), bit)| if bit { (p + q).to_affine() } else { p }))
+    }
+
+    /// Assigns `bit` and returns the scalar recombined up to it, in the next row.
+    fn assign_bit(
+        &self,
+        region: &mut Region<'_, Fp>,
+        row: usize,
+        bit: Value<bool>,
+        scalar: &AssignedCell<Fp>,
+    ) -> Result<AssignedCell<Fp>, Error> {
+        let bit = bit.map(|bit| Fp::from(bit as u64));
+        region.assign(self.config.bit, row, bit);
+
+        let next = scalar.field_value().zip(bit).map(|(s, b)| s.double() + b);
+        Ok(region.assign(self.config.scalar, row + 1, next))
+    }
+}
+
+impl EccInstructions for EccChip {
+    fn witness_point(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        value: Value<pallas::Affine>,
+    ) -> Result<EccPoint, Error> {
+        layouter.assign_region(
+            || "witness point",
+            |mut region| {
+                self.config.q_on_curve.enable(&mut region, 0)?;
+                self.assign_acc(&mut region, 0, value)
+            },
+        )
+    }
+
+    fn constant_point(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        value: pallas::Affine,
+    ) -> Result<EccPoint, Error> {
+        let (x, y) = coordinates(value).ok_or(Error::Synthesis)?;
+        layouter.assign_region(
+            || "constant point",
+            |mut region| {
+                let [x_column, y_column] = self.config.acc;
+                Ok(EccPoint {
+                    x: region.assign_constant(x_column, 0, x)?,
+                    y: region.assign_constant(y_column, 0, y)?,
+                })
+            },
+        )
+    }
+
+    fn add(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        p: &EccPoint,
+        q: &EccPoint,
+    ) -> Result<EccPoint, Error> {
+        layouter.assign_region(
+            || "add",
+            |mut region| {
+                self.config.q_add.enable(&mut region, 0)?;
+                for (cell, column) in [&p.x, &p.y].into_iter().zip(self.config.double) {
+                    cell.copy_to(&mut region, column, 0);
+                }
+                for (cell, column) in [&q.x, &q.y].into_iter().zip(self.config.addend) {
+                    cell.copy_to(&mut region, column, 0);
+                }
+                region.assign_constant(self.config.bit, 0, Fp::ONE)?;
+
+                let sum =
+                    self.assign_slope(&mut region, 0, p.value(), q.value(), Value::known(true))?;
+                self.assign_acc(&mut region, 1, sum)
+            },
+        )
+    }
+
+    fn constrain_equal(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        p: &EccPoint,
+        q: &EccPoint,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "constrain equal",
+            |mut region| {
+                let x = p.x.copy_to(&mut region, self.config.acc[0], 0);
+                let y = p.y.copy_to(&mut region, self.config.acc[1], 0);
+                region.constrain_equal(x.cell(), q.x.cell());
+                region.constrain_equal(y.cell(), q.y.cell());
+                Ok(())
+            },
+        )
+    }
+
+    fn mul(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        base: &EccPoint,
+        bits: &[Value<bool>],
+    ) -> Result<(EccPoint, AssignedCell<Fp>), Error> {
+        layouter.assign_region(
+            || format!("mul {} bits", bits.len() + 1),
+            |mut region| {
+                let mut acc = EccPoint {
+                    x: base.x.copy_to(&mut region, self.config.acc[0], 0),
+                    y: base.y.copy_to(&mut region, self.config.acc[1], 0),
+                };
+                let mut scalar = region.assign_constant(self.config.scalar, 0, Fp::ONE)?;
+
+                for (row, bit) in bits.iter().enumerate() {
+                    self.config.q_double.enable(&mut region, row)?;
+                    self.config.q_add.enable(&mut region, row)?;
+                    self.config.q_scalar.enable(&mut region, row)?;
+
+                    let doubled = acc.value().map(|acc| (acc + acc).to_affine());
+                    let slope = witness_coordinates(acc.value()).map(|(x, y)| {
+                        x.square() * Fp::from(3) * y.double().invert().unwrap_or(Fp::ZERO)
+                    });
+                    region.assign(self.config.lambda_double, row, slope);
+                    let doubled_xy = witness_coordinates(doubled);
+                    for (i, column) in self.config.double.iter().enumerate() {
+                        region.assign(*column, row, doubled_xy.map(|xy| [xy.0, xy.1][i]));
+                    }
+                    base.x.copy_to(&mut region, self.config.addend[0], row);
+                    base.y.copy_to(&mut region, self.config.addend[1], row);
+
+                    let sum = self.assign_slope(&mut region, row, doubled, base.value(), *bit)?;
+                    scalar = self.assign_bit(&mut region, row, *bit, &scalar)?;
+                    acc = self.assign_acc(&mut region, row + 1, sum)?;
+                }
+
+                Ok((acc, scalar))
+            },
+        )
+    }
+
+    fn mul_fixed(
+        &self,
+        layouter: impl Layouter<Fp>,
+        base: pallas::Affine,
+        bits: &[Value<bool>],
+    ) -> Result<(EccPoint, AssignedCell<Fp>), Error> {
+        let (point, mut scalars) = self.mul_fixed_sum(layouter, &[(base, bits)])?;
+        Ok((point, scalars.remove(0)))
+    }
+
+    fn mul_fixed_sum(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        terms: &[(pallas::Affine, &[Value<bool>])],
+    ) -> Result<(EccPoint, Vec<AssignedCell<Fp>>), Error> {
+        let num_bits: usize = terms.iter().map(|(_, bits)| bits.len()).sum();
+        let (acc, scalars) = layouter.assign_region(
+            || format!("mul fixed {} bits", num_bits),
+            |mut region| {
+                let (x, y) = coordinates(offset()).ok_or(Error::Synthesis)?;
+                let mut acc = EccPoint {
+                    x: region.assign_constant(self.config.acc[0], 0, x)?,
+                    y: region.assign_constant(self.config.acc[1], 0, y)?,
+                };
+
+                // every term starts a row after the last one ended, with a copy
+                // of the accumulator and its own scalar
+                let mut start = 0;
+                let mut scalars = vec![];
+                for (base, bits) in terms {
+                    if start > 0 {
+                        acc = EccPoint {
+                            x: acc.x.copy_to(&mut region, self.config.acc[0], start),
+                            y: acc.y.copy_to(&mut region, self.config.acc[1], start),
+                        };
+                    }
+                    let mut scalar = region.assign_constant(self.config.scalar, start, Fp::ZERO)?;
+
+                    for (i, (bit, multiple)) in
+                        bits.iter().zip(multiples(*base, bits.len())).enumerate()
+                    {
+                        let row = start + i;
+                        self.config.q_add_fixed.enable(&mut region, row)?;
+                        self.config.q_scalar.enable(&mut region, row)?;
+
+                        let (x, y) = coordinates(multiple).ok_or(Error::Synthesis)?;
+                        for (column, value) in self.config.fixed_base.iter().zip([x, y]) {
+                            region.assign_fixed(*column, row, value);
+                        }
+
+                        let sum = self.assign_slope(
+                            &mut region,
+                            row,
+                            acc.value(),
+                            Value::known(multiple),
+                            *bit,
+                        )?;
+                        scalar = self.assign_bit(&mut region, row, *bit, &scalar)?;
+                        acc = self.assign_acc(&mut region, row + 1, sum)?;
+                    }
+
+                    scalars.push(scalar);
+                    start += bits.len() + 1;
+                }
+
+                Ok((acc, scalars))
+            },
+        )?;
+
+        let offset = self.constant_point(layouter.namespace(|| "offset"), -offset())?;
+        let point = self.add(layouter.namespace(|| "remove offset"), &acc, &offset)?;
+        Ok((point, scalars))
+    }
+}
+
+/// The constraints of `out = p + bit·q`, given the slope `lambda` of the line
+/// through `p` and `q` and `inv = 1 / (x_q - x_p)`, which rules out `q = ±p`
+/// when `bit` is set.
+fn conditional_add(
+    [xp, yp]: [Expression<Fp>; 2],
+    [xq, yq]: [Expression<Fp>; 2],
+    bit: Expression<Fp>,
+    lambda: Expression<Fp>,
+    inv: Expression<Fp>,
+    [x_out, y_out]: [Expression<Fp>; 2],
+) -> Vec<Expression<Fp>> {
+    let one = Expression::Constant(Fp::ONE);
+    let x_sum = lambda.clone().square() - xp.clone() - xq.clone();
+    let y_sum = lambda.clone() * (xp.clone() - x_sum.clone()) - yp.clone();
+    vec![
+        bit.clone() * (one.clone() - bit.clone()),
+        bit.clone() * (lambda * (xq.clone() - xp.clone()) - (yq - yp.clone())),
+        bit.clone() * ((xq - xp.clone()) * inv - one),
+        x_out - (xp.clone() + bit.clone() * (x_sum - xp)),
+        y_out - (yp.clone() + bit * (y_sum - yp)),
+    ]
+}
+
+#[cfg(test)]
+mod test {
+    use group::Group;
+    use halo2_proofs::{
+        dev::MockProver,
+        plonk::{Circuit, Instance},
+    };
+
+    use super::*;
+
+    use crate::planner::SimpleFloorPlanner;
+
+    /// Proves `[k]G` and `[2^n + m]P` for public results, with `n` the number
+    /// of bits of `m`.
+    #[derive(Default)]
+    struct MulCircuit {
+        k: Value<pallas::Scalar>,
+        p: Value<pallas::Affine>,
+        m: Value<u64>,
+    }
+
+    const M_BITS: usize = 16;
+
+    impl Circuit<Fp> for MulCircuit {
+        type Config = (EccConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 11].map(|_| meta.advice_column());
+            let constants = meta.fixed_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (EccChip::configure(meta, advice, constants), instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = EccChip::construct(config);
+
+            let k_bits: Vec<Value<bool>> = self
+                .k
+                .map(|k| scalar_bits(&k))
+                .transpose_vec(pallas::Scalar::NUM_BITS as usize);
+            let (kg, _) = chip.mul_fixed(
+                layouter.namespace(|| "[k]G"),
+                pallas::Point::generator().to_affine(),
+                &k_bits,
+            )?;
+
+            let p = chip.witness_point(layouter.namespace(|| "P"), self.p)?;
+            let m_bits: Vec<Value<bool>> = (0..M_BITS)
+                .rev()
+                .map(|i| self.m.map(|m| (m >> i) & 1 == 1))
+                .collect();
+            let (mp, m) = chip.mul(layouter.namespace(|| "[m]P"), &p, &m_bits)?;
+
+            for (row, cell) in [kg.x, kg.y, mp.x, mp.y, m].iter().enumerate() {
+                layouter.constrain_instance(cell.cell(), instance, row);
+            }
+            Ok(())
+        }
+    }
+
+    fn instances(k: pallas::Scalar, p: pallas::Affine, m: u64) -> Vec<Vec<Fp>> {
+        let m = m + (1 << M_BITS);
+        let kg = coordinates((pallas::Point::generator() * k).to_affine()).unwrap();
+        let mp = coordinates((p * pallas::Scalar::from(m)).to_affine()).unwrap();
+        vec![vec![kg.0, kg.1, mp.0, mp.1, Fp::from(m)]]
+    }
+
+    #[test]
+    fn mul_test() {
+        let k = -pallas::Scalar::from(123_456_789);
+        let p = (pallas::Point::generator() * pallas::Scalar::from(42)).to_affine();
+        for m in [0, 1, 0xbeef, 0xffff] {
+            let circuit = MulCircuit {
+                k: Value::known(k),
+                p: Value::known(p),
+                m: Value::known(m),
+            };
+            MockProver::run(10, &circuit, instances(k, p, m))
+                .unwrap()
+                .assert_satisfied();
+        }
+
+        let circuit = MulCircuit {
+            k: Value::known(k),
+            p: Value::known(p),
+            m: Value::known(7),
+        };
+        for wrong in [instances(k + pallas::Scalar::ONE, p, 7), instances(k, p, 8)] {
+            assert!(MockProver::run(10, &circuit, wrong)
+                .unwrap()
+                .verify()
+                .is_err());
+        }
+    }
+
+    /// `[k]P` for the bits of `k` after its leading one.
+    #[derive(Default)]
+    struct BitsMulCircuit {
+        p: Value<pallas::Affine>,
+        bits: Vec<Value<bool>>,
+    }
+
+    impl Circuit<Fp> for BitsMulCircuit {
+        type Config = EccConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                p: Value::unknown(),
+                bits: vec![Value::unknown(); self.bits.len()],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 11].map(|_| meta.advice_column());
+            let constants = meta.fixed_column();
+            EccChip::configure(meta, advice, constants)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = EccChip::construct(config);
+            let p = chip.witness_point(layouter.namespace(|| "P"), self.p)?;
+            chip.mul(layouter.namespace(|| "[k]P"), &p, &self.bits)?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn mul_degenerate_scalar_test() {
+        // k = 2q for the group order q: the accumulator reaches the identity,
+        // whose y = 0 has no doubling slope, before the last row
+        let mut q_bits = scalar_bits(&-pallas::Scalar::ONE);
+        *q_bits.last_mut().unwrap() = true;
+        let bits = q_bits[1..]
+            .iter()
+            .chain([&false])
+            .map(|bit| Value::known(*bit))
+            .collect();
+        let circuit = BitsMulCircuit {
+            p: Value::known(pallas::Point::generator().to_affine()),
+            bits,
+        };
+        assert!(MockProver::run(10, &circuit, vec![])
+            .unwrap()
+            .verify()
+            .is_err());
+    }
+
+    #[test]
+    fn scalar_bits_test() {
+        let bits = scalar_bits(&pallas::Scalar::from(5));
+        assert_eq!(bits.len(), 255);
+        assert_eq!(bits[252..], [true, false, true]);
+        assert!(bits[..252].iter().all(|bit| !bit));
+    }
+}
@@ -0,0 +1,189 @@
+//! On-chain verification of KZG proofs over BN254.
+//!
+//! snark-verifier turns a verifying key into a verifier written in Yul, which
+//! checks the opening with the BN254 precompiles, and [`yul`] compiles that to
+//! bytecode without `solc`. The verifier replays the transcript with Keccak, so
+//! the proofs it accepts come from [`EvmKzg`] rather than [`Kzg`], which hashes
+//! with Blake2b; everything else, including the parameters, is the same.
+//!
+//! The contract takes the public inputs followed by the proof as calldata, as
+//! [`calldata`] lays them out, and reverts unless the proof verifies.
+
+pub mod yul;
+
+use std::rc::Rc;
+
+use halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fq, Fr, G1Affine},
+    plonk::{create_proof, verify_proof, Circuit, Error, ProvingKey, VerifyingKey},
+    poly::{
+        commitment::ParamsProver,
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::{ProverSHPLONK, VerifierSHPLONK},
+            strategy::SingleStrategy,
+        },
+    },
+    transcript::{TranscriptReadBuffer, TranscriptWriterBuffer},
+};
+use rand_core::OsRng;
+use snark_verifier::{
+    loader::evm::{encode_calldata, EvmLoader},
+    pcs::kzg::{Bdfg21, KzgAs},
+    system::halo2::{compile, transcript::evm::EvmTranscript, Config},
+    verifier::{plonk::PlonkVerifier, SnarkVerifier},
+};
+
+use crate::proof::{Kzg, Scalar, Scheme};
+
+use self::yul::YulError;
+
+/// KZG over BN254 like [`Kzg`], with the Keccak transcript of the EVM
+/// verifier.
+#[derive(Clone, Copy, Debug)]
+pub struct EvmKzg;
+
+impl Scheme for EvmKzg {
+    type Curve = G1Affine;
+    type Params = ParamsKZG<Bn256>;
+
+    fn setup(k: u32) -> Self::Params {
+        Kzg::setup(k)
+    }
+
+    fn create_proof<Circ: Circuit<Scalar<Self>>>(
+        params: &Self::Params,
+        pk: &ProvingKey<G1Affine>,
+        circuit: Circ,
+        instances: &[&[Scalar<Self>]],
+    ) -> Result<Vec<u8>, Error> {
+        let mut transcript = TranscriptWriterBuffer::<_, G1Affine, _>::init(vec![]);
+        create_proof::<
+            KZGCommitmentScheme<Bn256>,
+            ProverSHPLONK<_>,
+            _,
+            _,
+            EvmTranscript<_, _, _, _>,
+            _,
+        >(params, pk, &[circuit], &[instances], OsRng, &mut transcript)?;
+
+        Ok(transcript.finalize())
+    }
+
+    fn verify_proof(
+        params: &Self::Params,
+        vk: &VerifyingKey<G1Affine>,
+        proof: &[u8],
+        instances: &[&[Scalar<Self>]],
+    ) -> Result<(), Error> {
+        let strategy = SingleStrategy::new(params);
+        let mut transcript = TranscriptReadBuffer::<_, G1Affine, _>::init(proof);
+
+        verify_proof::<
+            KZGCommitmentScheme<Bn256>,
+            VerifierSHPLONK<_>,
+            _,
+            EvmTranscript<_, _, _, _>,
+            _,
+        >(
+            params.verifier_params(),
+            vk,
+            strategy,
+            &[instances],
+            &mut transcript,
+        )
+    }
+}
+
+/// The verifier of the proofs under `vk`, as a Solidity contract whose body is
+/// inline assembly. `num_instances` holds the length of every instance column.
+pub fn solidity_verifier(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    num_instances: &[usize],
+) -> String {
+    let protocol = compile(
+        params,
+        vk,
+        Config::kzg().with_num_instance(num_instances.to_vec()),
+    );
+    let deciding_key = (params.get_g()[0], params.g2(), params.s_g2()).into();
+
+    let loader = EvmLoader::new::<Fq, Fr>();
+    let protocol = protocol.loaded(&loader);
+    let mut transcript = EvmTranscript::<_, Rc<EvmLoader>, _, _>::new(&loader);
+    let instances = transcript.load_instances(num_instances.to_vec());
+    let proof = PlonkVerifier::<KzgAs<Bn256, Bdfg21>>::read_proof(
+        &deciding_key,
+        &protocol,
+        &instances,
+        &mut transcript,
+    )
+    .expect("the EVM loader only records operations");
+    PlonkVerifier::<KzgAs<Bn256, Bdfg21>>::verify(&deciding_key, &protocol, &instances, &proof)
+        .expect("the EVM loader only records operations");
+
+    loader.solidity_code()
+}
+
+/// The code that deploys the verifier of [`solidity_verifier`].
+pub fn deployment_code(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    num_instances: &[usize],
+) -> Result<Vec<u8>, YulError> {
+    let solidity = solidity_verifier(params, vk, num_instances);
+    let assembly = &solidity[solidity.find("assembly").unwrap_or(0)..];
+    // solc's code sets the free memory pointer to 0x80 first, which the
+    // verifier checks
+    let assembly = assembly.replacen('{', "{\nmstore(0x40, 0x80)", 1);
+
+    Ok(yul::deployment_code(&yul::compile(&assembly)?))
+}
+
+/// The calldata of a call to the verifier: the public inputs, column by column,
+/// as 32-byte big-endian words, then the proof.
+pub fn calldata(instances: &[&[Fr]], proof: &[u8]) -> Vec<u8> {
+    let instances: Vec<Vec<Fr>> = instances.iter().map(|column| column.to_vec()).collect();
+    encode_calldata(&instances, proof)
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::circuit::Value;
+    use snark_verifier::loader::evm::deploy_and_call;
+
+    use crate::fibo_circuit::FiboCircuit;
+    use crate::proof::{keygen, prove, verify};
+
+    use super::*;
+
+    #[test]
+    fn evm_verify_test() {
+        let circuit = FiboCircuit::<Fr> {
+            a: Value::known(Fr::from(1)),
+            b: Value::known(Fr::from(1)),
+        };
+        let public_input = [1, 1, 55].map(Fr::from);
+
+        let (params, pk) = keygen::<EvmKzg, _>(&circuit, None).unwrap();
+        let proof = prove::<EvmKzg, _>(&params, &pk, circuit, &[&public_input]).unwrap();
+        assert!(verify::<EvmKzg>(&params, pk.get_vk(), &proof, &[&public_input]).is_ok());
+
+        let code = deployment_code(&params, pk.get_vk(), &[public_input.len()]).unwrap();
+        let call = |instances: &[Fr], proof: &[u8]| {
+            deploy_and_call(code.clone(), calldata(&[instances], proof))
+        };
+        assert!(call(&public_input, &proof).is_ok());
+
+        // a flipped byte in the proof
+        for at in [0, proof.len() / 2, proof.len() - 1] {
+            let mut tampered = proof.clone();
+            tampered[at] ^= 1;
+            assert!(call(&public_input, &tampered).is_err());
+        }
+
+        // another output
+        assert!(call(&[1, 1, 56].map(Fr::from), &proof).is_err());
+    }
+}
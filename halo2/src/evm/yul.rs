@@ -0,0 +1,667 @@
+//! A compiler from the subset of Yul that snark-verifier emits to EVM bytecode.
+//!
+//! The verifier code is straight-line: `let`, assignments, `if` and nested
+//! blocks over EVM builtins, plus one helper function. Variables live on the
+//! stack and are reached with `DUP`/`SWAP`, so a variable more than 16 slots
+//! below the top is an error, as it is for `solc` without its optimizer. Calls
+//! to functions defined in the code are inlined, which is all loops-free code
+//! with a handful of call sites needs. Nothing is optimized.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use snark_verifier::loader::evm::U256;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum YulError {
+    Syntax {
+        line: usize,
+        message: String,
+    },
+    /// A variable or function that is not in scope.
+    Unknown(String),
+    /// A builtin or function called with the wrong number of arguments, or
+    /// whose result is used or dropped against its signature.
+    Arity(String),
+    /// A variable more than 16 slots below the top of the stack.
+    StackTooDeep(String),
+}
+
+impl fmt::Display for YulError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            YulError::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+            YulError::Unknown(name) => write!(f, "unknown identifier `{}`", name),
+            YulError::Arity(name) => write!(f, "wrong number of values in call to `{}`", name),
+            YulError::StackTooDeep(name) => write!(f, "`{}` is too deep in the stack", name),
+        }
+    }
+}
+
+impl std::error::Error for YulError {}
+
+/// Compiles the Yul block that starts at the first `{` of `code` to runtime
+/// bytecode, ignoring anything after its closing brace.
+pub fn compile(code: &str) -> Result<Vec<u8>, YulError> {
+    let tokens = tokenize(code)?;
+    let mut parser = Parser {
+        tokens: &tokens,
+        pos: 0,
+    };
+    let block = parser.block()?;
+
+    let mut codegen = Codegen::default();
+    codegen.block(&block)?;
+    Ok(codegen.finish())
+}
+
+/// Wraps `runtime` in code that deploys it.
+pub fn deployment_code(runtime: &[u8]) -> Vec<u8> {
+    const INIT_LEN: usize = 13;
+
+    let len = u16::try_from(runtime.len()).expect("runtime code fits in 64 KiB");
+    let [len_hi, len_lo] = len.to_be_bytes();
+    let mut code = vec![
+        op::PUSH2,
+        len_hi,
+        len_lo,
+        op::PUSH2,
+        0,
+        INIT_LEN as u8,
+        op::PUSH0,
+        op::CODECOPY,
+        op::PUSH2,
+        len_hi,
+        len_lo,
+        op::PUSH0,
+        op::RETURN,
+    ];
+    debug_assert_eq!(code.len(), INIT_LEN);
+    code.extend_from_slice(runtime);
+    code
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    Number(U256),
+    Symbol(&'static str),
+}
+
+fn tokenize(code: &str) -> Result<Vec<(Token, usize)>, YulError> {
+    let syntax = |line, message: &str| YulError::Syntax {
+        line,
+        message: message.to_string(),
+    };
+
+    let mut tokens = vec![];
+    for (line, text) in code.lines().enumerate() {
+        let line = line + 1;
+        let text = text.split("//").next().unwrap();
+        let mut chars = text.char_indices().peekable();
+        while let Some((start, c)) = chars.next() {
+            if c.is_whitespace() {
+                continue;
+            }
+            let mut end = start + c.len_utf8();
+            let mut extend = |chars: &mut std::iter::Peekable<std::str::CharIndices>,
+                              accept: fn(char) -> bool| {
+                while let Some((i, c)) = chars.peek().copied() {
+                    if !accept(c) {
+                        break;
+                    }
+                    end = i + c.len_utf8();
+                    chars.next();
+                }
+            };
+            let token = match c {
+                '{' => Token::Symbol("{"),
+                '}' => Token::Symbol("}"),
+                '(' => Token::Symbol("("),
+                ')' => Token::Symbol(")"),
+                ',' => Token::Symbol(","),
+                ':' if chars.next_if(|(_, c)| *c == '=').is_some() => Token::Symbol(":="),
+                '-' if chars.next_if(|(_, c)| *c == '>').is_some() => Token::Symbol("->"),
+                '"' => {
+                    // only `assembly ("memory-safe")` has strings, and they are
+                    // not part of the block
+                    extend(&mut chars, |c| c != '"');
+                    chars.next();
+                    continue;
+                }
+                c if c.is_ascii_digit() => {
+                    extend(&mut chars, |c| c.is_ascii_alphanumeric());
+                    let literal = &text[start..end];
+                    let value = match literal.strip_prefix("0x") {
+                        Some(hex) => U256::from_str_radix(hex, 16),
+                        None => U256::from_str_radix(literal, 10),
+                    };
+                    Token::Number(value.map_err(|_| syntax(line, "invalid number"))?)
+                }
+                c if c.is_ascii_alphabetic() || c == '_' || c == '$' => {
+                    extend(&mut chars, |c| {
+                        c.is_ascii_alphanumeric() || c == '_' || c == '$' || c == '.'
+                    });
+                    match &text[start..end] {
+                        "true" => Token::Number(U256::from(1)),
+                        "false" => Token::Number(U256::ZERO),
+                        ident => Token::Ident(ident.to_string()),
+                    }
+                }
+                _ => return Err(syntax(line, &format!("unexpected `{}`", c))),
+            };
+            tokens.push((token, line));
+        }
+    }
+    Ok(tokens)
+}
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Literal(U256),
+    Ident(String),
+    Call(String, Vec<Expr>),
+}
+
+#[derive(Clone, Debug)]
+enum Stmt {
+    Let(String, Option<Expr>),
+    Assign(String, Expr),
+    If(Expr, Block),
+    Block(Block),
+    Expr(Expr),
+}
+
+#[derive(Clone, Debug)]
+struct Function {
+    params: Vec<String>,
+    returns: Vec<String>,
+    body: Block,
+}
+
+#[derive(Clone, Debug, Default)]
+struct Block {
+    stmts: Vec<Stmt>,
+    functions: HashMap<String, Function>,
+}
+
+struct Parser<'t> {
+    tokens: &'t [(Token, usize)],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> YulError {
+        let line = self
+            .tokens
+            .get(self.pos)
+            .or(self.tokens.last())
+            .map_or(0, |(_, line)| *line);
+        YulError::Syntax {
+            line,
+            message: message.to_string(),
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    fn next(&mut self) -> Result<Token, YulError> {
+        let token = self.peek().cloned().ok_or(self.error("unexpected end"))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn eat(&mut self, symbol: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Symbol(s)) if *s == symbol) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), YulError> {
+        if self.eat(symbol) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{}`", symbol)))
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, YulError> {
+        match self.next()? {
+            Token::Ident(name) => Ok(name),
+            _ => {
+                self.pos -= 1;
+                Err(self.error("expected an identifier"))
+            }
+        }
+    }
+
+    /// Skips to the first `{` and parses the block it opens.
+    fn block(&mut self) -> Result<Block, YulError> {
+        while !self.eat("{") {
+            self.next()?;
+        }
+        self.block_body()
+    }
+
+    fn block_body(&mut self) -> Result<Block, YulError> {
+        let mut block = Block::default();
+        while !self.eat("}") {
+            match self.peek() {
+                Some(Token::Ident(keyword)) if keyword == "function" => {
+                    self.pos += 1;
+                    let (name, function) = self.function()?;
+                    block.functions.insert(name, function);
+                }
+                _ => block.stmts.push(self.stmt()?),
+            }
+        }
+        Ok(block)
+    }
+
+    fn function(&mut self) -> Result<(String, Function), YulError> {
+        let name = self.ident()?;
+        self.expect("(")?;
+        let params = self.idents(")")?;
+        let returns = if self.eat("->") {
+            self.idents("{")?
+        } else {
+            self.expect("{")?;
+            vec![]
+        };
+        let body = self.block_body()?;
+        Ok((
+            name,
+            Function {
+                params,
+                returns,
+                body,
+            },
+        ))
+    }
+
+    /// A comma separated list of identifiers, up to and including `end`.
+    fn idents(&mut self, end: &str) -> Result<Vec<String>, YulError> {
+        let mut idents = vec![];
+        while !self.eat(end) {
+            if !idents.is_empty() {
+                self.expect(",")?;
+            }
+            idents.push(self.ident()?);
+        }
+        Ok(idents)
+    }
+
+    fn stmt(&mut self) -> Result<Stmt, YulError> {
+        if self.eat("{") {
+            return Ok(Stmt::Block(self.block_body()?));
+        }
+        match self.peek() {
+            Some(Token::Ident(keyword)) if keyword == "let" => {
+                self.pos += 1;
+                let name = self.ident()?;
+                let value = if self.eat(":=") {
+                    Some(self.expr()?)
+                } else {
+                    None
+                };
+                Ok(Stmt::Let(name, value))
+            }
+            Some(Token::Ident(keyword)) if keyword == "if" => {
+                self.pos += 1;
+                let condition = self.expr()?;
+                self.expect("{")?;
+                Ok(Stmt::If(condition, self.block_body()?))
+            }
+            Some(Token::Ident(_)) => {
+                let start = self.pos;
+                let name = self.ident()?;
+                if self.eat(":=") {
+                    return Ok(Stmt::Assign(name, self.expr()?));
+                }
+                self.pos = start;
+                match self.expr()? {
+                    call @ Expr::Call(..) => Ok(Stmt::Expr(call)),
+                    _ => Err(self.error("expected a statement")),
+                }
+            }
+            _ => Err(self.error("expected a statement")),
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr, YulError> {
+        match self.next()? {
+            Token::Number(value) => Ok(Expr::Literal(value)),
+            Token::Ident(name) => {
+                if !self.eat("(") {
+                    return Ok(Expr::Ident(name));
+                }
+                let mut args = vec![];
+                while !self.eat(")") {
+                    if !args.is_empty() {
+                        self.expect(",")?;
+                    }
+                    args.push(self.expr()?);
+                }
+                Ok(Expr::Call(name, args))
+            }
+            Token::Symbol(_) => {
+                self.pos -= 1;
+                Err(self.error("expected an expression"))
+            }
+        }
+    }
+}
+
+mod op {
+    pub const STOP: u8 = 0x00;
+    pub const ISZERO: u8 = 0x15;
+    pub const CODECOPY: u8 = 0x39;
+    pub const POP: u8 = 0x50;
+    pub const JUMPI: u8 = 0x57;
+    pub const JUMPDEST: u8 = 0x5b;
+    pub const PUSH0: u8 = 0x5f;
+    pub const PUSH1: u8 = 0x60;
+    pub const PUSH2: u8 = 0x61;
+    pub const DUP1: u8 = 0x80;
+    pub const SWAP1: u8 = 0x90;
+    pub const RETURN: u8 = 0xf3;
+}
+
+/// The opcode, number of arguments and number of results of a builtin.
+fn builtin(name: &str) -> Option<(u8, usize, usize)> {
+    Some(match name {
+        "stop" => (op::STOP, 0, 0),
+        "add" => (0x01, 2, 1),
+        "mul" => (0x02, 2, 1),
+        "sub" => (0x03, 2, 1),
+        "div" => (0x04, 2, 1),
+        "mod" => (0x06, 2, 1),
+        "addmod" => (0x08, 3, 1),
+        "mulmod" => (0x09, 3, 1),
+        "exp" => (0x0a, 2, 1),
+        "lt" => (0x10, 2, 1),
+        "gt" => (0x11, 2, 1),
+        "eq" => (0x14, 2, 1),
+        "iszero" => (op::ISZERO, 1, 1),
+        "and" => (0x16, 2, 1),
+        "or" => (0x17, 2, 1),
+        "xor" => (0x18, 2, 1),
+        "not" => (0x19, 1, 1),
+        "byte" => (0x1a, 2, 1),
+        "shl" => (0x1b, 2, 1),
+        "shr" => (0x1c, 2, 1),
+        "keccak256" => (0x20, 2, 1),
+        "calldataload" => (0x35, 1, 1),
+        "calldatasize" => (0x36, 0, 1),
+        "calldatacopy" => (0x37, 3, 0),
+        "codecopy" => (op::CODECOPY, 3, 0),
+        "returndatasize" => (0x3d, 0, 1),
+        "returndatacopy" => (0x3e, 3, 0),
+        "pop" => (op::POP, 1, 0),
+        "mload" => (0x51, 1, 1),
+        "mstore" => (0x52, 2, 0),
+        "mstore8" => (0x53, 2, 0),
+        "gas" => (0x5a, 0, 1),
+        "staticcall" => (0xfa, 6, 1),
+        "return" => (op::RETURN, 2, 0),
+        "revert" => (0xfd, 2, 0),
+        _ => return None,
+    })
+}
+
+#[derive(Default)]
+struct Codegen {
+    code: Vec<u8>,
+    /// the number of values on the stack
+    height: usize,
+    /// the variables in scope, innermost last, with their stack slots
+    vars: Vec<Vec<(String, usize)>>,
+    /// the functions in scope, innermost last
+    functions: Vec<HashMap<String, Function>>,
+    /// the `PUSH2` operands to patch with the offsets of their labels
+    jumps: Vec<(usize, usize)>,
+    labels: Vec<usize>,
+}
+
+impl Codegen {
+    fn finish(mut self) -> Vec<u8> {
+        for (at, label) in self.jumps {
+            let [hi, lo] = u16::try_from(self.labels[label])
+                .expect("code fits in 64 KiB")
+                .to_be_bytes();
+            self.code[at] = hi;
+            self.code[at + 1] = lo;
+        }
+        self.code.push(op::STOP);
+        self.code
+    }
+
+    fn emit(&mut self, opcode: u8, args: usize, results: usize) {
+        self.code.push(opcode);
+        self.height = self.height + results - args;
+    }
+
+    fn push(&mut self, value: U256) {
+        let bytes = value.to_be_bytes::<32>();
+        let skip = bytes.iter().take_while(|byte| **byte == 0).count();
+        if skip == 32 {
+            self.code.push(op::PUSH0);
+        } else {
+            self.code.push(op::PUSH1 + (31 - skip) as u8);
+            self.code.extend_from_slice(&bytes[skip..]);
+        }
+        self.height += 1;
+    }
+
+    fn block(&mut self, block: &Block) -> Result<(), YulError> {
+        self.vars.push(vec![]);
+        self.functions.push(block.functions.clone());
+        for stmt in &block.stmts {
+            self.stmt(stmt)?;
+        }
+        self.functions.pop();
+        for _ in self.vars.pop().unwrap() {
+            self.emit(op::POP, 1, 0);
+        }
+        Ok(())
+    }
+
+    fn declare(&mut self, name: &str) {
+        let slot = self.height - 1;
+        self.vars.last_mut().unwrap().push((name.to_string(), slot));
+    }
+
+    fn slot(&self, name: &str) -> Result<usize, YulError> {
+        self.vars
+            .iter()
+            .rev()
+            .flatten()
+            .find(|(var, _)| var == name)
+            .map(|(_, slot)| *slot)
+            .ok_or_else(|| YulError::Unknown(name.to_string()))
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) -> Result<(), YulError> {
+        match stmt {
+            Stmt::Let(name, value) => {
+                match value {
+                    Some(value) => self.expr(value, 1)?,
+                    None => self.push(U256::ZERO),
+                }
+                self.declare(name);
+            }
+            Stmt::Assign(name, value) => {
+                let slot = self.slot(name)?;
+                self.expr(value, 1)?;
+                let depth = self.height - 1 - slot;
+                if depth > 16 {
+                    return Err(YulError::StackTooDeep(name.clone()));
+                }
+                self.emit(op::SWAP1 + depth as u8 - 1, 0, 0);
+                self.emit(op::POP, 1, 0);
+            }
+            Stmt::If(condition, body) => {
+                self.expr(condition, 1)?;
+                self.emit(op::ISZERO, 1, 1);
+                let end = self.labels.len();
+                self.labels.push(0);
+                self.code.push(op::PUSH2);
+                self.jumps.push((self.code.len(), end));
+                self.code.extend_from_slice(&[0, 0]);
+                self.height += 1;
+                self.emit(op::JUMPI, 2, 0);
+                self.block(body)?;
+                self.labels[end] = self.code.len();
+                self.code.push(op::JUMPDEST);
+            }
+            Stmt::Block(block) => self.block(block)?,
+            Stmt::Expr(call) => self.expr(call, 0)?,
+        }
+        Ok(())
+    }
+
+    /// Pushes the values of `expr`, which must be `results` many. Only calls
+    /// are used as statements, so literals and variables are always one value.
+    fn expr(&mut self, expr: &Expr, results: usize) -> Result<(), YulError> {
+        match expr {
+            Expr::Literal(value) => self.push(*value),
+            Expr::Ident(name) => {
+                let depth = self.height - self.slot(name)?;
+                if depth > 16 {
+                    return Err(YulError::StackTooDeep(name.clone()));
+                }
+                self.emit(op::DUP1 + depth as u8 - 1, 0, 1);
+            }
+            Expr::Call(name, args) => {
+                // arguments are evaluated right to left, leaving the first on top
+                for arg in args.iter().rev() {
+                    self.expr(arg, 1)?;
+                }
+                if let Some((opcode, arity, returns)) = builtin(name) {
+                    if arity != args.len() || returns != results {
+                        return Err(YulError::Arity(name.clone()));
+                    }
+                    self.emit(opcode, arity, returns);
+                } else {
+                    self.inline(name, args.len(), results)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs the body of the function `name` on the arguments on top of the
+    /// stack, and replaces them with its results.
+    fn inline(&mut self, name: &str, args: usize, results: usize) -> Result<(), YulError> {
+        let function = self
+            .functions
+            .iter()
+            .rev()
+            .find_map(|functions| functions.get(name))
+            .cloned()
+            .ok_or_else(|| YulError::Unknown(name.to_string()))?;
+        if function.params.len() != args || function.returns.len() != results || results > 1 {
+            return Err(YulError::Arity(name.to_string()));
+        }
+
+        // the body only sees its parameters and results
+        let outer = std::mem::take(&mut self.vars);
+        let height = self.height;
+        self.vars.push(
+            (function.params.iter().enumerate())
+                .map(|(i, param)| (param.clone(), height - 1 - i))
+                .collect(),
+        );
+        for ret in &function.returns {
+            self.push(U256::ZERO);
+            self.declare(ret);
+        }
+        self.block(&function.body)?;
+        self.vars = outer;
+
+        // drop the arguments from under the result
+        for _ in 0..args {
+            if results == 1 {
+                self.emit(op::SWAP1, 0, 0);
+            }
+            self.emit(op::POP, 1, 0);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use snark_verifier::loader::evm::deploy_and_call;
+
+    use super::*;
+
+    fn word(value: u64) -> Vec<u8> {
+        U256::from(value).to_be_bytes::<32>().to_vec()
+    }
+
+    #[test]
+    fn compile_test() {
+        // succeeds if the third word of calldata is the product of the first
+        // two and the fourth their sum
+        let code = "
+            assembly (\"memory-safe\") {
+                let a := calldataload(0)
+                let b := calldataload(0x20)
+                function check(x, y, z) -> ok {
+                    {
+                        let product := mul(x, y)
+                        ok := eq(product, z)
+                    }
+                }
+                // called twice, so inlined twice
+                if iszero(and(check(a, b, calldataload(0x40)), check(b, a, calldataload(64)))) {
+                    revert(0, 0)
+                }
+                let sum
+                sum := add(a, b)
+                if iszero(eq(sum, calldataload(0x60))) { revert(0, 0) }
+                return(0, 0)
+            }
+            anything after the block is ignored }";
+        let runtime = compile(code).unwrap();
+
+        let run = |words: [u64; 4]| {
+            let calldata = words.into_iter().flat_map(word).collect();
+            deploy_and_call(deployment_code(&runtime), calldata)
+        };
+        assert!(run([6, 7, 42, 13]).is_ok());
+        assert!(run([6, 7, 43, 13]).is_err());
+        assert!(run([6, 7, 42, 14]).is_err());
+    }
+
+    #[test]
+    fn compile_error_test() {
+        assert_eq!(
+            compile("{ let a := b }"),
+            Err(YulError::Unknown("b".to_string()))
+        );
+        assert_eq!(
+            compile("{ mstore(0) }"),
+            Err(YulError::Arity("mstore".to_string()))
+        );
+        assert_eq!(
+            compile("{ let a := 1\n let := 2 }"),
+            Err(YulError::Syntax {
+                line: 2,
+                message: "expected an identifier".to_string()
+            })
+        );
+
+        let deep: String = (0..17).map(|i| format!("let v{} := {}\n", i, i)).collect();
+        assert_eq!(
+            compile(&format!("{{ {} v0 := 1 }}", deep)),
+            Err(YulError::StackTooDeep("v0".to_string()))
+        );
+    }
+}
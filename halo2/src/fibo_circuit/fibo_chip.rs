@@ -1,14 +1,43 @@
-use halo2_proofs::circuit::{AssignedCell, Layouter};
+use halo2_proofs::circuit::{Chip, Layouter, Value};
+use halo2_proofs::plonk::*;
 use halo2_proofs::plonk::{Advice, Column, Error};
 use halo2_proofs::poly::Rotation;
-use halo2_proofs::{arithmetic::FieldExt, plonk::*};
 use std::marker::PhantomData;
 
+use crate::cell::{AssignedCell, RegionExt};
+use crate::field::FieldExt;
+use crate::instance::InstanceColumns;
+
+/// The Fibonacci sequence, one `a + b = c` row per term.
+pub trait FiboRowInstructions<F: FieldExt>: Chip<F> {
+    fn assign_first_row(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Value<F>,
+        b: Value<F>,
+    ) -> Result<Row<F>, Error>;
+
+    fn assign_row(
+        &self,
+        layouter: impl Layouter<F>,
+        prev_b: &ACell<F>,
+        prev_c: &ACell<F>,
+    ) -> Result<ACell<F>, Error>;
+
+    /// Constrains `cell` to equal the public input called `name`.
+    fn expose_public(
+        &self,
+        layouter: impl Layouter<F>,
+        cell: &ACell<F>,
+        name: &str,
+    ) -> Result<(), Error>;
+}
+
 #[derive(Clone, Debug)]
 pub struct FiboConfig {
     pub advice: [Column<Advice>; 3],
     pub selector: Selector,
-    pub instance: Column<Instance>,
+    pub instance: InstanceColumns,
 }
 
 pub struct FiboChip<F: FieldExt> {
@@ -16,8 +45,24 @@ pub struct FiboChip<F: FieldExt> {
     _marker: std::marker::PhantomData<F>,
 }
 
+impl<F: FieldExt> Chip<F> for FiboChip<F> {
+    type Config = FiboConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
 #[derive(Debug, Clone)]
-pub struct ACell<F: FieldExt>(AssignedCell<F, F>);
+pub struct ACell<F: FieldExt>(AssignedCell<F>);
+
+/// The cells of a row: `a`, `b` and `c = a + b`.
+pub type Row<F> = (ACell<F>, ACell<F>, ACell<F>);
 
 impl<F: FieldExt> FiboChip<F> {
     pub fn construct(config: FiboConfig) -> Self {
@@ -29,7 +74,7 @@ impl<F: FieldExt> FiboChip<F> {
     pub fn configure(
         meta: &mut ConstraintSystem<F>,
         advice: [Column<Advice>; 3],
-        instance: Column<Instance>,
+        instance: InstanceColumns,
     ) -> FiboConfig {
         let col_a = advice[0];
         let col_b = advice[1];
@@ -39,7 +84,6 @@ impl<F: FieldExt> FiboChip<F> {
         meta.enable_equality(col_a);
         meta.enable_equality(col_b);
         meta.enable_equality(col_c);
-        meta.enable_equality(instance);
 
         // col_a  | col_b  | col_c | selector
         //   a       b         c       s
@@ -57,51 +101,32 @@ impl<F: FieldExt> FiboChip<F> {
             instance,
         }
     }
+}
 
-    pub fn assign_first_row(
+impl<F: FieldExt> FiboRowInstructions<F> for FiboChip<F> {
+    fn assign_first_row(
         &self,
         mut layouter: impl Layouter<F>,
-        a: Option<F>,
-        b: Option<F>,
-    ) -> Result<(ACell<F>, ACell<F>, ACell<F>), Error> {
+        a: Value<F>,
+        b: Value<F>,
+    ) -> Result<Row<F>, Error> {
         layouter.assign_region(
             || "first row",
             |mut region| {
                 self.config.selector.enable(&mut region, 0)?;
 
-                let cell_a = region
-                    .assign_advice(
-                        || "cell_a",
-                        self.config.advice[0],
-                        0,
-                        || a.ok_or(Error::Synthesis),
-                    )
-                    .map(ACell)?;
-
-                let cell_b = region
-                    .assign_advice(
-                        || "cell_b",
-                        self.config.advice[1],
-                        0,
-                        || b.ok_or(Error::Synthesis),
-                    )
-                    .map(ACell)?;
+                let cell_a = ACell(region.assign(self.config.advice[0], 0, a));
+
+                let cell_b = ACell(region.assign(self.config.advice[1], 0, b));
                 let c = a.and_then(|a| b.map(|b| a + b));
-                let cell_c = region
-                    .assign_advice(
-                        || "cell_c",
-                        self.config.advice[2],
-                        0,
-                        || c.ok_or(Error::Synthesis),
-                    )
-                    .map(ACell)?;
+                let cell_c = ACell(region.assign(self.config.advice[2], 0, c));
 
                 Ok((cell_a, cell_b, cell_c))
             },
         )
     }
 
-    pub fn assign_row(
+    fn assign_row(
         &self,
         mut layouter: impl Layouter<F>,
         prev_b: &ACell<F>,
@@ -112,49 +137,28 @@ impl<F: FieldExt> FiboChip<F> {
             |mut region| {
                 self.config.selector.enable(&mut region, 0)?;
 
-                region
-                    .assign_advice(
-                        || "cell_n_a",
-                        self.config.advice[0],
-                        0,
-                        || prev_b.0.value().copied().ok_or(Error::Synthesis),
-                    )
-                    .map(ACell)?;
-
-                region
-                    .assign_advice(
-                        || "cell_n_b",
-                        self.config.advice[1],
-                        0,
-                        || prev_c.0.value().copied().ok_or(Error::Synthesis),
-                    )
-                    .map(ACell)?;
+                region.assign(self.config.advice[0], 0, prev_b.0.field_value());
+
+                region.assign(self.config.advice[1], 0, prev_c.0.field_value());
 
                 let c = prev_b
                     .0
                     .value()
                     .and_then(|a| prev_c.0.value().map(|b| *a + *b));
 
-                let cell_c = region
-                    .assign_advice(
-                        || "cell_n_c",
-                        self.config.advice[2],
-                        0,
-                        || c.ok_or(Error::Synthesis),
-                    )
-                    .map(ACell)?;
+                let cell_c = ACell(region.assign(self.config.advice[2], 0, c));
 
                 Ok(cell_c)
             },
         )
     }
 
-    pub fn expose_public(
+    fn expose_public(
         &self,
-        mut layouter: impl Layouter<F>,
+        layouter: impl Layouter<F>,
         cell: &ACell<F>,
-        row: usize,
+        name: &str,
     ) -> Result<(), Error> {
-        layouter.constrain_instance(cell.0.cell(), self.config.instance, row)
+        self.config.instance.expose(layouter, cell.0.cell(), name)
     }
 }
@@ -1,19 +1,36 @@
 mod fibo_chip;
 
-use crate::fibo_circuit::fibo_chip::{FiboChip, FiboConfig};
-use halo2_proofs::arithmetic::FieldExt;
-use halo2_proofs::circuit::{Layouter, SimpleFloorPlanner};
+pub use self::fibo_chip::{FiboChip, FiboConfig, FiboRowInstructions};
+
+use crate::backend::GenericCircuit;
+use crate::field::FieldExt;
+use crate::input::{CircuitInput, CircuitWithInstances, FieldValue, InputError};
+use crate::instance::{InstanceColumns, InstanceLayout, NamedInstances};
+use crate::planner::SimpleFloorPlanner;
+use halo2_proofs::circuit::{Layouter, Value};
 use halo2_proofs::plonk::{Circuit, ConstraintSystem, Error};
+use serde::Deserialize;
+
+/// The public inputs of both Fibonacci circuits: the first two terms and the `N`-th.
+pub const INSTANCE_LAYOUT: InstanceLayout = InstanceLayout::new(&[&["a", "b", "out"]]);
 
+/// Proves the `N`-th term of the Fibonacci sequence starting at `a, b`.
 #[derive(Default)]
-pub struct FiboCircuit<F: FieldExt> {
-    pub a: Option<F>,
-    pub b: Option<F>,
+pub struct FiboCircuit<F: FieldExt, const N: usize = 10> {
+    pub a: Value<F>,
+    pub b: Value<F>,
+}
+
+impl<F: FieldExt, const N: usize> NamedInstances for FiboCircuit<F, N> {
+    fn instance_layout() -> InstanceLayout {
+        INSTANCE_LAYOUT
+    }
 }
 
-impl<F: FieldExt> Circuit<F> for FiboCircuit<F> {
+impl<F: FieldExt, const N: usize> Circuit<F> for FiboCircuit<F, N> {
     type Config = FiboConfig;
     type FloorPlanner = SimpleFloorPlanner;
+    type Params = ();
 
     fn without_witnesses(&self) -> Self {
         Self::default()
@@ -23,7 +40,7 @@ impl<F: FieldExt> Circuit<F> for FiboCircuit<F> {
         let col_a = meta.advice_column();
         let col_b = meta.advice_column();
         let col_c = meta.advice_column();
-        let instance = meta.instance_column();
+        let instance = InstanceColumns::configure(meta, INSTANCE_LAYOUT);
         FiboChip::configure(meta, [col_a, col_b, col_c], instance)
     }
 
@@ -42,10 +59,10 @@ impl<F: FieldExt> Circuit<F> for FiboCircuit<F> {
             )
             .unwrap();
 
-        chip.expose_public(layouter.namespace(|| "private a"), &a, 0)?;
-        chip.expose_public(layouter.namespace(|| "private b"), &prev_b, 1)?;
+        chip.expose_public(layouter.namespace(|| "private a"), &a, "a")?;
+        chip.expose_public(layouter.namespace(|| "private b"), &prev_b, "b")?;
 
-        for i in 3..10 {
+        for i in 3..N {
             let c = chip
                 .assign_row(
                     layouter.namespace(|| format!("Assining {i}")),
@@ -58,8 +75,107 @@ impl<F: FieldExt> Circuit<F> for FiboCircuit<F> {
             prev_c = c;
         }
 
-        chip.expose_public(layouter.namespace(|| "out"), &prev_c, 2)?;
+        chip.expose_public(layouter.namespace(|| "out"), &prev_c, "out")?;
 
         Ok(())
     }
 }
+
+/// The inputs of [`FiboCircuit`], independent of the field it is built over.
+#[derive(Clone, Debug)]
+pub struct FiboInput<const N: usize> {
+    pub a: u64,
+    pub b: u64,
+}
+
+impl<const N: usize> GenericCircuit for FiboInput<N> {
+    type Circuit<F: FieldExt> = FiboCircuit<F, N>;
+
+    fn circuit<F: FieldExt>(&self) -> Self::Circuit<F> {
+        FiboCircuit {
+            a: Value::known(F::from(self.a)),
+            b: Value::known(F::from(self.b)),
+        }
+    }
+
+    fn instances<F: FieldExt>(&self) -> Vec<Vec<F>> {
+        let (a, b) = (F::from(self.a), F::from(self.b));
+        INSTANCE_LAYOUT
+            .assemble(&[("a", a), ("b", b), ("out", fibo(a, b, N))])
+            .unwrap()
+    }
+}
+
+/// The JSON inputs of [`FiboCircuit`], see [`crate::input`].
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FiboInputFile<const N: usize> {
+    pub private: FiboPrivateInputs,
+    pub public: FiboPublicInputs,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FiboPrivateInputs {
+    pub a: FieldValue,
+    pub b: FieldValue,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FiboPublicInputs {
+    pub a: FieldValue,
+    pub b: FieldValue,
+    pub out: FieldValue,
+}
+
+impl FiboPublicInputs {
+    /// The instance column laid out as [`INSTANCE_LAYOUT`], checked against the
+    /// values the circuit computes from `private`.
+    pub fn instances<F: FieldExt, const N: usize>(
+        &self,
+        private: &FiboPrivateInputs,
+    ) -> Result<Vec<Vec<F>>, InputError> {
+        let instances = INSTANCE_LAYOUT.assemble(&[
+            ("a", self.a.to_field("public.a")?),
+            ("b", self.b.to_field("public.b")?),
+            ("out", self.out.to_field("public.out")?),
+        ])?;
+
+        let (a, b) = (
+            private.a.to_field("private.a")?,
+            private.b.to_field("private.b")?,
+        );
+        let instance_slices: Vec<&[F]> = instances.iter().map(Vec::as_slice).collect();
+        INSTANCE_LAYOUT.check_values(
+            &instance_slices,
+            &[("a", a), ("b", b), ("out", fibo(a, b, N))],
+        )?;
+
+        Ok(instances)
+    }
+}
+
+impl<const N: usize> CircuitInput for FiboInputFile<N> {
+    type Circuit<F: FieldExt> = FiboCircuit<F, N>;
+
+    fn build<F: FieldExt>(&self) -> Result<CircuitWithInstances<Self, F>, InputError> {
+        let circuit = FiboCircuit {
+            a: Value::known(self.private.a.to_field("private.a")?),
+            b: Value::known(self.private.b.to_field("private.b")?),
+        };
+
+        Ok((circuit, self.public.instances::<F, N>(&self.private)?))
+    }
+}
+
+/// The `n`-th term of the Fibonacci sequence starting at `a, b`.
+pub fn fibo<F: FieldExt>(a: F, b: F, n: usize) -> F {
+    let (mut a, mut b) = (a, b);
+    for _ in 2..n {
+        let c = a + b;
+        a = b;
+        b = c;
+    }
+    b
+}
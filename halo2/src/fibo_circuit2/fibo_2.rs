@@ -1,28 +1,33 @@
-mod fibo_circuit2;
+use std::env;
 
-use halo2_proofs::{dev::MockProver, pasta::Fp};
-
-use crate::fibo_circuit2::FiboCircuit;
+use halo2_proofs::{dev::MockProver, halo2curves::pasta::Fp};
+use zkG::backend::{Backend, GenericCircuit};
+use zkG::fibo_circuit2::FiboInput;
 
 fn main() {
-    println!("Setting up the circuit...");
-    let k = 5;
-    let a = Fp::from(1);
-    let b = Fp::from(1);
-    let out: Fp = Fp::from(55);
-
-    let circuit = FiboCircuit {
-        a: Some(a),
-        b: Some(b),
+    let backend: Backend = match env::args().nth(1) {
+        Some(name) => name.parse().unwrap_or_else(|e| panic!("{}", e)),
+        None => Backend::IpaVesta,
     };
 
-    let public_input = vec![a, b, out];
+    println!("Setting up the circuit...");
+    let input = FiboInput::<10> { a: 1, b: 1 };
 
-    println!("Calculating the proof...");
-    let prover = MockProver::run(k, &circuit, vec![public_input.clone()]).unwrap();
+    let circuit = input.circuit::<Fp>();
+    let public_input = input.instances::<Fp>();
 
-    println!("Verifing proof...");
+    let k = backend.min_k(&input).unwrap();
+    println!("The circuit fits in 2^{} rows", k);
+
+    println!("Checking the constraints...");
+    let prover = MockProver::run(k, &circuit, public_input).unwrap();
     prover.assert_satisfied();
 
+    println!("Calculating the proof with {}...", backend);
+    let proof = backend.prove(&input, None).unwrap();
+
+    println!("Verifing proof...");
+    backend.verify(&input, &proof, None).unwrap();
+
     println!("Proof is verified!");
 }
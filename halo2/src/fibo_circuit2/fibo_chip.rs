@@ -1,14 +1,23 @@
-use halo2_proofs::circuit::{AssignedCell, Layouter};
+use halo2_proofs::circuit::{Chip, Layouter};
+use halo2_proofs::plonk::*;
 use halo2_proofs::plonk::{Advice, Column, Error};
 use halo2_proofs::poly::Rotation;
-use halo2_proofs::{arithmetic::FieldExt, plonk::*};
 use std::marker::PhantomData;
 
+use crate::cell::{AssignedCell, RegionExt};
+use crate::field::FieldExt;
+use crate::instance::InstanceColumns;
+
+/// The Fibonacci sequence down a single advice column.
+pub trait FiboColumnInstructions<F: FieldExt>: Chip<F> {
+    fn assign(&self, layouter: impl Layouter<F>, nrows: usize) -> Result<AssignedCell<F>, Error>;
+}
+
 #[derive(Clone, Debug)]
 pub struct FiboConfig {
     pub advice: Column<Advice>,
     pub selector: Selector,
-    pub instance: Column<Instance>,
+    pub instance: InstanceColumns,
 }
 
 pub struct FiboChip<F: FieldExt> {
@@ -16,8 +25,18 @@ pub struct FiboChip<F: FieldExt> {
     _marker: std::marker::PhantomData<F>,
 }
 
-#[derive(Debug, Clone)]
-pub struct ACell<F: FieldExt>(AssignedCell<F, F>);
+impl<F: FieldExt> Chip<F> for FiboChip<F> {
+    type Config = FiboConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
 
 impl<F: FieldExt> FiboChip<F> {
     pub fn construct(config: FiboConfig) -> Self {
@@ -29,12 +48,11 @@ impl<F: FieldExt> FiboChip<F> {
     pub fn configure(
         meta: &mut ConstraintSystem<F>,
         advice: Column<Advice>,
-        instance: Column<Instance>,
+        instance: InstanceColumns,
     ) -> FiboConfig {
         let selector = meta.selector();
 
         meta.enable_equality(advice);
-        meta.enable_equality(instance);
 
         // col_a  | col_b  | col_c | selector
         //   a       b         c       s
@@ -52,60 +70,47 @@ impl<F: FieldExt> FiboChip<F> {
             instance,
         }
     }
+}
 
-    pub fn assign(
+impl<F: FieldExt> FiboColumnInstructions<F> for FiboChip<F> {
+    fn assign(
         &self,
         mut layouter: impl Layouter<F>,
         nrows: usize,
-    ) -> Result<AssignedCell<F, F>, Error> {
+    ) -> Result<AssignedCell<F>, Error> {
         layouter.assign_region(
             || "first row",
             |mut region| {
                 self.config.selector.enable(&mut region, 0)?;
                 self.config.selector.enable(&mut region, 1)?;
-                let mut a_cell = region.assign_advice_from_instance(
-                    || "f(0)",
-                    self.config.instance,
-                    0,
-                    self.config.advice,
-                    0,
-                )?;
+                let (instance_a, row_a) = self.config.instance.get("a")?;
+                let (instance_b, row_b) = self.config.instance.get("b")?;
+                let (instance_out, row_out) = self.config.instance.get("out")?;
 
-                let mut b_cell = region.assign_advice_from_instance(
-                    || "f(1)",
-                    self.config.instance,
-                    1,
-                    self.config.advice,
-                    1,
-                )?;
+                let mut a_cell =
+                    region.assign_from_instance(self.config.advice, 0, instance_a, row_a)?;
+
+                let mut b_cell =
+                    region.assign_from_instance(self.config.advice, 1, instance_b, row_b)?;
 
                 for i in 2..nrows {
                     if i < nrows - 2 {
                         self.config.selector.enable(&mut region, i)?;
                     }
 
-                    let r = a_cell
-                        .value()
-                        .copied()
-                        .and_then(|a| b_cell.value().copied().map(|b| a + b));
+                    let r = a_cell.field_value() + b_cell.field_value();
 
-                    let c_cell = region.assign_advice(
-                        || "f(n)",
-                        self.config.advice,
-                        i,
-                        || r.ok_or(Error::Synthesis),
-                    )?;
+                    let c_cell = region.assign(self.config.advice, i, r);
 
                     a_cell = b_cell;
                     b_cell = c_cell;
                 }
 
-                region.assign_advice_from_instance(
-                    || "out",
-                    self.config.instance,
-                    2,
+                region.assign_from_instance(
                     self.config.advice,
                     nrows - 1,
+                    instance_out,
+                    row_out,
                 )?;
 
                 Ok(b_cell)
@@ -1,19 +1,34 @@
 mod fibo_chip;
 
-use crate::fibo_circuit2::fibo_chip::{FiboChip, FiboConfig};
-use halo2_proofs::arithmetic::FieldExt;
-use halo2_proofs::circuit::{Layouter, SimpleFloorPlanner};
+pub use self::fibo_chip::{FiboChip, FiboColumnInstructions, FiboConfig};
+
+use crate::backend::GenericCircuit;
+use crate::fibo_circuit::{fibo, FiboPrivateInputs, FiboPublicInputs, INSTANCE_LAYOUT};
+use crate::field::FieldExt;
+use crate::input::{CircuitInput, CircuitWithInstances, InputError};
+use crate::instance::{InstanceColumns, InstanceLayout, NamedInstances};
+use crate::planner::SimpleFloorPlanner;
+use halo2_proofs::circuit::{Layouter, Value};
 use halo2_proofs::plonk::{Circuit, ConstraintSystem, Error};
+use serde::Deserialize;
 
+/// Proves the `N`-th term of the Fibonacci sequence starting at `a, b`.
 #[derive(Default)]
-pub struct FiboCircuit<F: FieldExt> {
-    pub a: Option<F>,
-    pub b: Option<F>,
+pub struct FiboCircuit<F: FieldExt, const N: usize = 10> {
+    pub a: Value<F>,
+    pub b: Value<F>,
 }
 
-impl<F: FieldExt> Circuit<F> for FiboCircuit<F> {
+impl<F: FieldExt, const N: usize> NamedInstances for FiboCircuit<F, N> {
+    fn instance_layout() -> InstanceLayout {
+        INSTANCE_LAYOUT
+    }
+}
+
+impl<F: FieldExt, const N: usize> Circuit<F> for FiboCircuit<F, N> {
     type Config = FiboConfig;
     type FloorPlanner = SimpleFloorPlanner;
+    type Params = ();
 
     fn without_witnesses(&self) -> Self {
         Self::default()
@@ -21,7 +36,7 @@ impl<F: FieldExt> Circuit<F> for FiboCircuit<F> {
 
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
         let advices = meta.advice_column();
-        let instance = meta.instance_column();
+        let instance = InstanceColumns::configure(meta, INSTANCE_LAYOUT);
         FiboChip::configure(meta, advices, instance)
     }
 
@@ -32,9 +47,56 @@ impl<F: FieldExt> Circuit<F> for FiboCircuit<F> {
     ) -> Result<(), Error> {
         let chip: FiboChip<F> = FiboChip::construct(config);
 
-        chip.assign(layouter.namespace(|| "First Row Assignment"), 10)
+        chip.assign(layouter.namespace(|| "First Row Assignment"), N)
             .unwrap();
 
         Ok(())
     }
 }
+
+/// The inputs of [`FiboCircuit`], independent of the field it is built over.
+#[derive(Clone, Debug)]
+pub struct FiboInput<const N: usize> {
+    pub a: u64,
+    pub b: u64,
+}
+
+impl<const N: usize> GenericCircuit for FiboInput<N> {
+    type Circuit<F: FieldExt> = FiboCircuit<F, N>;
+
+    fn circuit<F: FieldExt>(&self) -> Self::Circuit<F> {
+        FiboCircuit {
+            a: Value::known(F::from(self.a)),
+            b: Value::known(F::from(self.b)),
+        }
+    }
+
+    fn instances<F: FieldExt>(&self) -> Vec<Vec<F>> {
+        let (a, b) = (F::from(self.a), F::from(self.b));
+        INSTANCE_LAYOUT
+            .assemble(&[("a", a), ("b", b), ("out", fibo(a, b, N))])
+            .unwrap()
+    }
+}
+
+/// The JSON inputs of [`FiboCircuit`], which has the same schema as
+/// [`crate::fibo_circuit::FiboInputFile`].
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FiboInputFile<const N: usize> {
+    pub private: FiboPrivateInputs,
+    pub public: FiboPublicInputs,
+}
+
+impl<const N: usize> CircuitInput for FiboInputFile<N> {
+    type Circuit<F: FieldExt> = FiboCircuit<F, N>;
+
+    fn build<F: FieldExt>(&self) -> Result<CircuitWithInstances<Self, F>, InputError> {
+        let circuit = FiboCircuit {
+            a: Value::known(self.private.a.to_field("private.a")?),
+            b: Value::known(self.private.b.to_field("private.b")?),
+        };
+
+        Ok((circuit, self.public.instances::<F, N>(&self.private)?))
+    }
+}
@@ -0,0 +1,165 @@
+use halo2_proofs::circuit::{Chip, Layouter, Value};
+use halo2_proofs::plonk::*;
+use halo2_proofs::plonk::{Advice, Column, Error};
+use halo2_proofs::poly::Rotation;
+use std::marker::PhantomData;
+
+use crate::cell::{AssignedCell, RegionExt};
+use crate::field::FieldExt;
+use crate::instance::InstanceColumns;
+
+/// The Fibonacci sequence, several terms per row.
+pub trait FiboWideInstructions<F: FieldExt>: Chip<F> {
+    /// Assigns the first `n` terms starting at `a, b`, `W` terms per row, and
+    /// returns the cells of `a`, `b` and the `n`-th term.
+    #[allow(clippy::type_complexity)]
+    fn assign(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Value<F>,
+        b: Value<F>,
+        n: usize,
+    ) -> Result<(AssignedCell<F>, AssignedCell<F>, AssignedCell<F>), Error>;
+
+    /// Constrains `cell` to equal the public input called `name`.
+    fn expose_public(
+        &self,
+        layouter: impl Layouter<F>,
+        cell: &AssignedCell<F>,
+        name: &str,
+    ) -> Result<(), Error>;
+}
+
+#[derive(Clone, Debug)]
+pub struct FiboConfig<const W: usize> {
+    pub advice: [Column<Advice>; W],
+    pub selector: Selector,
+    pub link: Selector,
+    pub instance: InstanceColumns,
+}
+
+pub struct FiboChip<F: FieldExt, const W: usize> {
+    config: FiboConfig<W>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const W: usize> Chip<F> for FiboChip<F, W> {
+    type Config = FiboConfig<W>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt, const W: usize> FiboChip<F, W> {
+    pub fn construct(config: FiboConfig<W>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; W],
+        instance: InstanceColumns,
+    ) -> FiboConfig<W> {
+        assert!(W >= 2, "a row must hold at least two terms");
+
+        let selector = meta.selector();
+        let link = meta.selector();
+
+        for column in advice {
+            meta.enable_equality(column);
+        }
+
+        // col_0    | col_1    | ... | col_{W-1}  | selector | link
+        // f(0)       f(1)       ...   f(W-1)         1          0
+        // f(W)       f(W+1)     ...   f(2W-1)        1          1
+        //
+        // with two terms per row, every addition crosses rows
+        if W > 2 {
+            meta.create_gate("add in row", |meta| {
+                let s = meta.query_selector(selector);
+                let cur = advice.map(|column| meta.query_advice(column, Rotation::cur()));
+                (2..W)
+                    .map(|j| s.clone() * (cur[j].clone() - cur[j - 1].clone() - cur[j - 2].clone()))
+                    .collect::<Vec<_>>()
+            });
+        }
+
+        // every row but the first continues the sequence of the row above it
+        meta.create_gate("add across rows", |meta| {
+            let s = meta.query_selector(link);
+            let cur = advice.map(|column| meta.query_advice(column, Rotation::cur()));
+            let prev = advice.map(|column| meta.query_advice(column, Rotation::prev()));
+            vec![
+                s.clone() * (cur[0].clone() - prev[W - 2].clone() - prev[W - 1].clone()),
+                s * (cur[1].clone() - prev[W - 1].clone() - cur[0].clone()),
+            ]
+        });
+
+        FiboConfig {
+            advice,
+            selector,
+            link,
+            instance,
+        }
+    }
+}
+
+impl<F: FieldExt, const W: usize> FiboWideInstructions<F> for FiboChip<F, W> {
+    #[allow(clippy::type_complexity)]
+    fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Value<F>,
+        b: Value<F>,
+        n: usize,
+    ) -> Result<(AssignedCell<F>, AssignedCell<F>, AssignedCell<F>), Error> {
+        assert!(n >= 2, "the sequence starts with two terms");
+        let nrows = n.div_ceil(W);
+
+        layouter.assign_region(
+            || "terms",
+            |mut region| {
+                for row in 0..nrows {
+                    self.config.selector.enable(&mut region, row)?;
+                    if row > 0 {
+                        self.config.link.enable(&mut region, row)?;
+                    }
+                }
+
+                let mut terms = vec![
+                    region.assign(self.config.advice[0], 0, a),
+                    region.assign(self.config.advice[1], 0, b),
+                ];
+
+                // the last row is filled up with terms past the n-th
+                for i in 2..nrows * W {
+                    let c = terms[i - 2]
+                        .value()
+                        .and_then(|a| terms[i - 1].value().map(|b| *a + *b));
+                    let cell = region.assign(self.config.advice[i % W], i / W, c);
+                    terms.push(cell);
+                }
+
+                Ok((terms[0].clone(), terms[1].clone(), terms[n - 1].clone()))
+            },
+        )
+    }
+
+    fn expose_public(
+        &self,
+        layouter: impl Layouter<F>,
+        cell: &AssignedCell<F>,
+        name: &str,
+    ) -> Result<(), Error> {
+        self.config.instance.expose(layouter, cell.cell(), name)
+    }
+}
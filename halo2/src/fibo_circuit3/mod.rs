@@ -0,0 +1,143 @@
+mod fibo_chip;
+
+pub use self::fibo_chip::{FiboChip, FiboConfig, FiboWideInstructions};
+
+use crate::backend::GenericCircuit;
+use crate::fibo_circuit::{fibo, FiboPrivateInputs, FiboPublicInputs, INSTANCE_LAYOUT};
+use crate::field::FieldExt;
+use crate::input::{CircuitInput, CircuitWithInstances, InputError};
+use crate::instance::{InstanceColumns, InstanceLayout, NamedInstances};
+use crate::planner::SimpleFloorPlanner;
+use halo2_proofs::circuit::{Layouter, Value};
+use halo2_proofs::plonk::{Circuit, ConstraintSystem, Error};
+use serde::Deserialize;
+
+/// Proves the `N`-th term of the Fibonacci sequence starting at `a, b`, with
+/// `W` terms per row.
+///
+/// The circuit uses `N / W` rows (rounded up) and `W` advice columns, so a wider
+/// row trades columns, which grow the proof, for rows, which grow `k`.
+#[derive(Default)]
+pub struct FiboCircuit<F: FieldExt, const N: usize = 10, const W: usize = 4> {
+    pub a: Value<F>,
+    pub b: Value<F>,
+}
+
+impl<F: FieldExt, const N: usize, const W: usize> NamedInstances for FiboCircuit<F, N, W> {
+    fn instance_layout() -> InstanceLayout {
+        INSTANCE_LAYOUT
+    }
+}
+
+impl<F: FieldExt, const N: usize, const W: usize> Circuit<F> for FiboCircuit<F, N, W> {
+    type Config = FiboConfig<W>;
+    type FloorPlanner = SimpleFloorPlanner;
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); W].map(|_| meta.advice_column());
+        let instance = InstanceColumns::configure(meta, INSTANCE_LAYOUT);
+        FiboChip::configure(meta, advice, instance)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip: FiboChip<F, W> = FiboChip::construct(config);
+
+        let (a, b, out) = chip.assign(layouter.namespace(|| "terms"), self.a, self.b, N)?;
+
+        chip.expose_public(layouter.namespace(|| "private a"), &a, "a")?;
+        chip.expose_public(layouter.namespace(|| "private b"), &b, "b")?;
+        chip.expose_public(layouter.namespace(|| "out"), &out, "out")?;
+
+        Ok(())
+    }
+}
+
+/// The inputs of [`FiboCircuit`], independent of the field it is built over.
+#[derive(Clone, Debug)]
+pub struct FiboInput<const N: usize, const W: usize> {
+    pub a: u64,
+    pub b: u64,
+}
+
+impl<const N: usize, const W: usize> GenericCircuit for FiboInput<N, W> {
+    type Circuit<F: FieldExt> = FiboCircuit<F, N, W>;
+
+    fn circuit<F: FieldExt>(&self) -> Self::Circuit<F> {
+        FiboCircuit {
+            a: Value::known(F::from(self.a)),
+            b: Value::known(F::from(self.b)),
+        }
+    }
+
+    fn instances<F: FieldExt>(&self) -> Vec<Vec<F>> {
+        let (a, b) = (F::from(self.a), F::from(self.b));
+        INSTANCE_LAYOUT
+            .assemble(&[("a", a), ("b", b), ("out", fibo(a, b, N))])
+            .unwrap()
+    }
+}
+
+/// The JSON inputs of [`FiboCircuit`], which has the same schema as
+/// [`crate::fibo_circuit::FiboInputFile`].
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FiboInputFile<const N: usize, const W: usize> {
+    pub private: FiboPrivateInputs,
+    pub public: FiboPublicInputs,
+}
+
+impl<const N: usize, const W: usize> CircuitInput for FiboInputFile<N, W> {
+    type Circuit<F: FieldExt> = FiboCircuit<F, N, W>;
+
+    fn build<F: FieldExt>(&self) -> Result<CircuitWithInstances<Self, F>, InputError> {
+        let circuit = FiboCircuit {
+            a: Value::known(self.private.a.to_field("private.a")?),
+            b: Value::known(self.private.b.to_field("private.b")?),
+        };
+
+        Ok((circuit, self.public.instances::<F, N>(&self.private)?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::{arithmetic::Field, dev::MockProver, halo2curves::pasta::Fp};
+
+    use super::*;
+
+    fn check<const N: usize, const W: usize>(k: u32) {
+        let input = FiboInput::<N, W> { a: 1, b: 1 };
+        let circuit = input.circuit::<Fp>();
+        let mut instances = input.instances::<Fp>();
+
+        MockProver::run(k, &circuit, instances.clone())
+            .unwrap()
+            .assert_satisfied();
+
+        instances[0][2] += Fp::ONE;
+        assert!(MockProver::run(k, &circuit, instances)
+            .unwrap()
+            .verify()
+            .is_err());
+    }
+
+    #[test]
+    fn wide_row_test() {
+        check::<10, 2>(4);
+        check::<10, 3>(4);
+        check::<10, 4>(4);
+        check::<10, 10>(4);
+        check::<40, 8>(4);
+        check::<41, 8>(4);
+        check::<2, 4>(4);
+    }
+}
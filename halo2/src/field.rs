@@ -0,0 +1,19 @@
+//! The field bound of the chips.
+//!
+//! halo2-axiom works with `ff`'s `PrimeField`, which lacks the helpers of
+//! halo2_proofs 0.1's `FieldExt`. [`FieldExt`] brings back the one the chips
+//! use and is implemented for every 256-bit field a circuit can be built over,
+//! among them the scalar fields the backends prove over: BN254's and the Pasta
+//! fields of [`pasta`](crate::pasta).
+
+use halo2_proofs::halo2curves::ff::{FromUniformBytes, PrimeField};
+
+pub trait FieldExt: PrimeField<Repr = [u8; 32]> + FromUniformBytes<64> + Ord {
+    /// The low 128 bits of the canonical representation.
+    fn get_lower_128(&self) -> u128 {
+        let repr = self.to_repr();
+        u128::from_le_bytes(repr[..16].try_into().unwrap())
+    }
+}
+
+impl<F: PrimeField<Repr = [u8; 32]> + FromUniformBytes<64> + Ord> FieldExt for F {}
@@ -0,0 +1,662 @@
+//! Signed fixed-point numbers with `FRAC` fractional bits, e.g. fees and
+//! interest rates.
+//!
+//! A number `x` is the integer `x·2^FRAC`, stored as an `i64` natively and as
+//! a field element in a circuit, where negative values are `p - |x|`. A cell is
+//! a valid number if `x + 2^63` fits in 64 bits, which every operation checks
+//! for its result, so an overflow makes the circuit unsatisfiable.
+//!
+//! Products and quotients are rounded to the nearest number, ties upwards,
+//! with a witnessed remainder `r`:
+//!
+//! ```text
+//!  a | b | c | r | s    | t              | selector
+//!  a   b   c   r          r·2^(64 - FRAC)  q_mul: a·b + 2^(FRAC - 1) = c·2^FRAC + r
+//!  a   b   c   r   sign   d - 1            q_div: 2n·2^FRAC + d = c·2d + r
+//!  2d - 1 - r
+//! ```
+//!
+//! For a division `n = ±a` and `d = ±b`, negated if `b < 0` so that `d > 0`.
+//! Range checking `r` and `t` for a product, and `r`, `d - 1` and `2d - 1 - r`
+//! for a quotient, leaves one remainder in `[0, 2^FRAC)` or `[0, 2d)`, and so
+//! one rounded result. All of these are far smaller than `p`, so the equations
+//! hold over the integers. Dividing by zero has no `d > 0` and is unsatisfiable.
+//!
+//! [`FixedPoint`] is the native reference, which the circuit matches bit for
+//! bit: its operations return `None` exactly when the circuit is unsatisfiable.
+
+use std::fmt;
+
+use halo2_proofs::{
+    circuit::{Chip, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+
+use crate::cell::{AssignedCell, RegionExt};
+use crate::field::FieldExt;
+use crate::integer::{ByteTable, IntegerChip, IntegerConfig, IntegerInstructions};
+
+/// A signed number with `FRAC` fractional bits.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FixedPoint<const FRAC: usize>(pub i64);
+
+impl<const FRAC: usize> FixedPoint<FRAC> {
+    pub const ONE: Self = Self(1 << FRAC);
+
+    /// The nearest number to `value`, if it is in range.
+    pub fn from_f64(value: f64) -> Option<Self> {
+        let raw = (value * (1u64 << FRAC) as f64).round();
+        (raw >= i64::MIN as f64 && raw < i64::MAX as f64).then_some(Self(raw as i64))
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.0 as f64 / (1u64 << FRAC) as f64
+    }
+
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        self.0.checked_add(other.0).map(Self)
+    }
+
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        self.0.checked_sub(other.0).map(Self)
+    }
+
+    /// The product, rounded to the nearest number, ties upwards.
+    pub fn checked_mul(self, other: Self) -> Option<Self> {
+        let (c, _) = Self::mul_rem(self.0, other.0);
+        c.try_into().ok().map(Self)
+    }
+
+    /// The quotient, rounded to the nearest number, ties upwards.
+    pub fn checked_div(self, other: Self) -> Option<Self> {
+        if other.0 == 0 {
+            return None;
+        }
+        let (c, _) = Self::div_rem(self.0, other.0);
+        c.try_into().ok().map(Self)
+    }
+
+    /// `a·b / 2^FRAC` rounded, and the remainder.
+    fn mul_rem(a: i64, b: i64) -> (i128, i128) {
+        let product = a as i128 * b as i128 + (1 << (FRAC - 1));
+        (product.div_euclid(1 << FRAC), product.rem_euclid(1 << FRAC))
+    }
+
+    /// `a·2^FRAC / b` rounded, and the remainder, for `b != 0`.
+    fn div_rem(a: i64, b: i64) -> (i128, i128) {
+        let (n, d) = if b < 0 {
+            (-(a as i128), -(b as i128))
+        } else {
+            (a as i128, b as i128)
+        };
+        let numerator = 2 * n * (1 << FRAC) + d;
+        (numerator.div_euclid(2 * d), numerator.rem_euclid(2 * d))
+    }
+}
+
+impl<const FRAC: usize> fmt::Display for FixedPoint<FRAC> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_f64())
+    }
+}
+
+/// A cell holding a number with `FRAC` fractional bits.
+#[derive(Clone, Debug)]
+pub struct AssignedFixed<F: FieldExt, const FRAC: usize>(pub AssignedCell<F>);
+
+impl<F: FieldExt, const FRAC: usize> AssignedFixed<F, FRAC> {
+    pub fn value(&self) -> Value<FixedPoint<FRAC>> {
+        self.0.field_value().map(|v| {
+            let shifted = (v + F::from_u128(1 << 63)).get_lower_128() as i128;
+            FixedPoint((shifted - (1 << 63)) as i64)
+        })
+    }
+}
+
+/// A signed integer as a field element.
+pub(crate) fn signed<F: FieldExt>(value: i128) -> F {
+    if value < 0 {
+        -F::from_u128(value.unsigned_abs())
+    } else {
+        F::from_u128(value as u128)
+    }
+}
+
+/// Signed fixed-point arithmetic with `FRAC` fractional bits.
+pub trait FixedPointInstructions<F: FieldExt, const FRAC: usize>: Chip<F> {
+    /// Loads the byte table of the range checks; call once per circuit.
+    fn load_table(&self, layouter: impl Layouter<F>) -> Result<(), Error>;
+
+    /// Assigns a private number, checked to be in range.
+    fn witness(
+        &self,
+        layouter: impl Layouter<F>,
+        value: Value<FixedPoint<FRAC>>,
+    ) -> Result<AssignedFixed<F, FRAC>, Error>;
+
+    fn add(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedFixed<F, FRAC>,
+        b: &AssignedFixed<F, FRAC>,
+    ) -> Result<AssignedFixed<F, FRAC>, Error>;
+
+    fn sub(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedFixed<F, FRAC>,
+        b: &AssignedFixed<F, FRAC>,
+    ) -> Result<AssignedFixed<F, FRAC>, Error>;
+
+    /// `a·b`, rounded to the nearest number, ties upwards.
+    fn mul(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedFixed<F, FRAC>,
+        b: &AssignedFixed<F, FRAC>,
+    ) -> Result<AssignedFixed<F, FRAC>, Error>;
+
+    /// `a / b`, rounded to the nearest number, ties upwards. The circuit is
+    /// unsatisfiable if `b` is zero.
+    fn div(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedFixed<F, FRAC>,
+        b: &AssignedFixed<F, FRAC>,
+    ) -> Result<AssignedFixed<F, FRAC>, Error>;
+}
+
+#[derive(Clone, Debug)]
+pub struct FixedPointConfig<F: FieldExt, const FRAC: usize> {
+    integer: IntegerConfig<F>,
+    /// `a, b, c, r, s, t` as in the module documentation
+    advice: [Column<Advice>; 6],
+    /// `x` in `a` and `x + 2^63` in `b`
+    q_signed: Selector,
+    q_add: Selector,
+    q_sub: Selector,
+    q_mul: Selector,
+    q_div: Selector,
+}
+
+pub struct FixedPointChip<F: FieldExt, const FRAC: usize> {
+    config: FixedPointConfig<F, FRAC>,
+    integer: IntegerChip<F>,
+}
+
+impl<F: FieldExt, const FRAC: usize> Chip<F> for FixedPointChip<F, FRAC> {
+    type Config = FixedPointConfig<F, FRAC>;
+    type Loaded = ByteTable<F>;
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        self.integer.loaded()
+    }
+}
+
+impl<F: FieldExt, const FRAC: usize> FixedPointChip<F, FRAC> {
+    pub fn construct(config: FixedPointConfig<F, FRAC>) -> Self {
+        Self {
+            integer: IntegerChip::construct(config.integer.clone()),
+            config,
+        }
+    }
+
+    /// Configures an [`IntegerChip`] for the range checks on the same columns.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 6],
+        constants: Column<Fixed>,
+    ) -> FixedPointConfig<F, FRAC> {
+        assert!(0 < FRAC && FRAC < 63, "there are 1 to 62 fractional bits");
+        let integer = IntegerChip::configure(meta, advice, constants);
+        let q_signed = meta.selector();
+        let q_add = meta.selector();
+        let q_sub = meta.selector();
+        let q_mul = meta.selector();
+        let q_div = meta.selector();
+        // `t` is range checked too
+        meta.enable_equality(advice[5]);
+
+        let constant = |value: u128| Expression::Constant(F::from_u128(value));
+
+        meta.create_gate("signed", |meta| {
+            let q_signed = meta.query_selector(q_signed);
+            let x = meta.query_advice(advice[0], Rotation::cur());
+            let shifted = meta.query_advice(advice[1], Rotation::cur());
+            vec![q_signed * (shifted - (x + constant(1 << 63)))]
+        });
+
+        meta.create_gate("fixed add", |meta| {
+            let q_add = meta.query_selector(q_add);
+            let [a, b, c] = [0, 1, 2].map(|i| meta.query_advice(advice[i], Rotation::cur()));
+            vec![q_add * (c - (a + b))]
+        });
+
+        meta.create_gate("fixed sub", |meta| {
+            let q_sub = meta.query_selector(q_sub);
+            let [a, b, c] = [0, 1, 2].map(|i| meta.query_advice(advice[i], Rotation::cur()));
+            vec![q_sub * (c - (a - b))]
+        });
+
+        meta.create_gate("fixed mul", |meta| {
+            let q_mul = meta.query_selector(q_mul);
+            let [a, b, c, r] = [0, 1, 2, 3].map(|i| meta.query_advice(advice[i], Rotation::cur()));
+            let t = meta.query_advice(advice[5], Rotation::cur());
+            vec![
+                q_mul.clone()
+                    * (a * b + constant(1 << (FRAC - 1)) - (c * constant(1 << FRAC) + r.clone())),
+                q_mul * (t - r * constant(1 << (64 - FRAC))),
+            ]
+        });
+
+        meta.create_gate("fixed div", |meta| {
+            let q_div = meta.query_selector(q_div);
+            let [a, b, c, r, s, t] =
+                advice.map(|column| meta.query_advice(column, Rotation::cur()));
+            let slack = meta.query_advice(advice[0], Rotation::next());
+            let one = constant(1);
+            let two = constant(2);
+
+            let sign = one.clone() - two.clone() * s.clone();
+            let n = a * sign.clone();
+            let d = b * sign;
+            vec![
+                q_div.clone() * s.clone() * (one.clone() - s),
+                q_div.clone()
+                    * (two.clone() * n * constant(1 << FRAC) + d.clone()
+                        - (c * two.clone() * d.clone() + r.clone())),
+                q_div.clone() * (t - (d.clone() - one.clone())),
+                q_div * (slack - (two * d - one - r)),
+            ]
+        });
+
+        FixedPointConfig {
+            integer,
+            advice,
+            q_signed,
+            q_add,
+            q_sub,
+            q_mul,
+            q_div,
+        }
+    }
+
+    /// Assigns an operation row with `c` and extra cells, by position in the
+    /// row, where position 6 is the first column of the next row. Returns the
+    /// cells of `c` and of the extras.
+    fn assign_op(
+        &self,
+        mut layouter: impl Layouter<F>,
+        selector: Selector,
+        a: &AssignedFixed<F, FRAC>,
+        b: &AssignedFixed<F, FRAC>,
+        c: Value<i128>,
+        extra: &[(usize, Value<i128>)],
+    ) -> Result<Vec<AssignedCell<F>>, Error> {
+        let advice = self.config.advice;
+        layouter.assign_region(
+            || "fixed op",
+            |mut region| {
+                selector.enable(&mut region, 0)?;
+                a.0.copy_to(&mut region, advice[0], 0);
+                b.0.copy_to(&mut region, advice[1], 0);
+
+                let mut cells = vec![];
+                for (position, value) in [(2, c)].iter().chain(extra) {
+                    cells.push(region.assign(
+                        advice[position % 6],
+                        position / 6,
+                        value.map(signed::<F>),
+                    ));
+                }
+                Ok(cells)
+            },
+        )
+    }
+
+    /// Checks that `x + 2^63` fits in 64 bits.
+    fn signed_range_check(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: &AssignedCell<F>,
+    ) -> Result<AssignedFixed<F, FRAC>, Error> {
+        let shifted = layouter.assign_region(
+            || "x + 2^63",
+            |mut region| {
+                self.config.q_signed.enable(&mut region, 0)?;
+                x.copy_to(&mut region, self.config.advice[0], 0);
+                Ok(region.assign(
+                    self.config.advice[1],
+                    0,
+                    x.field_value().map(|x| x + F::from_u128(1 << 63)),
+                ))
+            },
+        )?;
+        self.integer
+            .range_check::<64>(layouter.namespace(|| "x + 2^63"), &shifted)?;
+        Ok(AssignedFixed(x.clone()))
+    }
+}
+
+impl<F: FieldExt, const FRAC: usize> FixedPointInstructions<F, FRAC> for FixedPointChip<F, FRAC> {
+    fn load_table(&self, layouter: impl Layouter<F>) -> Result<(), Error> {
+        self.integer.load_table(layouter)
+    }
+
+    fn witness(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Value<FixedPoint<FRAC>>,
+    ) -> Result<AssignedFixed<F, FRAC>, Error> {
+        let cell = layouter.assign_region(
+            || "witness fixed",
+            |mut region| {
+                Ok(region.assign(
+                    self.config.advice[0],
+                    0,
+                    value.map(|x| signed::<F>(x.0 as i128)),
+                ))
+            },
+        )?;
+        self.signed_range_check(layouter.namespace(|| "x"), &cell)
+    }
+
+    fn add(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedFixed<F, FRAC>,
+        b: &AssignedFixed<F, FRAC>,
+    ) -> Result<AssignedFixed<F, FRAC>, Error> {
+        let c = a
+            .value()
+            .zip(b.value())
+            .map(|(a, b)| a.0 as i128 + b.0 as i128);
+        let cells = self.assign_op(
+            layouter.namespace(|| "add"),
+            self.config.q_add,
+            a,
+            b,
+            c,
+            &[],
+        )?;
+        self.signed_range_check(layouter.namespace(|| "c"), &cells[0])
+    }
+
+    fn sub(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedFixed<F, FRAC>,
+        b: &AssignedFixed<F, FRAC>,
+    ) -> Result<AssignedFixed<F, FRAC>, Error> {
+        let c = a
+            .value()
+            .zip(b.value())
+            .map(|(a, b)| a.0 as i128 - b.0 as i128);
+        let cells = self.assign_op(
+            layouter.namespace(|| "sub"),
+            self.config.q_sub,
+            a,
+            b,
+            c,
+            &[],
+        )?;
+        self.signed_range_check(layouter.namespace(|| "c"), &cells[0])
+    }
+
+    fn mul(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedFixed<F, FRAC>,
+        b: &AssignedFixed<F, FRAC>,
+    ) -> Result<AssignedFixed<F, FRAC>, Error> {
+        let values = a
+            .value()
+            .zip(b.value())
+            .map(|(a, b)| FixedPoint::<FRAC>::mul_rem(a.0, b.0));
+        let c = values.map(|(c, _)| c);
+        let r = values.map(|(_, r)| r);
+        let t = r.map(|r| r << (64 - FRAC));
+
+        let cells = self.assign_op(
+            layouter.namespace(|| "mul"),
+            self.config.q_mul,
+            a,
+            b,
+            c,
+            &[(3, r), (5, t)],
+        )?;
+        for (cell, name) in cells[1..].iter().zip(["r", "t"]) {
+            self.integer
+                .range_check::<64>(layouter.namespace(|| name), cell)?;
+        }
+        self.signed_range_check(layouter.namespace(|| "c"), &cells[0])
+    }
+
+    fn div(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedFixed<F, FRAC>,
+        b: &AssignedFixed<F, FRAC>,
+    ) -> Result<AssignedFixed<F, FRAC>, Error> {
+        // a zero divisor has no valid witness, so any is as good as another
+        let values = a.value().zip(b.value()).map(|(a, b)| match b.0 {
+            0 => (0, 0),
+            _ => FixedPoint::<FRAC>::div_rem(a.0, b.0),
+        });
+        let c = values.map(|(c, _)| c);
+        let r = values.map(|(_, r)| r);
+        let s = b.value().map(|b| (b.0 < 0) as i128);
+        let d = b.value().map(|b| (b.0 as i128).abs());
+        let t = d.map(|d| d - 1);
+        let slack = d.zip(r).map(|(d, r)| 2 * d - 1 - r);
+
+        let cells = self.assign_op(
+            layouter.namespace(|| "div"),
+            self.config.q_div,
+            a,
+            b,
+            c,
+            &[(3, r), (4, s), (5, t), (6, slack)],
+        )?;
+        for (cell, name) in
+            [&cells[1], &cells[3], &cells[4]]
+                .into_iter()
+                .zip(["r", "d - 1", "2d - 1 - r"])
+        {
+            self.integer
+                .range_check::<64>(layouter.namespace(|| name), cell)?;
+        }
+        self.signed_range_check(layouter.namespace(|| "c"), &cells[0])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use group::ff::PrimeField;
+    use halo2_proofs::{
+        dev::MockProver,
+        halo2curves::pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    use super::*;
+
+    use crate::planner::SimpleFloorPlanner;
+
+    const FRAC: usize = 16;
+    type Fx = FixedPoint<FRAC>;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    enum Op {
+        Add,
+        Sub,
+        Mul,
+        Div,
+    }
+
+    impl Op {
+        const ALL: [Op; 4] = [Op::Add, Op::Sub, Op::Mul, Op::Div];
+
+        fn native(&self, a: Fx, b: Fx) -> Option<Fx> {
+            match self {
+                Op::Add => a.checked_add(b),
+                Op::Sub => a.checked_sub(b),
+                Op::Mul => a.checked_mul(b),
+                Op::Div => a.checked_div(b),
+            }
+        }
+    }
+
+    /// Proves `a op b == c` for a public `c`.
+    #[derive(Default)]
+    struct FixedPointCircuit {
+        op: Option<Op>,
+        a: Value<Fx>,
+        b: Value<Fx>,
+    }
+
+    impl Circuit<Fp> for FixedPointCircuit {
+        type Config = (FixedPointConfig<Fp, FRAC>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                op: self.op,
+                ..Self::default()
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 6].map(|_| meta.advice_column());
+            let constants = meta.fixed_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (FixedPointChip::configure(meta, advice, constants), instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = FixedPointChip::construct(config);
+            chip.load_table(layouter.namespace(|| "table"))?;
+
+            let a = chip.witness(layouter.namespace(|| "a"), self.a)?;
+            let b = chip.witness(layouter.namespace(|| "b"), self.b)?;
+            let op = layouter.namespace(|| "op");
+            let c = match self.op.ok_or(Error::Synthesis)? {
+                Op::Add => chip.add(op, &a, &b)?,
+                Op::Sub => chip.sub(op, &a, &b)?,
+                Op::Mul => chip.mul(op, &a, &b)?,
+                Op::Div => chip.div(op, &a, &b)?,
+            };
+            layouter.constrain_instance(c.0.cell(), instance, 0);
+            Ok(())
+        }
+    }
+
+    fn raw(value: i64) -> Fx {
+        FixedPoint(value)
+    }
+
+    fn fx(value: f64) -> Fx {
+        Fx::from_f64(value).unwrap()
+    }
+
+    /// Checks that the circuit is satisfied exactly when the native operation
+    /// is defined, and then only by its result.
+    fn check(op: Op, a: Fx, b: Fx) {
+        let circuit = FixedPointCircuit {
+            op: Some(op),
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+        let native = op.native(a, b);
+        // the result the prover comes up with when there is none
+        let c = native.unwrap_or(Fx::ONE);
+        let instance = |c: Fx| vec![vec![signed::<Fp>(c.0 as i128)]];
+
+        let result = MockProver::run(10, &circuit, instance(c)).unwrap().verify();
+        assert_eq!(result.is_ok(), native.is_some(), "{:?} {} {}", op, a, b);
+
+        if native.is_some() {
+            for wrong in [raw(c.0.wrapping_add(1)), raw(c.0.wrapping_sub(1))] {
+                assert!(MockProver::run(10, &circuit, instance(wrong))
+                    .unwrap()
+                    .verify()
+                    .is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn native_test() {
+        assert_eq!(fx(1.5).checked_add(fx(-2.25)), Some(fx(-0.75)));
+        assert_eq!(fx(1.5).checked_mul(fx(-2.25)), Some(fx(-3.375)));
+        assert_eq!(fx(-3.375).checked_div(fx(1.5)), Some(fx(-2.25)));
+        assert_eq!(fx(1.0).checked_div(fx(0.0)), None);
+        assert_eq!(raw(i64::MAX).checked_add(raw(1)), None);
+
+        // 1/3 rounds down, 2/3 up, and ties go upwards on both signs
+        assert_eq!(Fx::ONE.checked_div(raw(3 << FRAC)), Some(raw(21845)));
+        assert_eq!(raw(2 << FRAC).checked_div(raw(3 << FRAC)), Some(raw(43691)));
+        assert_eq!(raw(1).checked_mul(fx(0.5)), Some(raw(1)));
+        assert_eq!(raw(-1).checked_mul(fx(0.5)), Some(raw(0)));
+        assert_eq!(raw(1).checked_div(raw(-2 << FRAC)), Some(raw(0)));
+        assert_eq!(raw(-1).checked_div(raw(2 << FRAC)), Some(raw(0)));
+    }
+
+    #[test]
+    fn ops_test() {
+        let values = [
+            fx(0.0),
+            fx(1.5),
+            fx(-2.25),
+            fx(-1000.0 / 3.0),
+            raw(1),
+            raw(i64::MAX),
+            raw(i64::MIN),
+        ];
+        for op in Op::ALL {
+            for a in values {
+                for b in values {
+                    check(op, a, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fee_test() {
+        // a 0.3% fee on 1234.5678 tokens
+        let amount = fx(1234.5678);
+        let rate = fx(0.003);
+        let fee = amount.checked_mul(rate).unwrap();
+        // 0.003 is 197 / 2^16, which is off by up to 2^-17 per token
+        assert!((fee.to_f64() - 3.7037).abs() < 1234.5678 / (1 << 17) as f64);
+        check(Op::Mul, amount, rate);
+        check(Op::Div, fee, rate);
+    }
+
+    #[test]
+    fn out_of_range_test() {
+        // a field element that is no i64, as a number of the right sign would be
+        let circuit = FixedPointCircuit {
+            op: Some(Op::Add),
+            a: Value::known(raw(i64::MAX)),
+            b: Value::known(raw(1)),
+        };
+        let overflowed = Fp::from_u128(1 << 63);
+        assert!(MockProver::run(10, &circuit, vec![vec![overflowed]])
+            .unwrap()
+            .verify()
+            .is_err());
+    }
+}
@@ -0,0 +1,349 @@
+//! Polynomial evaluation with Horner's rule, `p(x) = (..(c_d·x + c_{d-1})·x
+//! + ..)·x + c_0`, one row per coefficient:
+//!
+//! ```text
+//!  acc     | c       | x
+//!  0         c_d       x       q_horner: acc' = acc·x + c
+//!  c_d       c_{d-1}   x
+//!  ...
+//!  p(x)
+//! ```
+//!
+//! A coefficient is a constant of the circuit, a public input or a private
+//! value; see [`Coefficient`]. [`SimplePolynomialCircuit`] is circom's
+//! `simple-polynomial`, `x^3 - x + 7`, with constant coefficients.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{Chip, Layouter, Region, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Fixed, Instance, Selector},
+    poly::Rotation,
+};
+
+use crate::cell::{AssignedCell, RegionExt};
+use crate::field::FieldExt;
+use crate::instance::{InstanceColumns, InstanceLayout, NamedInstances};
+use crate::planner::SimpleFloorPlanner;
+
+/// Where a coefficient comes from.
+#[derive(Clone, Debug)]
+pub enum Coefficient<F: FieldExt> {
+    /// A constant of the circuit.
+    Fixed(F),
+    /// A public input, at a row of an instance column.
+    Instance(Column<Instance>, usize),
+    /// A private value.
+    Witness(Value<F>),
+    /// A cell assigned elsewhere.
+    Cell(AssignedCell<F>),
+}
+
+impl<F: FieldExt> Coefficient<F> {
+    /// Assigns the coefficient to `column` at `offset`. The column needs
+    /// equality enabled for any but private values.
+    pub fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        column: Column<Advice>,
+        offset: usize,
+    ) -> Result<AssignedCell<F>, Error> {
+        match self {
+            Coefficient::Fixed(c) => region.assign_constant(column, offset, *c),
+            Coefficient::Instance(instance, row) => {
+                region.assign_from_instance(column, offset, *instance, *row)
+            }
+            Coefficient::Witness(c) => Ok(region.assign(column, offset, *c)),
+            Coefficient::Cell(cell) => Ok(cell.copy_to(region, column, offset)),
+        }
+    }
+}
+
+/// `p(x)` for the coefficients of `p`, lowest degree first.
+pub fn evaluate<F: FieldExt>(coefficients: &[F], x: F) -> F {
+    coefficients
+        .iter()
+        .rev()
+        .fold(F::ZERO, |acc, c| acc * x + c)
+}
+
+/// Polynomial evaluation with Horner's rule.
+pub trait HornerInstructions<F: FieldExt>: Chip<F> {
+    /// Assigns a private value.
+    fn witness(
+        &self,
+        layouter: impl Layouter<F>,
+        value: Value<F>,
+    ) -> Result<AssignedCell<F>, Error>;
+
+    /// `p(x)` for the coefficients of `p`, lowest degree first. Instance
+    /// columns holding coefficients need equality enabled.
+    fn evaluate(
+        &self,
+        layouter: impl Layouter<F>,
+        coefficients: &[Coefficient<F>],
+        x: &AssignedCell<F>,
+    ) -> Result<AssignedCell<F>, Error>;
+}
+
+#[derive(Clone, Debug)]
+pub struct HornerConfig<F: FieldExt> {
+    /// `acc, c, x`
+    advice: [Column<Advice>; 3],
+    q_horner: Selector,
+    _marker: PhantomData<F>,
+}
+
+pub struct HornerChip<F: FieldExt> {
+    config: HornerConfig<F>,
+}
+
+impl<F: FieldExt> Chip<F> for HornerChip<F> {
+    type Config = HornerConfig<F>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> HornerChip<F> {
+    pub fn construct(config: HornerConfig<F>) -> Self {
+        Self { config }
+    }
+
+    /// `constants` must be a fixed column, which holds the fixed coefficients
+    /// and the zero the evaluation starts from.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 3],
+        constants: Column<Fixed>,
+    ) -> HornerConfig<F> {
+        let q_horner = meta.selector();
+        for column in advice {
+            meta.enable_equality(column);
+        }
+        meta.enable_constant(constants);
+
+        meta.create_gate("horner", |meta| {
+            let q_horner = meta.query_selector(q_horner);
+            let [acc, c, x] = advice.map(|column| meta.query_advice(column, Rotation::cur()));
+            let next_acc = meta.query_advice(advice[0], Rotation::next());
+            vec![q_horner * (next_acc - (acc * x + c))]
+        });
+
+        HornerConfig {
+            advice,
+            q_horner,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: FieldExt> HornerInstructions<F> for HornerChip<F> {
+    fn witness(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Value<F>,
+    ) -> Result<AssignedCell<F>, Error> {
+        layouter.assign_region(
+            || "witness",
+            |mut region| Ok(region.assign(self.config.advice[2], 0, value)),
+        )
+    }
+
+    fn evaluate(
+        &self,
+        mut layouter: impl Layouter<F>,
+        coefficients: &[Coefficient<F>],
+        x: &AssignedCell<F>,
+    ) -> Result<AssignedCell<F>, Error> {
+        let [acc_column, c_column, x_column] = self.config.advice;
+
+        layouter.assign_region(
+            || "horner",
+            |mut region| {
+                let mut acc = region.assign_constant(acc_column, 0, F::ZERO)?;
+
+                for (row, coefficient) in coefficients.iter().rev().enumerate() {
+                    self.config.q_horner.enable(&mut region, row)?;
+                    x.copy_to(&mut region, x_column, row);
+                    let c = coefficient.assign(&mut region, c_column, row)?;
+
+                    let next = acc
+                        .field_value()
+                        .zip(c.field_value())
+                        .zip(x.field_value())
+                        .map(|((acc, c), x)| acc * x + c);
+                    acc = region.assign(acc_column, row + 1, next);
+                }
+                Ok(acc)
+            },
+        )
+    }
+}
+
+/// circom's `simple-polynomial`: knowledge of a private `x` with
+/// `x^3 - x + 7 = out` for a public `out`.
+#[derive(Default)]
+pub struct SimplePolynomialCircuit<F: FieldExt> {
+    pub x: Value<F>,
+}
+
+impl<F: FieldExt> SimplePolynomialCircuit<F> {
+    pub const LAYOUT: InstanceLayout = InstanceLayout::new(&[&["out"]]);
+
+    /// `x^3 - x + 7`, lowest degree first.
+    pub fn coefficients() -> [F; 4] {
+        [F::from(7), -F::ONE, F::ZERO, F::ONE]
+    }
+}
+
+impl<F: FieldExt> NamedInstances for SimplePolynomialCircuit<F> {
+    fn instance_layout() -> InstanceLayout {
+        Self::LAYOUT
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for SimplePolynomialCircuit<F> {
+    type Config = (HornerConfig<F>, InstanceColumns);
+    type FloorPlanner = SimpleFloorPlanner;
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 3].map(|_| meta.advice_column());
+        let constants = meta.fixed_column();
+        let instance = InstanceColumns::configure(meta, Self::LAYOUT);
+        (HornerChip::configure(meta, advice, constants), instance)
+    }
+
+    fn synthesize(
+        &self,
+        (config, instance): Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = HornerChip::construct(config);
+        let x = chip.witness(layouter.namespace(|| "x"), self.x)?;
+        let coefficients = Self::coefficients().map(Coefficient::Fixed);
+        let out = chip.evaluate(layouter.namespace(|| "p(x)"), &coefficients, &x)?;
+        instance.expose(layouter.namespace(|| "out"), out.cell(), "out")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::{arithmetic::Field, dev::MockProver, halo2curves::pasta::Fp};
+
+    use super::*;
+
+    #[test]
+    fn simple_polynomial_test() {
+        // as in circom-starter's simple-polynomial.json
+        let x = Fp::from(5);
+        let out = evaluate(&SimplePolynomialCircuit::<Fp>::coefficients(), x);
+        assert_eq!(out, Fp::from(127));
+
+        let circuit = SimplePolynomialCircuit { x: Value::known(x) };
+        let prover = MockProver::run(4, &circuit, vec![vec![out]]).unwrap();
+        prover.assert_satisfied();
+
+        let prover = MockProver::run(4, &circuit, vec![vec![out + Fp::ONE]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    const LAYOUT: InstanceLayout = InstanceLayout::new(&[&["c_1", "y"]]);
+
+    /// `c_0 + c_1·x + c_2·x^2 + c_3·x^3 = y` for a constant `c_0`, a public
+    /// `c_1`, a private `c_2` and `c_3` assigned by another region.
+    #[derive(Default)]
+    struct MixedCircuit {
+        c_2: Value<Fp>,
+        c_3: Value<Fp>,
+        x: Value<Fp>,
+    }
+
+    const C_0: u64 = 11;
+
+    impl Circuit<Fp> for MixedCircuit {
+        type Config = (HornerConfig<Fp>, InstanceColumns);
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 3].map(|_| meta.advice_column());
+            let constants = meta.fixed_column();
+            let instance = InstanceColumns::configure(meta, LAYOUT);
+            (HornerChip::configure(meta, advice, constants), instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = HornerChip::construct(config);
+            let x = chip.witness(layouter.namespace(|| "x"), self.x)?;
+            let c_3 = chip.witness(layouter.namespace(|| "c_3"), self.c_3)?;
+            let (column, row) = instance.get("c_1")?;
+
+            let coefficients = [
+                Coefficient::Fixed(Fp::from(C_0)),
+                Coefficient::Instance(column, row),
+                Coefficient::Witness(self.c_2),
+                Coefficient::Cell(c_3),
+            ];
+            let y = chip.evaluate(layouter.namespace(|| "p(x)"), &coefficients, &x)?;
+            instance.expose(layouter.namespace(|| "y"), y.cell(), "y")
+        }
+    }
+
+    fn prove(c_1: Fp, c_2: Fp, c_3: Fp, x: Fp, y: Fp) -> Result<(), ()> {
+        let circuit = MixedCircuit {
+            c_2: Value::known(c_2),
+            c_3: Value::known(c_3),
+            x: Value::known(x),
+        };
+        let instances = LAYOUT.assemble(&[("c_1", c_1), ("y", y)]).unwrap();
+        MockProver::run(4, &circuit, instances)
+            .unwrap()
+            .verify()
+            .map_err(|_| ())
+    }
+
+    #[test]
+    fn mixed_coefficients_test() {
+        let [c_1, c_2, c_3, x] = [3, 5, 2, 9].map(Fp::from);
+        let y = evaluate(&[Fp::from(C_0), c_1, c_2, c_3], x);
+        assert_eq!(y, Fp::from(11 + 3 * 9 + 5 * 81 + 2 * 729));
+        assert_eq!(prove(c_1, c_2, c_3, x, y), Ok(()));
+
+        // at zero, only the constant coefficient is left
+        assert_eq!(prove(c_1, c_2, c_3, Fp::ZERO, Fp::from(C_0)), Ok(()));
+
+        // another public coefficient
+        assert!(prove(c_1 + Fp::ONE, c_2, c_3, x, y).is_err());
+        // another private one
+        assert!(prove(c_1, c_2 + Fp::ONE, c_3, x, y).is_err());
+        assert!(prove(c_1, c_2, c_3, x, y + Fp::ONE).is_err());
+    }
+
+    #[test]
+    fn evaluate_test() {
+        assert_eq!(evaluate::<Fp>(&[], Fp::from(3)), Fp::ZERO);
+        assert_eq!(evaluate(&[Fp::from(4)], Fp::from(3)), Fp::from(4));
+        let coefficients = [1, 2, 3].map(Fp::from);
+        assert_eq!(evaluate(&coefficients, -Fp::ONE), Fp::from(2));
+    }
+}
@@ -0,0 +1,329 @@
+//! Circuit inputs read from JSON files, like circom's `input.json`.
+//!
+//! Every circuit has a schema with a `private` section for its witnesses and a
+//! `public` section for its instance columns, e.g. for the Fibonacci circuits:
+//!
+//! ```json
+//! {
+//!     "private": { "a": "1", "b": "1" },
+//!     "public": { "a": "1", "b": "1", "out": "0x37" }
+//! }
+//! ```
+//!
+//! Field elements are decimal or `0x`-prefixed hex strings; small values may
+//! also be plain JSON numbers.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::Path;
+
+use halo2_proofs::plonk::Circuit;
+use serde::de::{self, DeserializeOwned, Deserializer, Visitor};
+use serde::Deserialize;
+
+use crate::field::FieldExt;
+use crate::instance::InstanceError;
+
+/// A circuit together with its instance vectors, one per instance column.
+pub type CircuitWithInstances<I, F> = (<I as CircuitInput>::Circuit<F>, Vec<Vec<F>>);
+
+/// A circuit whose inputs can be read from a file.
+pub trait CircuitInput: DeserializeOwned {
+    type Circuit<F: FieldExt>: Circuit<F>;
+
+    fn build<F: FieldExt>(&self) -> Result<CircuitWithInstances<Self, F>, InputError>;
+}
+
+/// Reads the inputs of a circuit from the JSON file at `path`.
+pub fn load<I: CircuitInput, F: FieldExt>(
+    path: impl AsRef<Path>,
+) -> Result<CircuitWithInstances<I, F>, InputError> {
+    let file = File::open(path)?;
+    let input: I = serde_json::from_reader(BufReader::new(file))?;
+    input.build()
+}
+
+/// Reads the inputs of a circuit from a JSON string.
+pub fn from_json<I: CircuitInput, F: FieldExt>(
+    json: &str,
+) -> Result<CircuitWithInstances<I, F>, InputError> {
+    let input: I = serde_json::from_str(json)?;
+    input.build()
+}
+
+/// The `public` section of a circuit without instance columns, which may be left out.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NoPublicInputs {}
+
+#[derive(Debug)]
+pub enum InputError {
+    Io(io::Error),
+    /// Malformed JSON, a missing or unknown field, or a value that is not a number.
+    Json(serde_json::Error),
+    /// A value that is too large for the field the circuit is built over.
+    OutOfRange {
+        field: &'static str,
+        value: String,
+    },
+    /// Public inputs that do not match the circuit's instance layout.
+    Instance(InstanceError),
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::Io(e) => write!(f, "cannot read input file: {}", e),
+            InputError::Json(e) => write!(f, "invalid input file: {}", e),
+            InputError::OutOfRange { field, value } => write!(
+                f,
+                "`{}` is out of range: {} is not smaller than the field modulus",
+                field, value
+            ),
+            InputError::Instance(e) => write!(f, "invalid public inputs: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+impl From<io::Error> for InputError {
+    fn from(e: io::Error) -> Self {
+        InputError::Io(e)
+    }
+}
+
+impl From<InstanceError> for InputError {
+    fn from(e: InstanceError) -> Self {
+        InputError::Instance(e)
+    }
+}
+
+impl From<serde_json::Error> for InputError {
+    fn from(e: serde_json::Error) -> Self {
+        InputError::Json(e)
+    }
+}
+
+/// An unsigned integer of up to 256 bits, not yet reduced into any field.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldValue {
+    /// little-endian 64-bit limbs
+    limbs: [u64; 4],
+    text: String,
+}
+
+impl FieldValue {
+    /// Converts to an element of `F`, failing if the value is not smaller than
+    /// the modulus. `field` names the value in the error.
+    pub fn to_field<F: FieldExt>(&self, field: &'static str) -> Result<F, InputError> {
+        let out_of_range = || InputError::OutOfRange {
+            field,
+            value: self.text.clone(),
+        };
+
+        let bytes: Vec<u8> = self.limbs.iter().flat_map(|l| l.to_le_bytes()).collect();
+        let mut repr = F::Repr::default();
+        let len = repr.as_ref().len().min(bytes.len());
+        if bytes[len..].iter().any(|b| *b != 0) {
+            return Err(out_of_range());
+        }
+        repr.as_mut()[..len].copy_from_slice(&bytes[..len]);
+
+        Option::from(F::from_repr(repr)).ok_or_else(out_of_range)
+    }
+
+    pub(crate) fn parse(text: &str) -> Result<Self, String> {
+        let (digits, radix) = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+            Some(hex) => (hex, 16),
+            None => (text, 10),
+        };
+        if digits.is_empty() {
+            return Err(format!("`{}` is not a number", text));
+        }
+
+        let mut limbs = [0u64; 4];
+        for c in digits.chars() {
+            let digit = c
+                .to_digit(radix)
+                .ok_or_else(|| format!("`{}` is not a decimal or 0x-prefixed hex number", text))?;
+
+            // limbs = limbs * radix + digit
+            let mut carry = digit as u128;
+            for limb in limbs.iter_mut() {
+                let v = (*limb as u128) * radix as u128 + carry;
+                *limb = v as u64;
+                carry = v >> 64;
+            }
+            if carry != 0 {
+                return Err(format!("`{}` does not fit in 256 bits", text));
+            }
+        }
+
+        Ok(Self {
+            limbs,
+            text: text.to_string(),
+        })
+    }
+}
+
+impl From<u64> for FieldValue {
+    fn from(v: u64) -> Self {
+        Self {
+            limbs: [v, 0, 0, 0],
+            text: v.to_string(),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for FieldValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FieldValueVisitor;
+
+        impl<'de> Visitor<'de> for FieldValueVisitor {
+            type Value = FieldValue;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a field element as a decimal or 0x-prefixed hex string")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<FieldValue, E> {
+                FieldValue::parse(v).map_err(E::custom)
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<FieldValue, E> {
+                Ok(FieldValue::from(v))
+            }
+        }
+
+        deserializer.deserialize_any(FieldValueVisitor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::{
+        dev::MockProver,
+        halo2curves::pasta::{Fp, Fq},
+    };
+
+    use crate::fibo_circuit::FiboInputFile;
+    use crate::planner::min_k;
+    use crate::range_check::{eg1_simple, eg2_lookup, eg3_numbits};
+    use crate::{fibo_circuit2, is_zero};
+
+    use super::*;
+
+    // the Pallas base field modulus
+    const P: &str = "0x40000000000000000000000000000000224698fc094cf91b992d30ed00000001";
+
+    #[test]
+    fn parse_field_value_test() {
+        let value = |s: &str| serde_json::from_str::<FieldValue>(s).unwrap();
+
+        assert_eq!(value("\"55\"").to_field::<Fp>("x").unwrap(), Fp::from(55));
+        assert_eq!(value("\"0x37\"").to_field::<Fp>("x").unwrap(), Fp::from(55));
+        assert_eq!(value("55").to_field::<Fp>("x").unwrap(), Fp::from(55));
+        // p is out of range for Fp, but not for the larger Fq
+        let p = value(&format!("\"{}\"", P));
+        assert!(p.to_field::<Fp>("x").is_err());
+        assert!(p.to_field::<Fq>("x").is_ok());
+
+        assert!(serde_json::from_str::<FieldValue>("\"-1\"").is_err());
+        assert!(serde_json::from_str::<FieldValue>("\"0x\"").is_err());
+        assert!(serde_json::from_str::<FieldValue>("\"12ab\"").is_err());
+        assert!(serde_json::from_str::<FieldValue>(&format!("\"{}0\"", P)).is_err());
+    }
+
+    #[test]
+    fn out_of_range_test() {
+        let json = format!(
+            r#"{{"private": {{"a": "1", "b": "1"}}, "public": {{"a": "1", "b": "1", "out": "{}"}}}}"#,
+            P
+        );
+        let err = from_json::<FiboInputFile<10>, Fp>(&json).err().unwrap();
+        assert!(matches!(
+            err,
+            InputError::OutOfRange {
+                field: "public.out",
+                ..
+            }
+        ));
+        assert!(err.to_string().contains("public.out"));
+
+        // p - 1 is the largest element, so it is in range but not the right output
+        let json = json.replace("001\"", "000\"");
+        assert!(matches!(
+            from_json::<FiboInputFile<10>, Fp>(&json),
+            Err(InputError::Instance(_))
+        ));
+    }
+
+    #[test]
+    fn wrong_public_input_test() {
+        let json =
+            r#"{"private": {"a": "2", "b": "3"}, "public": {"a": "3", "b": "2", "out": "144"}}"#;
+        let err = from_json::<FiboInputFile<10>, Fp>(json).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "invalid public inputs: public input `a` at column 0, row 0 has the wrong value; \
+             its value is at column 0, row 1 instead, are the inputs out of order?"
+        );
+
+        let json = json.replace("\"3\", \"b\": \"2\"", "\"2\", \"b\": \"3\"");
+        assert!(from_json::<FiboInputFile<10>, Fp>(&json).is_ok());
+
+        let json = json.replace("144", "145");
+        let err = from_json::<FiboInputFile<10>, Fp>(&json).err().unwrap();
+        assert!(err
+            .to_string()
+            .ends_with("`out` at column 0, row 2 has the wrong value"));
+    }
+
+    #[test]
+    fn missing_field_test() {
+        let json = r#"{"private": {"a": "1", "b": "1"}, "public": {"a": "1", "b": "1"}}"#;
+        let err = from_json::<FiboInputFile<10>, Fp>(json).err().unwrap();
+        assert!(err.to_string().contains("missing field `out`"));
+
+        let json = r#"{"private": {"a": "1", "b": "1", "c": "2"}, "public": {"a": "1", "b": "1", "out": "55"}}"#;
+        let err = from_json::<FiboInputFile<10>, Fp>(json).err().unwrap();
+        assert!(err.to_string().contains("unknown field `c`"));
+    }
+
+    #[test]
+    fn load_test() {
+        let (_, instances) =
+            load::<FiboInputFile<10>, Fp>(concat!(env!("CARGO_MANIFEST_DIR"), "/inputs/fibo.json"))
+                .unwrap();
+        assert_eq!(
+            instances,
+            vec![vec![Fp::from(1), Fp::from(1), Fp::from(55)]]
+        );
+
+        assert!(matches!(
+            load::<FiboInputFile<10>, Fp>("no/such/input.json"),
+            Err(InputError::Io(_))
+        ));
+    }
+
+    fn check_example<I: CircuitInput>(name: &str) {
+        let path = format!("{}/inputs/{}", env!("CARGO_MANIFEST_DIR"), name);
+        let (circuit, instances) = load::<I, Fp>(path).unwrap();
+        let k = min_k(&circuit).unwrap();
+        MockProver::run(k, &circuit, instances)
+            .unwrap()
+            .assert_satisfied();
+    }
+
+    #[test]
+    fn example_inputs_test() {
+        check_example::<FiboInputFile<10>>("fibo.json");
+        check_example::<fibo_circuit2::FiboInputFile<10>>("fibo.json");
+        check_example::<eg1_simple::RangeCheckInputFile<8>>("range_check.json");
+        check_example::<eg2_lookup::RangeCheckInputFile<8>>("range_check.json");
+        check_example::<eg3_numbits::RangeCheckInputFile>("range_check_numbits.json");
+        check_example::<is_zero::FunctionInputFile>("is_zero.json");
+    }
+}
@@ -0,0 +1,413 @@
+//! Named public inputs.
+//!
+//! An [`InstanceLayout`] names the public input in every row of every instance
+//! column, so chips expose cells by name instead of by row number, and instance
+//! vectors are built from, and checked against, those names before proving.
+
+use std::fmt;
+
+use halo2_proofs::{
+    circuit::{Cell, Layouter},
+    plonk::{Column, ConstraintSystem, Error, Instance},
+};
+
+use crate::field::FieldExt;
+
+/// The names of the public inputs, one list per instance column, in row order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InstanceLayout(&'static [&'static [&'static str]]);
+
+impl InstanceLayout {
+    /// Names are expected to be unique across all columns.
+    pub const fn new(columns: &'static [&'static [&'static str]]) -> Self {
+        Self(columns)
+    }
+
+    /// A layout for circuits without public inputs.
+    pub const fn empty() -> Self {
+        Self(&[])
+    }
+
+    pub fn num_columns(&self) -> usize {
+        self.0.len()
+    }
+
+    /// The `(column, row)` of the public input called `name`.
+    pub fn position(&self, name: &str) -> Option<(usize, usize)> {
+        self.0.iter().enumerate().find_map(|(column, names)| {
+            names
+                .iter()
+                .position(|n| *n == name)
+                .map(|row| (column, row))
+        })
+    }
+
+    /// Orders named values into instance vectors. Every public input must be
+    /// given exactly once.
+    pub fn assemble<F: FieldExt>(
+        &self,
+        values: &[(&str, F)],
+    ) -> Result<Vec<Vec<F>>, InstanceError> {
+        let mut columns: Vec<Vec<Option<F>>> =
+            self.0.iter().map(|names| vec![None; names.len()]).collect();
+
+        for (name, value) in values {
+            let (column, row) = self
+                .position(name)
+                .ok_or_else(|| InstanceError::Unknown(name.to_string()))?;
+            if columns[column][row].replace(*value).is_some() {
+                return Err(InstanceError::Duplicate(name.to_string()));
+            }
+        }
+
+        columns
+            .into_iter()
+            .zip(self.0)
+            .map(|(values, names)| {
+                values
+                    .into_iter()
+                    .zip(names.iter())
+                    .map(|(value, name)| value.ok_or(InstanceError::Missing(name)))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Checks that `instances` has one vector per column, each as long as the
+    /// number of names in that column.
+    pub fn check<F: FieldExt>(&self, instances: &[&[F]]) -> Result<(), InstanceError> {
+        if instances.len() != self.num_columns() {
+            return Err(InstanceError::ColumnCount {
+                expected: self.num_columns(),
+                found: instances.len(),
+            });
+        }
+
+        for (column, (values, names)) in instances.iter().zip(self.0).enumerate() {
+            if values.len() != names.len() {
+                return Err(InstanceError::Length {
+                    column,
+                    names,
+                    found: values.len(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks `instances` against the values the public inputs are known to
+    /// take, e.g. the ones computed from the witness by the prover.
+    pub fn check_values<F: FieldExt>(
+        &self,
+        instances: &[&[F]],
+        expected: &[(&str, F)],
+    ) -> Result<(), InstanceError> {
+        self.check(instances)?;
+
+        for (name, value) in expected {
+            let (column, row) = self
+                .position(name)
+                .ok_or_else(|| InstanceError::Unknown(name.to_string()))?;
+            if instances[column][row] != *value {
+                let found_at = instances.iter().enumerate().find_map(|(column, values)| {
+                    values
+                        .iter()
+                        .position(|v| v == value)
+                        .map(|row| (column, row))
+                });
+                return Err(InstanceError::Value {
+                    name: self.0[column][row],
+                    column,
+                    row,
+                    found_at,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A circuit whose public inputs are named by an [`InstanceLayout`]. The
+/// proving functions check the instance vectors against it before proving.
+pub trait NamedInstances {
+    fn instance_layout() -> InstanceLayout;
+}
+
+/// The instance columns of a circuit, created from its [`InstanceLayout`].
+#[derive(Clone, Debug)]
+pub struct InstanceColumns {
+    layout: InstanceLayout,
+    columns: Vec<Column<Instance>>,
+}
+
+impl InstanceColumns {
+    /// Creates one instance column per column of `layout`, enabling equality on each.
+    pub fn configure<F: FieldExt>(meta: &mut ConstraintSystem<F>, layout: InstanceLayout) -> Self {
+        let columns = (0..layout.num_columns())
+            .map(|_| {
+                let column = meta.instance_column();
+                meta.enable_equality(column);
+                column
+            })
+            .collect();
+
+        Self { layout, columns }
+    }
+
+    pub fn layout(&self) -> InstanceLayout {
+        self.layout
+    }
+
+    /// The column and row of the public input called `name`.
+    pub fn get(&self, name: &str) -> Result<(Column<Instance>, usize), InstanceError> {
+        let (column, row) = self
+            .layout
+            .position(name)
+            .ok_or_else(|| InstanceError::Unknown(name.to_string()))?;
+        Ok((self.columns[column], row))
+    }
+
+    /// Constrains `cell` to equal the public input called `name`.
+    pub fn expose<F: FieldExt>(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: Cell,
+        name: &str,
+    ) -> Result<(), Error> {
+        let (column, row) = self.get(name)?;
+        layouter.constrain_instance(cell, column, row);
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InstanceError {
+    ColumnCount {
+        expected: usize,
+        found: usize,
+    },
+    Length {
+        column: usize,
+        names: &'static [&'static str],
+        found: usize,
+    },
+    Unknown(String),
+    Duplicate(String),
+    Missing(&'static str),
+    Value {
+        name: &'static str,
+        column: usize,
+        row: usize,
+        /// where the expected value was found instead, if anywhere
+        found_at: Option<(usize, usize)>,
+    },
+}
+
+impl fmt::Display for InstanceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InstanceError::ColumnCount { expected, found } => {
+                write!(f, "expected {} instance column(s), got {}", expected, found)
+            }
+            InstanceError::Length {
+                column,
+                names,
+                found,
+            } => write!(
+                f,
+                "instance column {} should hold {} value(s) {:?}, got {}",
+                column,
+                names.len(),
+                names,
+                found
+            ),
+            InstanceError::Unknown(name) => write!(f, "unknown public input `{}`", name),
+            InstanceError::Duplicate(name) => write!(f, "public input `{}` is given twice", name),
+            InstanceError::Missing(name) => write!(f, "public input `{}` is missing", name),
+            InstanceError::Value {
+                name,
+                column,
+                row,
+                found_at,
+            } => {
+                write!(
+                    f,
+                    "public input `{}` at column {}, row {} has the wrong value",
+                    name, column, row
+                )?;
+                match found_at {
+                    Some((column, row)) => write!(
+                        f,
+                        "; its value is at column {}, row {} instead, are the inputs out of order?",
+                        column, row
+                    ),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+impl std::error::Error for InstanceError {}
+
+/// For `?` in `Circuit::synthesize`, which can only fail with halo2's errors.
+impl From<InstanceError> for Error {
+    fn from(_: InstanceError) -> Self {
+        Error::Synthesis
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::{
+        circuit::Value,
+        dev::MockProver,
+        halo2curves::pasta::Fp,
+        plonk::{Advice, Circuit},
+    };
+
+    use super::*;
+
+    use crate::planner::SimpleFloorPlanner;
+
+    const LAYOUT: InstanceLayout = InstanceLayout::new(&[&["x", "y"], &["sum"]]);
+
+    #[test]
+    fn assemble_test() {
+        let (x, y) = (Fp::from(2), Fp::from(3));
+        let instances = LAYOUT
+            .assemble(&[("sum", x + y), ("y", y), ("x", x)])
+            .unwrap();
+        assert_eq!(instances, vec![vec![x, y], vec![x + y]]);
+
+        assert_eq!(
+            LAYOUT.assemble(&[("x", x), ("sum", x + y)]),
+            Err(InstanceError::Missing("y"))
+        );
+        assert_eq!(
+            LAYOUT.assemble(&[("x", x), ("x", x)]),
+            Err(InstanceError::Duplicate("x".to_string()))
+        );
+        assert_eq!(
+            LAYOUT.assemble(&[("z", x)]),
+            Err(InstanceError::Unknown("z".to_string()))
+        );
+    }
+
+    #[test]
+    fn check_test() {
+        let (x, y) = (Fp::from(2), Fp::from(3));
+
+        let err = LAYOUT.check(&[&[x, y]]).unwrap_err();
+        assert_eq!(err.to_string(), "expected 2 instance column(s), got 1");
+
+        let err = LAYOUT.check(&[&[x, y, x], &[x + y]]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "instance column 0 should hold 2 value(s) [\"x\", \"y\"], got 3"
+        );
+
+        let expected = [("x", x), ("y", y), ("sum", x + y)];
+        assert!(LAYOUT.check_values(&[&[x, y], &[x + y]], &expected).is_ok());
+
+        let err = LAYOUT
+            .check_values(&[&[y, x], &[x + y]], &expected)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            InstanceError::Value {
+                name: "x",
+                column: 0,
+                row: 0,
+                found_at: Some((0, 1)),
+            }
+        );
+        assert!(err.to_string().contains("out of order"));
+    }
+
+    #[test]
+    fn get_test() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let columns = InstanceColumns::configure(&mut meta, LAYOUT);
+
+        assert_eq!(columns.get("sum").map(|(_, row)| row), Ok(0));
+        assert_eq!(
+            columns.get("product"),
+            Err(InstanceError::Unknown("product".to_string()))
+        );
+    }
+
+    #[derive(Default)]
+    struct SumCircuit {
+        x: Value<Fp>,
+        y: Value<Fp>,
+    }
+
+    impl Circuit<Fp> for SumCircuit {
+        type Config = (Column<Advice>, InstanceColumns);
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = meta.advice_column();
+            meta.enable_equality(advice);
+            (advice, InstanceColumns::configure(meta, LAYOUT))
+        }
+
+        fn synthesize(
+            &self,
+            (advice, instances): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            // not a real sum gate, only enough to expose cells by name
+            let cells = layouter.assign_region(
+                || "values",
+                |mut region| {
+                    let sum = self.x.zip(self.y).map(|(x, y)| x + y);
+                    let values = [("x", self.x), ("y", self.y), ("sum", sum)];
+                    values
+                        .iter()
+                        .enumerate()
+                        .map(|(row, (name, value))| {
+                            let cell = region.assign_advice(advice, row, *value);
+                            Ok((*name, cell.cell()))
+                        })
+                        .collect::<Result<Vec<_>, Error>>()
+                },
+            )?;
+
+            for (name, cell) in cells {
+                instances.expose(layouter.namespace(|| name), cell, name)?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn expose_by_name_test() {
+        let (x, y) = (Fp::from(2), Fp::from(3));
+        let circuit = SumCircuit {
+            x: Value::known(x),
+            y: Value::known(y),
+        };
+
+        let instances = LAYOUT
+            .assemble(&[("x", x), ("y", y), ("sum", x + y)])
+            .unwrap();
+        MockProver::run(4, &circuit, instances)
+            .unwrap()
+            .assert_satisfied();
+
+        let swapped = vec![vec![y, x], vec![x + y]];
+        assert!(MockProver::run(4, &circuit, swapped)
+            .unwrap()
+            .verify()
+            .is_err());
+    }
+}
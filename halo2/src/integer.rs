@@ -0,0 +1,652 @@
+//! Fixed-width unsigned integers: `u8`, `u32` and `u64` addition, subtraction
+//! and multiplication with the overflow behaviour of Rust's integers.
+//!
+//! Field arithmetic wraps modulo `p`, not modulo `2^N`, so an `N`-bit integer is
+//! a cell range checked to `N` bits, and every operation witnesses its wrapped
+//! result `c` and whether it overflowed, in one row:
+//!
+//! ```text
+//!  a | b | c | hi | overflow | inv  | modulus | selector
+//!  a   b   c   hi   flag       1/hi   2^N       q_add: a + b = c + flag·2^N
+//!                                               q_sub: a - b = c - flag·2^N
+//!                                               q_mul: a · b = c + hi·2^N, flag = (hi != 0)
+//! ```
+//!
+//! `c` and `hi` are range checked to `N` bits and the flag of an addition or a
+//! subtraction is boolean, which leaves a single valid `c` and flag. `N` is at
+//! most 64, so even a product fits in the field without wrapping.
+//!
+//! A range check splits a value into bytes with a running sum, every byte
+//! looked up in a 256-row table:
+//!
+//! ```text
+//!  running sum          | q_range
+//!  z_0 = value            1
+//!  z_1 = (z_0 - c_0)/2^8  1
+//!  ...
+//!  z_{N/8} = 0            0
+//! ```
+//!
+//! Every operation returns its overflow flag. [`Overflow::Wrapping`] behaves as
+//! Rust's `wrapping_*` and `overflowing_*`, and [`Overflow::Checked`] constrains
+//! the flag to zero, so an overflow makes the circuit unsatisfiable, as
+//! unwrapping `checked_*` panics.
+
+use std::fmt;
+
+use halo2_proofs::{
+    circuit::{Chip, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+
+use crate::cell::{AssignedCell, RegionExt};
+use crate::field::FieldExt;
+
+pub use self::table::ByteTable;
+
+mod table;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IntOp {
+    Add,
+    Sub,
+    Mul,
+}
+
+impl IntOp {
+    pub const ALL: [IntOp; 3] = [IntOp::Add, IntOp::Sub, IntOp::Mul];
+
+    /// The operation on native `num_bits`-bit integers, as Rust's
+    /// `overflowing_*`: the wrapped result and whether it overflowed.
+    pub fn overflowing(&self, a: u64, b: u64, num_bits: usize) -> (u64, bool) {
+        let (a, b, modulus) = (a as u128, b as u128, 1u128 << num_bits);
+        let (c, overflow) = match self {
+            IntOp::Add => ((a + b) % modulus, a + b >= modulus),
+            IntOp::Sub => ((a + modulus - b) % modulus, a < b),
+            IntOp::Mul => ((a * b) % modulus, a * b >= modulus),
+        };
+        (c as u64, overflow)
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            IntOp::Add => "add",
+            IntOp::Sub => "sub",
+            IntOp::Mul => "mul",
+        }
+    }
+
+    fn index(&self) -> usize {
+        match self {
+            IntOp::Add => 0,
+            IntOp::Sub => 1,
+            IntOp::Mul => 2,
+        }
+    }
+}
+
+impl fmt::Display for IntOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// What an operation does when its result does not fit in `N` bits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Overflow {
+    /// The result wraps around modulo `2^N`.
+    Wrapping,
+    /// The overflow flag is constrained to zero.
+    Checked,
+}
+
+/// A cell constrained to `N` bits.
+#[derive(Clone, Debug)]
+pub struct AssignedUint<F: FieldExt, const N: usize>(pub AssignedCell<F>);
+
+pub type AssignedU8<F> = AssignedUint<F, 8>;
+pub type AssignedU32<F> = AssignedUint<F, 32>;
+pub type AssignedU64<F> = AssignedUint<F, 64>;
+
+impl<F: FieldExt, const N: usize> AssignedUint<F, N> {
+    pub fn value(&self) -> Value<u64> {
+        self.0.field_value().map(|v| v.get_lower_128() as u64)
+    }
+}
+
+/// The result of an operation and whether it overflowed, as a boolean cell.
+#[derive(Clone, Debug)]
+pub struct Overflowing<F: FieldExt, const N: usize> {
+    pub value: AssignedUint<F, N>,
+    pub overflow: AssignedCell<F>,
+}
+
+/// Range-checked unsigned integers and their arithmetic.
+pub trait IntegerInstructions<F: FieldExt>: Chip<F> {
+    /// Loads the byte table; call once per circuit.
+    fn load_table(&self, layouter: impl Layouter<F>) -> Result<(), Error>;
+
+    /// Assigns a private integer, range checked to `N` bits.
+    fn witness<const N: usize>(
+        &self,
+        layouter: impl Layouter<F>,
+        value: Value<u64>,
+    ) -> Result<AssignedUint<F, N>, Error>;
+
+    /// Assigns an integer fixed by the circuit.
+    fn constant<const N: usize>(
+        &self,
+        layouter: impl Layouter<F>,
+        value: u64,
+    ) -> Result<AssignedUint<F, N>, Error>;
+
+    /// Constrains `cell` to `N` bits, in `N / 8` rows.
+    fn range_check<const N: usize>(
+        &self,
+        layouter: impl Layouter<F>,
+        cell: &AssignedCell<F>,
+    ) -> Result<AssignedUint<F, N>, Error>;
+
+    fn add<const N: usize>(
+        &self,
+        layouter: impl Layouter<F>,
+        mode: Overflow,
+        a: &AssignedUint<F, N>,
+        b: &AssignedUint<F, N>,
+    ) -> Result<Overflowing<F, N>, Error>;
+
+    fn sub<const N: usize>(
+        &self,
+        layouter: impl Layouter<F>,
+        mode: Overflow,
+        a: &AssignedUint<F, N>,
+        b: &AssignedUint<F, N>,
+    ) -> Result<Overflowing<F, N>, Error>;
+
+    fn mul<const N: usize>(
+        &self,
+        layouter: impl Layouter<F>,
+        mode: Overflow,
+        a: &AssignedUint<F, N>,
+        b: &AssignedUint<F, N>,
+    ) -> Result<Overflowing<F, N>, Error>;
+
+    /// Computes `a op b` modulo `2^N` and whether it overflowed.
+    fn op<const N: usize>(
+        &self,
+        layouter: impl Layouter<F>,
+        op: IntOp,
+        mode: Overflow,
+        a: &AssignedUint<F, N>,
+        b: &AssignedUint<F, N>,
+    ) -> Result<Overflowing<F, N>, Error>;
+}
+
+#[derive(Clone, Debug)]
+pub struct IntegerConfig<F: FieldExt> {
+    /// the operands and the wrapped result
+    operands: [Column<Advice>; 3],
+    /// the high half of a product
+    hi: Column<Advice>,
+    overflow: Column<Advice>,
+    /// the inverse of `hi`, or zero
+    inv: Column<Advice>,
+    /// the running sum of a range check
+    running_sum: Column<Advice>,
+    /// `2^N` for the width of the operation in this row
+    modulus: Column<Fixed>,
+    /// one selector per operation, in the order of [`IntOp::ALL`]
+    q_ops: [Selector; 3],
+    q_range: Selector,
+    table: ByteTable<F>,
+}
+
+pub struct IntegerChip<F: FieldExt> {
+    config: IntegerConfig<F>,
+}
+
+impl<F: FieldExt> Chip<F> for IntegerChip<F> {
+    type Config = IntegerConfig<F>;
+    type Loaded = ByteTable<F>;
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &self.config.table
+    }
+}
+
+impl<F: FieldExt> IntegerChip<F> {
+    pub fn construct(config: IntegerConfig<F>) -> Self {
+        Self { config }
+    }
+
+    /// `advice` holds the columns in the order of the table in the module
+    /// documentation: `a, b, c, hi, overflow, inv`. Range checks reuse `a`.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 6],
+        constants: Column<Fixed>,
+    ) -> IntegerConfig<F> {
+        let [a, b, c, hi, overflow, inv] = advice;
+        let running_sum = a;
+        let modulus = meta.fixed_column();
+        let q_ops = [(); 3].map(|_| meta.selector());
+        let q_range = meta.complex_selector();
+        let table = ByteTable::configure(meta);
+
+        meta.enable_constant(constants);
+        for column in [a, b, c, hi, overflow] {
+            meta.enable_equality(column);
+        }
+
+        for op in IntOp::ALL {
+            meta.create_gate(op.name(), |meta| {
+                let q_op = meta.query_selector(q_ops[op.index()]);
+                let [a, b, c, hi, overflow, inv] =
+                    advice.map(|column| meta.query_advice(column, Rotation::cur()));
+                let modulus = meta.query_fixed(modulus, Rotation::cur());
+                let one = Expression::Constant(F::ONE);
+
+                let constraints = match op {
+                    IntOp::Add => vec![
+                        a + b - c - overflow.clone() * modulus,
+                        overflow.clone() * (one - overflow),
+                    ],
+                    IntOp::Sub => vec![
+                        a - b - c + overflow.clone() * modulus,
+                        overflow.clone() * (one - overflow),
+                    ],
+                    // the flag is 0 if `hi` is, and 1 otherwise
+                    IntOp::Mul => vec![
+                        a * b - c - hi.clone() * modulus,
+                        hi.clone() * (one - overflow.clone()),
+                        overflow - hi * inv,
+                    ],
+                };
+                constraints
+                    .into_iter()
+                    .map(|poly| q_op.clone() * poly)
+                    .collect::<Vec<_>>()
+            });
+        }
+
+        // a disabled row looks up 0, which is in the table
+        meta.lookup("byte", |meta| {
+            let q_range = meta.query_selector(q_range);
+            let z = meta.query_advice(running_sum, Rotation::cur());
+            let next_z = meta.query_advice(running_sum, Rotation::next());
+            let byte = z - next_z * F::from(1 << 8);
+            vec![(q_range * byte, table.value)]
+        });
+
+        IntegerConfig {
+            operands: [a, b, c],
+            hi,
+            overflow,
+            inv,
+            running_sum,
+            modulus,
+            q_ops,
+            q_range,
+            table,
+        }
+    }
+}
+
+impl<F: FieldExt> IntegerInstructions<F> for IntegerChip<F> {
+    fn load_table(&self, layouter: impl Layouter<F>) -> Result<(), Error> {
+        self.config.table.load(layouter)
+    }
+
+    fn witness<const N: usize>(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Value<u64>,
+    ) -> Result<AssignedUint<F, N>, Error> {
+        let cell = layouter.assign_region(
+            || format!("witness u{}", N),
+            |mut region| Ok(region.assign(self.config.operands[1], 0, value.map(F::from))),
+        )?;
+        self.range_check(layouter, &cell)
+    }
+
+    fn constant<const N: usize>(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: u64,
+    ) -> Result<AssignedUint<F, N>, Error> {
+        check_width::<N>();
+        assert!(
+            N == 64 || value < 1 << N,
+            "{} does not fit in {} bits",
+            value,
+            N
+        );
+        let cell = layouter.assign_region(
+            || format!("constant u{}", N),
+            |mut region| region.assign_constant(self.config.operands[1], 0, F::from(value)),
+        )?;
+        Ok(AssignedUint(cell))
+    }
+
+    fn range_check<const N: usize>(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F>,
+    ) -> Result<AssignedUint<F, N>, Error> {
+        check_width::<N>();
+        let value = cell.field_value().map(|v| v.get_lower_128());
+
+        layouter.assign_region(
+            || format!("range check {} bits", N),
+            |mut region| {
+                let mut z = cell.copy_to(&mut region, self.config.running_sum, 0);
+                for i in 0..N / 8 {
+                    self.config.q_range.enable(&mut region, i)?;
+                    let next = value.map(|v| F::from_u128(v >> (8 * (i + 1))));
+                    z = region.assign(self.config.running_sum, i + 1, next);
+                }
+                region.constrain_constant(z.cell(), F::ZERO)
+            },
+        )?;
+
+        Ok(AssignedUint(cell.clone()))
+    }
+
+    fn add<const N: usize>(
+        &self,
+        layouter: impl Layouter<F>,
+        mode: Overflow,
+        a: &AssignedUint<F, N>,
+        b: &AssignedUint<F, N>,
+    ) -> Result<Overflowing<F, N>, Error> {
+        self.op(layouter, IntOp::Add, mode, a, b)
+    }
+
+    fn sub<const N: usize>(
+        &self,
+        layouter: impl Layouter<F>,
+        mode: Overflow,
+        a: &AssignedUint<F, N>,
+        b: &AssignedUint<F, N>,
+    ) -> Result<Overflowing<F, N>, Error> {
+        self.op(layouter, IntOp::Sub, mode, a, b)
+    }
+
+    fn mul<const N: usize>(
+        &self,
+        layouter: impl Layouter<F>,
+        mode: Overflow,
+        a: &AssignedUint<F, N>,
+        b: &AssignedUint<F, N>,
+    ) -> Result<Overflowing<F, N>, Error> {
+        self.op(layouter, IntOp::Mul, mode, a, b)
+    }
+
+    fn op<const N: usize>(
+        &self,
+        mut layouter: impl Layouter<F>,
+        op: IntOp,
+        mode: Overflow,
+        a: &AssignedUint<F, N>,
+        b: &AssignedUint<F, N>,
+    ) -> Result<Overflowing<F, N>, Error> {
+        check_width::<N>();
+        let result = a
+            .value()
+            .zip(b.value())
+            .map(|(a, b)| op.overflowing(a, b, N));
+        // the high half of a product
+        let hi = match op {
+            IntOp::Mul => a
+                .value()
+                .zip(b.value())
+                .map(|(a, b)| F::from_u128((a as u128 * b as u128) >> N)),
+            _ => Value::known(F::ZERO),
+        };
+
+        let (c, hi, overflow) = layouter.assign_region(
+            || format!("{} u{}", op, N),
+            |mut region| {
+                self.config.q_ops[op.index()].enable(&mut region, 0)?;
+                region.assign_fixed(self.config.modulus, 0, F::from_u128(1 << N));
+
+                let [a_column, b_column, c_column] = self.config.operands;
+                a.0.copy_to(&mut region, a_column, 0);
+                b.0.copy_to(&mut region, b_column, 0);
+                let c = region.assign(c_column, 0, result.map(|(c, _)| F::from(c)));
+                let hi_cell = region.assign(self.config.hi, 0, hi);
+                region.assign(
+                    self.config.inv,
+                    0,
+                    hi.map(|hi| hi.invert().unwrap_or(F::ZERO)),
+                );
+                let overflow = region.assign(
+                    self.config.overflow,
+                    0,
+                    result.map(|(_, overflow)| F::from(overflow as u64)),
+                );
+
+                if mode == Overflow::Checked {
+                    region.constrain_constant(overflow.cell(), F::ZERO)?;
+                }
+                Ok((c, hi_cell, overflow))
+            },
+        )?;
+
+        let value = self.range_check(layouter.namespace(|| "c"), &c)?;
+        if op == IntOp::Mul {
+            self.range_check::<N>(layouter.namespace(|| "hi"), &hi)?;
+        }
+
+        Ok(Overflowing { value, overflow })
+    }
+}
+
+fn check_width<const N: usize>() {
+    assert!(
+        N.is_multiple_of(8) && 0 < N && N <= 64,
+        "integers are 8 to 64 bits, in whole bytes"
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::{
+        arithmetic::Field,
+        dev::MockProver,
+        halo2curves::pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    use super::*;
+
+    use crate::planner::SimpleFloorPlanner;
+
+    /// Proves `a op b == (c, overflow)` for a public `c` and flag.
+    #[derive(Default)]
+    struct IntegerCircuit<const N: usize> {
+        op: Option<IntOp>,
+        mode: Option<Overflow>,
+        a: Value<u64>,
+        b: Value<u64>,
+    }
+
+    impl<const N: usize> Circuit<Fp> for IntegerCircuit<N> {
+        type Config = (IntegerConfig<Fp>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                op: self.op,
+                mode: self.mode,
+                ..Self::default()
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 6].map(|_| meta.advice_column());
+            let constants = meta.fixed_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (IntegerChip::configure(meta, advice, constants), instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = IntegerChip::construct(config);
+            chip.load_table(layouter.namespace(|| "table"))?;
+
+            let a = chip.witness::<N>(layouter.namespace(|| "a"), self.a)?;
+            let b = chip.witness::<N>(layouter.namespace(|| "b"), self.b)?;
+            let op = self.op.ok_or(Error::Synthesis)?;
+            let mode = self.mode.ok_or(Error::Synthesis)?;
+            let c = chip.op(layouter.namespace(|| "op"), op, mode, &a, &b)?;
+
+            layouter.constrain_instance(c.value.0.cell(), instance, 0);
+            layouter.constrain_instance(c.overflow.cell(), instance, 1);
+            Ok(())
+        }
+    }
+
+    /// Rust's `wrapping_*`, `overflowing_*` and `checked_*` on `$t`.
+    macro_rules! native {
+        ($t:ty, $op:expr, $a:expr, $b:expr) => {{
+            let (a, b) = ($a as $t, $b as $t);
+            let (wrapping, overflowing, checked) = match $op {
+                IntOp::Add => (a.wrapping_add(b), a.overflowing_add(b), a.checked_add(b)),
+                IntOp::Sub => (a.wrapping_sub(b), a.overflowing_sub(b), a.checked_sub(b)),
+                IntOp::Mul => (a.wrapping_mul(b), a.overflowing_mul(b), a.checked_mul(b)),
+            };
+            assert_eq!(wrapping, overflowing.0);
+            (wrapping as u64, overflowing.1, checked.map(|c| c as u64))
+        }};
+    }
+
+    fn check<const N: usize>(
+        op: IntOp,
+        a: u64,
+        b: u64,
+        (wrapping, overflow, checked): (u64, bool, Option<u64>),
+    ) {
+        assert_eq!(op.overflowing(a, b, N), (wrapping, overflow));
+
+        let circuit = |mode| IntegerCircuit::<N> {
+            op: Some(op),
+            mode: Some(mode),
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+        let instance = vec![vec![Fp::from(wrapping), Fp::from(overflow as u64)]];
+
+        MockProver::run(9, &circuit(Overflow::Wrapping), instance.clone())
+            .unwrap()
+            .assert_satisfied();
+
+        // neither the result nor the flag can be anything else
+        for wrong in [
+            vec![vec![
+                Fp::from(wrapping) + Fp::ONE,
+                Fp::from(overflow as u64),
+            ]],
+            vec![vec![Fp::from(wrapping), Fp::from(!overflow as u64)]],
+        ] {
+            assert!(MockProver::run(9, &circuit(Overflow::Wrapping), wrong)
+                .unwrap()
+                .verify()
+                .is_err());
+        }
+
+        let checked_prover = MockProver::run(9, &circuit(Overflow::Checked), instance).unwrap();
+        assert_eq!(
+            checked_prover.verify().is_ok(),
+            checked.is_some(),
+            "checked {} u{} of {} and {}",
+            op,
+            N,
+            a,
+            b
+        );
+    }
+
+    #[test]
+    fn native_ops_test() {
+        for op in IntOp::ALL {
+            for (a, b) in [
+                (0, 0),
+                (200, 100),
+                (100, 200),
+                (u64::MAX, 2),
+                (1 << 40, 1 << 30),
+            ] {
+                let (wrapping, overflow, _) = native!(u64, op, a, b);
+                assert_eq!(op.overflowing(a, b, 64), (wrapping, overflow));
+                let (wrapping, overflow, _) = native!(u32, op, a as u32, b as u32);
+                assert_eq!(
+                    op.overflowing(a as u32 as u64, b as u32 as u64, 32),
+                    (wrapping, overflow)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn u8_test() {
+        let values = [0, 1, 127, 128, 254, u8::MAX as u64];
+        for op in IntOp::ALL {
+            for a in values {
+                for b in values {
+                    check::<8>(op, a, b, native!(u8, op, a, b));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn u32_test() {
+        let values = [0, 1, 0xffff, 0x1_0000, 0x8000_0000, u32::MAX as u64];
+        for op in IntOp::ALL {
+            for a in values {
+                for b in values {
+                    check::<32>(op, a, b, native!(u32, op, a, b));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn u64_test() {
+        let values = [0, 3, 1 << 32, 0xdead_beef_cafe_babe, u64::MAX];
+        for op in IntOp::ALL {
+            for a in values {
+                for b in values {
+                    check::<64>(op, a, b, native!(u64, op, a, b));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn out_of_range_test() {
+        // 256 is not a u8, even if 256 + 0 fits in a field element
+        let circuit = IntegerCircuit::<8> {
+            op: Some(IntOp::Add),
+            mode: Some(Overflow::Wrapping),
+            a: Value::known(256),
+            b: Value::known(0),
+        };
+        let instance = vec![vec![Fp::ZERO, Fp::ONE]];
+        assert!(MockProver::run(9, &circuit, instance)
+            .unwrap()
+            .verify()
+            .is_err());
+    }
+}
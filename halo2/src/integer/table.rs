@@ -0,0 +1,39 @@
+use halo2_proofs::{
+    circuit::{Layouter, Value},
+    plonk::{ConstraintSystem, Error, TableColumn},
+};
+
+use crate::field::FieldExt;
+
+/// A lookup table of every byte, 256 rows.
+#[derive(Clone, Debug)]
+pub struct ByteTable<F: FieldExt> {
+    pub(super) value: TableColumn,
+    _marker: std::marker::PhantomData<F>,
+}
+
+impl<F: FieldExt> ByteTable<F> {
+    pub(super) fn configure(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            value: meta.lookup_table_column(),
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    pub(super) fn load(&self, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "byte table",
+            |mut table| {
+                for byte in 0..256 {
+                    table.assign_cell(
+                        || "byte",
+                        self.value,
+                        byte,
+                        || Value::known(F::from(byte as u64)),
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
@@ -1,14 +1,43 @@
+//! `value == 0` as an expression other gates can use.
+//!
+//! The chip witnesses `value_inv`, the inverse of `value` or zero, and
+//! constrains `value · (1 - value · value_inv) = 0`. Then
+//! `is_zero_expr = 1 - value · value_inv` is one exactly when `value` is zero.
+//! [`FunctionChip`] uses it to select `b` or `c`.
+
 use halo2_proofs::{
-    arithmetic::FieldExt,
-    circuit::{Layouter, Region, SimpleFloorPlanner},
+    circuit::{Chip, Layouter, Region, Value},
     plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Selector, VirtualCells},
     poly::Rotation,
 };
+use serde::Deserialize;
+
+use crate::cell::RegionExt;
+use crate::field::FieldExt;
+use crate::input::{CircuitInput, CircuitWithInstances, FieldValue, InputError, NoPublicInputs};
+use crate::instance::{InstanceLayout, NamedInstances};
+use crate::planner::SimpleFloorPlanner;
+
+/// Assigns the witness of an is-zero expression.
+pub trait IsZeroInstructions<F: FieldExt>: Chip<F> {
+    /// Assigns the inverse of `value`, or zero, in row `offset` of `region`,
+    /// where the gate is enabled.
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        value: Value<F>,
+    ) -> Result<(), Error>;
+
+    /// One if the value is zero and zero otherwise, in the rows where the gate
+    /// is enabled.
+    fn is_zero_expr(&self) -> Expression<F>;
+}
 
 #[derive(Clone, Debug)]
 pub struct IsZeroConfig<F: FieldExt> {
     pub value_inv: Column<Advice>,
-    pub is_zero_expr: Expression<F>, // it's zero if it's not zero :)
+    pub is_zero_expr: Expression<F>,
 }
 
 pub struct IsZeroChip<F: FieldExt> {
@@ -30,7 +59,7 @@ impl<F: FieldExt> IsZeroChip<F> {
         value: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
         value_inv: Column<Advice>,
     ) -> IsZeroConfig<F> {
-        let is_zero_expr = Expression::Constant(F::zero());
+        let mut is_zero_expr = Expression::Constant(F::ZERO);
 
         meta.create_gate("is zero check gate", |meta| {
             // This is the expression that we want to be zero
@@ -43,8 +72,8 @@ impl<F: FieldExt> IsZeroChip<F> {
             let value = value(meta);
             let value_inv = meta.query_advice(value_inv, Rotation::cur());
 
-            let is_zero_expr = Expression::Constant(F::one()) - (value.clone() * value_inv);
-            vec![(zero_check * value * is_zero_expr)]
+            is_zero_expr = Expression::Constant(F::ONE) - (value.clone() * value_inv);
+            vec![(zero_check * value * is_zero_expr.clone())]
         });
 
         IsZeroConfig {
@@ -52,21 +81,40 @@ impl<F: FieldExt> IsZeroChip<F> {
             is_zero_expr,
         }
     }
+}
 
-    pub fn assign(&self, r: &mut Region<'_, F>, value: F) -> Result<(), Error> {
-        let value_inv = value.invert().unwrap_or(F::zero());
-        r.assign_advice(
-            || "value inverse",
-            self.config.value_inv,
-            0,
-            || Ok(value_inv),
-        )?;
+impl<F: FieldExt> Chip<F> for IsZeroChip<F> {
+    type Config = IsZeroConfig<F>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> IsZeroInstructions<F> for IsZeroChip<F> {
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        value: Value<F>,
+    ) -> Result<(), Error> {
+        let value_inv = value.map(|value| value.invert().unwrap_or(F::ZERO));
+        region.assign(self.config.value_inv, offset, value_inv);
 
         Ok(())
     }
+
+    fn is_zero_expr(&self) -> Expression<F> {
+        self.config.is_zero_expr.clone()
+    }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct FunctionConfig<F: FieldExt> {
     pub a: Column<Advice>,
     pub b: Column<Advice>,
@@ -81,11 +129,11 @@ pub struct FunctionChip<F: FieldExt> {
 }
 
 impl<F: FieldExt> FunctionChip<F> {
-    fn construct(config: FunctionConfig<F>) -> Self {
+    pub fn construct(config: FunctionConfig<F>) -> Self {
         Self { config }
     }
 
-    fn configure(meta: &mut ConstraintSystem<F>) -> FunctionConfig<F> {
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> FunctionConfig<F> {
         let a = meta.advice_column();
         let b = meta.advice_column();
         let c = meta.advice_column();
@@ -116,8 +164,8 @@ impl<F: FieldExt> FunctionChip<F> {
             let a_is_zero = a_is_zero_config.is_zero_expr.clone();
 
             vec![
-                selector.clone() * a_is_zero.clone() * (c - output.clone()),
-                selector * (Expression::Constant(F::one()) - a_is_zero) * (b - output),
+                selector.clone() * a_is_zero.clone() * (b - output.clone()),
+                selector * (Expression::Constant(F::ONE) - a_is_zero) * (c - output),
             ]
         });
 
@@ -137,18 +185,18 @@ impl<F: FieldExt> FunctionChip<F> {
             || "function regions",
             |mut r| {
                 self.config.selector.enable(&mut r, 0)?;
-                a_is_zero_chip.assign(&mut r, a)?;
-                r.assign_advice(|| "a", self.config.a, 0, || Ok(a))?;
-                r.assign_advice(|| "b", self.config.b, 0, || Ok(b))?;
-                r.assign_advice(|| "c", self.config.c, 0, || Ok(c))?;
+                a_is_zero_chip.assign(&mut r, 0, Value::known(a))?;
+                r.assign(self.config.a, 0, Value::known(a));
+                r.assign(self.config.b, 0, Value::known(b));
+                r.assign(self.config.c, 0, Value::known(c));
 
                 // calculate out here:
-                let out = match a == F::zero() {
+                let out = match a == F::ZERO {
                     true => b,
                     false => c,
                 };
 
-                r.assign_advice(|| "out", self.config.out, 0, || Ok(out))?;
+                r.assign(self.config.out, 0, Value::known(out));
 
                 Ok(())
             },
@@ -158,6 +206,19 @@ impl<F: FieldExt> FunctionChip<F> {
     }
 }
 
+impl<F: FieldExt> Chip<F> for FunctionChip<F> {
+    type Config = FunctionConfig<F>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
 #[derive(Default)]
 pub struct FunctionCircuit<F: FieldExt> {
     pub a: F,
@@ -165,9 +226,16 @@ pub struct FunctionCircuit<F: FieldExt> {
     pub c: F,
 }
 
+impl<F: FieldExt> NamedInstances for FunctionCircuit<F> {
+    fn instance_layout() -> InstanceLayout {
+        InstanceLayout::empty()
+    }
+}
+
 impl<F: FieldExt> Circuit<F> for FunctionCircuit<F> {
     type Config = FunctionConfig<F>;
     type FloorPlanner = SimpleFloorPlanner;
+    type Params = ();
 
     fn without_witnesses(&self) -> Self {
         Self::default()
@@ -184,9 +252,40 @@ impl<F: FieldExt> Circuit<F> for FunctionCircuit<F> {
     }
 }
 
+/// The JSON inputs of [`FunctionCircuit`], see [`crate::input`].
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FunctionInputFile {
+    pub private: FunctionPrivateInputs,
+    #[serde(default)]
+    pub public: NoPublicInputs,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FunctionPrivateInputs {
+    pub a: FieldValue,
+    pub b: FieldValue,
+    pub c: FieldValue,
+}
+
+impl CircuitInput for FunctionInputFile {
+    type Circuit<F: FieldExt> = FunctionCircuit<F>;
+
+    fn build<F: FieldExt>(&self) -> Result<CircuitWithInstances<Self, F>, InputError> {
+        let circuit = FunctionCircuit {
+            a: self.private.a.to_field("private.a")?,
+            b: self.private.b.to_field("private.b")?,
+            c: self.private.c.to_field("private.c")?,
+        };
+
+        Ok((circuit, vec![]))
+    }
+}
+
 #[cfg(test)]
 pub mod test {
-    use halo2_proofs::{dev::MockProver, pasta::Fp};
+    use halo2_proofs::{dev::MockProver, halo2curves::pasta::Fp};
 
     use super::*;
 
@@ -201,5 +300,15 @@ pub mod test {
         let prover = MockProver::run(5, &c, vec![]).unwrap();
 
         prover.assert_satisfied();
+
+        let c = FunctionCircuit {
+            a: Fp::from(3),
+            b: Fp::from(1),
+            c: Fp::from(2),
+        };
+
+        let prover = MockProver::run(5, &c, vec![]).unwrap();
+
+        prover.assert_satisfied();
     }
 }
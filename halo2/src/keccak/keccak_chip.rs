@@ -0,0 +1,362 @@
+use halo2_proofs::circuit::{Chip, Layouter, Value};
+use halo2_proofs::plonk::{Advice, Column, ConstraintSystem, Error, Fixed};
+
+use super::{pad, Padding, RATE, ROTATIONS, ROUND_CONSTANTS};
+use crate::bitwise::{BitwiseChip, BitwiseConfig, BitwiseInstructions, BitwiseTable};
+use crate::cell::{AssignedCell, RegionExt};
+use crate::field::FieldExt;
+
+/// A 64-bit lane of the state. Every lane is range checked by the operation
+/// that produces it.
+#[derive(Clone, Debug)]
+pub struct Lane<F: FieldExt>(pub AssignedCell<F>);
+
+impl<F: FieldExt> Lane<F> {
+    pub fn value(&self) -> Value<u64> {
+        self.0.field_value().map(|v| v.get_lower_128() as u64)
+    }
+}
+
+/// The state of the permutation, lane `(x, y)` at `x + 5 * y`.
+pub type State<F> = [Lane<F>; 25];
+
+/// The Keccak sponge: absorbing bytes, permuting and squeezing lanes.
+pub trait KeccakInstructions<F: FieldExt>: Chip<F> {
+    /// Loads the bitwise tables; call once per circuit.
+    fn load_tables(&self, layouter: impl Layouter<F>) -> Result<(), Error>;
+
+    /// Assigns private bytes. They are range checked once they are absorbed.
+    fn load_bytes(
+        &self,
+        layouter: impl Layouter<F>,
+        values: &[Value<u8>],
+    ) -> Result<Vec<AssignedCell<F>>, Error>;
+
+    /// Assigns values fixed by the circuit, e.g. message padding.
+    fn load_constants(
+        &self,
+        layouter: impl Layouter<F>,
+        values: &[u64],
+    ) -> Result<Vec<AssignedCell<F>>, Error>;
+
+    /// Hashes `message` with a rate of [`RATE`] bytes and returns the first 32
+    /// bytes of the output. The message length is part of the circuit, so the
+    /// padding is made of constants.
+    fn hash(
+        &self,
+        layouter: impl Layouter<F>,
+        message: &[AssignedCell<F>],
+        padding: Padding,
+    ) -> Result<Vec<AssignedCell<F>>, Error>;
+
+    /// XORs a block of [`RATE`] bytes into the first lanes of `state`, or into
+    /// the all-zero state if there is none yet.
+    fn absorb(
+        &self,
+        layouter: impl Layouter<F>,
+        state: Option<&State<F>>,
+        block: &[AssignedCell<F>],
+    ) -> Result<State<F>, Error>;
+
+    /// Keccak-f\[1600\], 24 rounds.
+    fn permute(&self, layouter: impl Layouter<F>, state: &State<F>) -> Result<State<F>, Error>;
+
+    /// The first 32 bytes of the state.
+    fn squeeze(
+        &self,
+        layouter: impl Layouter<F>,
+        state: &State<F>,
+    ) -> Result<Vec<AssignedCell<F>>, Error>;
+}
+
+#[derive(Clone, Debug)]
+pub struct KeccakConfig<F: FieldExt> {
+    bitwise: BitwiseConfig<F, 8>,
+    /// holds message bytes and constants
+    advice: Column<Advice>,
+}
+
+/// Keccak-f\[1600\] and its sponge, on byte-sized chunks of the lanes.
+pub struct KeccakChip<F: FieldExt> {
+    config: KeccakConfig<F>,
+    bitwise: BitwiseChip<F, 8>,
+}
+
+impl<F: FieldExt> Chip<F> for KeccakChip<F> {
+    type Config = KeccakConfig<F>;
+    type Loaded = [BitwiseTable<F, 8>; 3];
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        self.bitwise.loaded()
+    }
+}
+
+impl<F: FieldExt> KeccakChip<F> {
+    pub fn construct(config: KeccakConfig<F>) -> Self {
+        Self {
+            bitwise: BitwiseChip::construct(config.bitwise.clone()),
+            config,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        chunks: [Column<Advice>; 3],
+        words: [Column<Advice>; 3],
+        constants: Column<Fixed>,
+    ) -> KeccakConfig<F> {
+        meta.enable_constant(constants);
+
+        KeccakConfig {
+            bitwise: BitwiseChip::configure(meta, chunks, words),
+            advice: words[0],
+        }
+    }
+
+    fn round(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &State<F>,
+        rc: &AssignedCell<F>,
+    ) -> Result<State<F>, Error> {
+        // θ: every lane is XORed with the parities of two neighbouring columns
+        let mut c = vec![];
+        for x in 0..5 {
+            let mut parity = a[x].0.clone();
+            for y in 1..5 {
+                parity = self.bitwise.xor(
+                    layouter.namespace(|| format!("theta c[{}]", x)),
+                    &parity,
+                    &a[x + 5 * y].0,
+                    64,
+                )?;
+            }
+            c.push(parity);
+        }
+
+        let mut d = vec![];
+        for x in 0..5 {
+            let rotated = self.bitwise.rotate_left(
+                layouter.namespace(|| format!("theta rot c[{}]", (x + 1) % 5)),
+                &c[(x + 1) % 5],
+                1,
+                64,
+            )?;
+            d.push(self.bitwise.xor(
+                layouter.namespace(|| format!("theta d[{}]", x)),
+                &c[(x + 4) % 5],
+                &rotated,
+                64,
+            )?);
+        }
+
+        // θ, then ρ and π: the lane at (x, y) is rotated and moved to (y, 2x + 3y)
+        let mut b: Vec<Option<AssignedCell<F>>> = vec![None; 25];
+        for y in 0..5 {
+            for x in 0..5 {
+                let lane = self.bitwise.xor(
+                    layouter.namespace(|| format!("theta a[{}][{}]", x, y)),
+                    &a[x + 5 * y].0,
+                    &d[x],
+                    64,
+                )?;
+                let rotation = ROTATIONS[x + 5 * y] as usize;
+                let lane = if rotation == 0 {
+                    lane
+                } else {
+                    self.bitwise.rotate_left(
+                        layouter.namespace(|| format!("rho a[{}][{}]", x, y)),
+                        &lane,
+                        rotation,
+                        64,
+                    )?
+                };
+                b[y + 5 * ((2 * x + 3 * y) % 5)] = Some(lane);
+            }
+        }
+        let b: Vec<AssignedCell<F>> = b.into_iter().map(Option::unwrap).collect();
+
+        // χ: a ^ (!b & c) is a ^ (c ^ (b & c)), which needs no NOT
+        let mut out = vec![];
+        for y in 0..5 {
+            for x in 0..5 {
+                let (b0, b1, b2) = (
+                    &b[x + 5 * y],
+                    &b[(x + 1) % 5 + 5 * y],
+                    &b[(x + 2) % 5 + 5 * y],
+                );
+                let name = |step: &str| format!("chi {} [{}][{}]", step, x, y);
+                let and = self
+                    .bitwise
+                    .and(layouter.namespace(|| name("and")), b1, b2, 64)?;
+                let masked = self
+                    .bitwise
+                    .xor(layouter.namespace(|| name("mask")), b2, &and, 64)?;
+                out.push(
+                    self.bitwise
+                        .xor(layouter.namespace(|| name("xor")), b0, &masked, 64)?,
+                );
+            }
+        }
+
+        // ι
+        out[0] = self
+            .bitwise
+            .xor(layouter.namespace(|| "iota"), &out[0], rc, 64)?;
+
+        Ok(out
+            .into_iter()
+            .map(Lane)
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap())
+    }
+}
+
+impl<F: FieldExt> KeccakInstructions<F> for KeccakChip<F> {
+    fn load_tables(&self, layouter: impl Layouter<F>) -> Result<(), Error> {
+        self.bitwise.load_tables(layouter)
+    }
+
+    fn load_bytes(
+        &self,
+        mut layouter: impl Layouter<F>,
+        values: &[Value<u8>],
+    ) -> Result<Vec<AssignedCell<F>>, Error> {
+        layouter.assign_region(
+            || "bytes",
+            |mut region| {
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(row, value)| {
+                        Ok(
+                            region.assign(
+                                self.config.advice,
+                                row,
+                                value.map(|v| F::from(v as u64)),
+                            ),
+                        )
+                    })
+                    .collect()
+            },
+        )
+    }
+
+    fn load_constants(
+        &self,
+        mut layouter: impl Layouter<F>,
+        values: &[u64],
+    ) -> Result<Vec<AssignedCell<F>>, Error> {
+        layouter.assign_region(
+            || "constants",
+            |mut region| {
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(row, value)| {
+                        region.assign_constant(self.config.advice, row, F::from(*value))
+                    })
+                    .collect()
+            },
+        )
+    }
+
+    fn hash(
+        &self,
+        mut layouter: impl Layouter<F>,
+        message: &[AssignedCell<F>],
+        padding: Padding,
+    ) -> Result<Vec<AssignedCell<F>>, Error> {
+        let padded = pad(&vec![0; message.len()], padding);
+        let padding: Vec<u64> = padded[message.len()..].iter().map(|b| *b as u64).collect();
+
+        let mut bytes = message.to_vec();
+        bytes.extend(self.load_constants(layouter.namespace(|| "padding"), &padding)?);
+
+        let mut state = None;
+        for (i, block) in bytes.chunks(RATE).enumerate() {
+            let absorbed = self.absorb(
+                layouter.namespace(|| format!("absorb {}", i)),
+                state.as_ref(),
+                block,
+            )?;
+            state = Some(self.permute(layouter.namespace(|| format!("permute {}", i)), &absorbed)?);
+        }
+
+        self.squeeze(layouter.namespace(|| "squeeze"), &state.unwrap())
+    }
+
+    fn absorb(
+        &self,
+        mut layouter: impl Layouter<F>,
+        state: Option<&State<F>>,
+        block: &[AssignedCell<F>],
+    ) -> Result<State<F>, Error> {
+        assert_eq!(block.len(), RATE, "a block is {} bytes", RATE);
+
+        let mut lanes = vec![];
+        for (i, bytes) in block.chunks(8).enumerate() {
+            // lanes are little-endian, chunks most significant first
+            let bytes: Vec<_> = bytes.iter().rev().cloned().collect();
+            let lane = self
+                .bitwise
+                .compose(layouter.namespace(|| format!("lane {}", i)), &bytes)?;
+            lanes.push(match state {
+                Some(state) => self.bitwise.xor(
+                    layouter.namespace(|| format!("absorb lane {}", i)),
+                    &state[i].0,
+                    &lane,
+                    64,
+                )?,
+                None => lane,
+            });
+        }
+
+        match state {
+            Some(state) => lanes.extend(state[RATE / 8..].iter().map(|lane| lane.0.clone())),
+            None => lanes.extend(
+                self.load_constants(layouter.namespace(|| "capacity"), &[0; 25 - RATE / 8])?,
+            ),
+        }
+
+        Ok(lanes
+            .into_iter()
+            .map(Lane)
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap())
+    }
+
+    fn permute(&self, mut layouter: impl Layouter<F>, state: &State<F>) -> Result<State<F>, Error> {
+        let constants =
+            self.load_constants(layouter.namespace(|| "round constants"), &ROUND_CONSTANTS)?;
+
+        let mut state = state.clone();
+        for (i, rc) in constants.iter().enumerate() {
+            state = self.round(layouter.namespace(|| format!("round {}", i)), &state, rc)?;
+        }
+        Ok(state)
+    }
+
+    fn squeeze(
+        &self,
+        mut layouter: impl Layouter<F>,
+        state: &State<F>,
+    ) -> Result<Vec<AssignedCell<F>>, Error> {
+        let mut bytes = vec![];
+        for (i, lane) in state[..4].iter().enumerate() {
+            let chunks = self.bitwise.decompose(
+                layouter.namespace(|| format!("lane {}", i)),
+                &lane.0,
+                64,
+            )?;
+            bytes.extend(chunks.into_iter().rev());
+        }
+        Ok(bytes)
+    }
+}
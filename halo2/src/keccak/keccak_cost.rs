@@ -0,0 +1,41 @@
+use halo2_proofs::halo2curves::pasta::Fp;
+use zkG::backend::{Backend, GenericCircuit};
+use zkG::keccak::Keccak256PreimageInput;
+use zkG::planner::SimpleFloorPlanner;
+use zkG::planner::{advice_rows_used, min_k, rows_used};
+use zkG::sha256::Sha256PreimageInput;
+
+/// Prints the rows `input` uses, with and without its lookup tables, the `k`
+/// they fit in and the size of an IPA proof for it over Vesta.
+fn report<G: GenericCircuit>(name: &str, input: &G) {
+    let circuit = input.circuit::<Fp>();
+    let rows = rows_used::<Fp, SimpleFloorPlanner, _>(&circuit).unwrap();
+    let advice_rows = advice_rows_used::<Fp, SimpleFloorPlanner, _>(&circuit).unwrap();
+    let k = min_k(&circuit).unwrap();
+
+    let proof = Backend::IpaVesta.prove(input, Some(k)).unwrap().len();
+    println!(
+        "{:<24} {:>7} {:>12} {:>4} {:>10}",
+        name, rows, advice_rows, k, proof
+    );
+}
+
+fn main() {
+    println!(
+        "{:<24} {:>7} {:>12} {:>4} {:>10}",
+        "circuit", "rows", "advice rows", "k", "proof (B)"
+    );
+
+    report(
+        "sha256 (32 bytes)",
+        &Sha256PreimageInput { preimage: [7; 32] },
+    );
+    report(
+        "keccak256 (32 bytes)",
+        &Keccak256PreimageInput { preimage: [7; 32] },
+    );
+    report(
+        "keccak256 (136 bytes)",
+        &Keccak256PreimageInput { preimage: [7; 136] },
+    );
+}
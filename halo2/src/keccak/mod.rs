@@ -0,0 +1,414 @@
+//! Keccak-256, as in Ethereum, and SHA3-256 (FIPS 202) in a circuit.
+//!
+//! The state is 25 lanes of 64 bits, and every step of a round is an XOR, an
+//! AND or a rotation of lanes, done by [`BitwiseChip`] with 8-bit lookups. A
+//! round takes 101 XORs, 25 ANDs and 29 rotations, about 1270 rows, so one
+//! permutation takes about 30400 rows; with the 2^16-row tables a single block
+//! (up to 135 bytes) needs `k = 17`. `keccak_cost` reports the exact numbers.
+//!
+//! Keccak-256 and SHA3-256 only differ in the first padding byte, see [`Padding`].
+//! [`Keccak256PreimageCircuit`] fixes the message length, which makes every
+//! padding byte a constant of the circuit.
+//!
+//! [`BitwiseChip`]: crate::bitwise::BitwiseChip
+
+mod keccak_chip;
+
+pub use self::keccak_chip::{KeccakChip, KeccakConfig, KeccakInstructions, Lane, State};
+
+use crate::backend::GenericCircuit;
+use crate::field::FieldExt;
+use crate::instance::{InstanceColumns, InstanceLayout, NamedInstances};
+use crate::planner::SimpleFloorPlanner;
+use halo2_proofs::circuit::{Layouter, Value};
+use halo2_proofs::plonk::{Circuit, ConstraintSystem, Error};
+use std::marker::PhantomData;
+
+/// The bytes absorbed per permutation for a 256-bit output.
+pub const RATE: usize = 136;
+
+/// The constants XORed into lane `(0, 0)` by the ι step of every round.
+pub const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// The left rotation of lane `(x, y)` by the ρ step, at `x + 5 * y`.
+pub const ROTATIONS: [u32; 25] = [
+    0, 1, 62, 28, 27, //
+    36, 44, 6, 55, 20, //
+    3, 10, 43, 25, 39, //
+    41, 45, 15, 21, 8, //
+    18, 2, 61, 56, 14,
+];
+
+/// How the message is padded: the original Keccak appends `1`, SHA-3 the
+/// domain bits `01` before it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Padding {
+    /// Keccak-256, as used by Ethereum and Solidity's `keccak256`
+    Keccak,
+    /// SHA3-256
+    Sha3,
+}
+
+impl Padding {
+    /// The byte appended to the message, before the final `0x80`.
+    fn first_byte(&self) -> u8 {
+        match self {
+            Padding::Keccak => 0x01,
+            Padding::Sha3 => 0x06,
+        }
+    }
+}
+
+/// Keccak-f\[1600\] on native integers.
+pub fn keccak_f(state: &mut [u64; 25]) {
+    for rc in ROUND_CONSTANTS {
+        let c: Vec<u64> = (0..5)
+            .map(|x| (0..5).fold(0, |c, y| c ^ state[x + 5 * y]))
+            .collect();
+        for x in 0..5 {
+            let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+            for y in 0..5 {
+                state[x + 5 * y] ^= d;
+            }
+        }
+
+        let mut b = [0u64; 25];
+        for x in 0..5 {
+            for y in 0..5 {
+                b[y + 5 * ((2 * x + 3 * y) % 5)] =
+                    state[x + 5 * y].rotate_left(ROTATIONS[x + 5 * y]);
+            }
+        }
+
+        for x in 0..5 {
+            for y in 0..5 {
+                state[x + 5 * y] =
+                    b[x + 5 * y] ^ (!b[(x + 1) % 5 + 5 * y] & b[(x + 2) % 5 + 5 * y]);
+            }
+        }
+
+        state[0] ^= rc;
+    }
+}
+
+/// Pads `message` to a whole number of [`RATE`]-byte blocks.
+pub fn pad(message: &[u8], padding: Padding) -> Vec<u8> {
+    let mut bytes = message.to_vec();
+    bytes.push(padding.first_byte());
+    while !bytes.len().is_multiple_of(RATE) {
+        bytes.push(0);
+    }
+    *bytes.last_mut().unwrap() |= 0x80;
+    bytes
+}
+
+/// The 32-byte digest of `message` on native integers.
+pub fn hash(message: &[u8], padding: Padding) -> [u8; 32] {
+    let mut state = [0u64; 25];
+    for block in pad(message, padding).chunks(RATE) {
+        for (lane, bytes) in state.iter_mut().zip(block.chunks(8)) {
+            *lane ^= u64::from_le_bytes(bytes.try_into().unwrap());
+        }
+        keccak_f(&mut state);
+    }
+
+    let mut digest = [0; 32];
+    for (bytes, lane) in digest.chunks_mut(8).zip(state) {
+        bytes.copy_from_slice(&lane.to_le_bytes());
+    }
+    digest
+}
+
+pub fn keccak256(message: &[u8]) -> [u8; 32] {
+    hash(message, Padding::Keccak)
+}
+
+pub fn sha3_256(message: &[u8]) -> [u8; 32] {
+    hash(message, Padding::Sha3)
+}
+
+#[rustfmt::skip]
+const DIGEST_NAMES: [&str; 32] = [
+    "d0", "d1", "d2", "d3", "d4", "d5", "d6", "d7",
+    "d8", "d9", "d10", "d11", "d12", "d13", "d14", "d15",
+    "d16", "d17", "d18", "d19", "d20", "d21", "d22", "d23",
+    "d24", "d25", "d26", "d27", "d28", "d29", "d30", "d31",
+];
+
+/// The public inputs of [`Keccak256PreimageCircuit`]: the digest as 32 bytes.
+pub const DIGEST_LAYOUT: InstanceLayout = InstanceLayout::new(&[&DIGEST_NAMES]);
+
+/// Proves knowledge of a `LEN`-byte message with a public Keccak-256 digest.
+#[derive(Default)]
+pub struct Keccak256PreimageCircuit<F: FieldExt, const LEN: usize> {
+    pub preimage: Value<[u8; LEN]>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const LEN: usize> Keccak256PreimageCircuit<F, LEN> {
+    pub fn new(preimage: [u8; LEN]) -> Self {
+        Self {
+            preimage: Value::known(preimage),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: FieldExt, const LEN: usize> NamedInstances for Keccak256PreimageCircuit<F, LEN> {
+    fn instance_layout() -> InstanceLayout {
+        DIGEST_LAYOUT
+    }
+}
+
+impl<F: FieldExt, const LEN: usize> Circuit<F> for Keccak256PreimageCircuit<F, LEN> {
+    type Config = (KeccakConfig<F>, InstanceColumns);
+    type FloorPlanner = SimpleFloorPlanner;
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let chunks = [(); 3].map(|_| meta.advice_column());
+        let words = [(); 3].map(|_| meta.advice_column());
+        let constants = meta.fixed_column();
+        let instance = InstanceColumns::configure(meta, DIGEST_LAYOUT);
+
+        (
+            KeccakChip::configure(meta, chunks, words, constants),
+            instance,
+        )
+    }
+
+    fn synthesize(
+        &self,
+        (config, instance): Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = KeccakChip::construct(config);
+        chip.load_tables(layouter.namespace(|| "tables"))?;
+
+        let message = chip.load_bytes(
+            layouter.namespace(|| "preimage"),
+            &self.preimage.transpose_array(),
+        )?;
+        let digest = chip.hash(
+            layouter.namespace(|| "keccak256"),
+            &message,
+            Padding::Keccak,
+        )?;
+
+        for (byte, name) in digest.iter().zip(DIGEST_NAMES) {
+            instance.expose(layouter.namespace(|| name), byte.cell(), name)?;
+        }
+        Ok(())
+    }
+}
+
+/// The inputs of [`Keccak256PreimageCircuit`], independent of the field it is built over.
+#[derive(Clone, Debug)]
+pub struct Keccak256PreimageInput<const LEN: usize> {
+    pub preimage: [u8; LEN],
+}
+
+impl<const LEN: usize> GenericCircuit for Keccak256PreimageInput<LEN> {
+    type Circuit<F: FieldExt> = Keccak256PreimageCircuit<F, LEN>;
+
+    fn circuit<F: FieldExt>(&self) -> Self::Circuit<F> {
+        Keccak256PreimageCircuit::new(self.preimage)
+    }
+
+    fn instances<F: FieldExt>(&self) -> Vec<Vec<F>> {
+        let digest = keccak256(&self.preimage);
+        let values: Vec<(&str, F)> = DIGEST_NAMES
+            .iter()
+            .zip(digest)
+            .map(|(name, byte)| (*name, F::from(byte as u64)))
+            .collect();
+        DIGEST_LAYOUT.assemble(&values).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::{
+        arithmetic::Field,
+        dev::MockProver,
+        halo2curves::pasta::Fp,
+        plonk::{Column, Instance},
+    };
+
+    use super::*;
+
+    /// (message, Keccak-256 digest) pairs, as Solidity's `keccak256` computes them.
+    const KECCAK_VECTORS: [(&str, &str); 2] = [
+        (
+            "",
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470",
+        ),
+        (
+            "abc",
+            "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45",
+        ),
+    ];
+
+    /// (message, SHA3-256 digest) pairs from the FIPS 202 examples.
+    const SHA3_VECTORS: [(&str, &str); 3] = [
+        (
+            "",
+            "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a",
+        ),
+        (
+            "abc",
+            "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532",
+        ),
+        (
+            "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+            "41c0dba2a9d6240849100376a8235e2c82e1b9998a999e21db32dd97496d3376",
+        ),
+    ];
+
+    fn hex(digest: [u8; 32]) -> String {
+        digest.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    fn unhex(digest: &str) -> Vec<Fp> {
+        (0..32)
+            .map(|i| Fp::from(u8::from_str_radix(&digest[i * 2..i * 2 + 2], 16).unwrap() as u64))
+            .collect()
+    }
+
+    #[test]
+    fn native_test() {
+        // the first lanes of Keccak-f[1600] applied to the all-zero state
+        let mut state = [0; 25];
+        keccak_f(&mut state);
+        assert_eq!(
+            state[..3],
+            [0xf1258f7940e1dde7, 0x84d5ccf933c0478a, 0xd598261ea65aa9ee]
+        );
+
+        for (message, digest) in KECCAK_VECTORS {
+            assert_eq!(hex(keccak256(message.as_bytes())), digest);
+        }
+        for (message, digest) in SHA3_VECTORS {
+            assert_eq!(hex(sha3_256(message.as_bytes())), digest);
+        }
+        assert_eq!(
+            hex(sha3_256(&[b'a'; 1_000_000])),
+            "5c8875ae474a3634ba4fd55ec85bffd661f32aca75c6d699d0cdcb6c115891c1"
+        );
+    }
+
+    /// Hashes a public message and exposes the digest.
+    #[derive(Default)]
+    struct DigestCircuit {
+        message: Vec<u8>,
+        padding: Option<Padding>,
+    }
+
+    impl Circuit<Fp> for DigestCircuit {
+        type Config = (KeccakConfig<Fp>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let chunks = [(); 3].map(|_| meta.advice_column());
+            let words = [(); 3].map(|_| meta.advice_column());
+            let constants = meta.fixed_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (
+                KeccakChip::configure(meta, chunks, words, constants),
+                instance,
+            )
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = KeccakChip::construct(config);
+            chip.load_tables(layouter.namespace(|| "tables"))?;
+
+            let message: Vec<u64> = self.message.iter().map(|b| *b as u64).collect();
+            let message = chip.load_constants(layouter.namespace(|| "message"), &message)?;
+            let padding = self.padding.ok_or(Error::Synthesis)?;
+            let digest = chip.hash(layouter.namespace(|| "hash"), &message, padding)?;
+            for (row, byte) in digest.iter().enumerate() {
+                layouter.constrain_instance(byte.cell(), instance, row);
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn known_answer_test() {
+        // one vector of each padding, a permutation takes a while to check
+        let vectors = [
+            (KECCAK_VECTORS[1], Padding::Keccak),
+            (SHA3_VECTORS[0], Padding::Sha3),
+        ];
+        for ((message, digest), padding) in vectors {
+            let circuit = DigestCircuit {
+                message: message.as_bytes().to_vec(),
+                padding: Some(padding),
+            };
+            MockProver::run(17, &circuit, vec![unhex(digest)])
+                .unwrap()
+                .assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn preimage_test() {
+        let input = Keccak256PreimageInput {
+            preimage: *b"transfer(address,uint256)",
+        };
+        // the selector of the ERC-20 transfer function
+        assert_eq!(hex(keccak256(&input.preimage))[..8], *"a9059cbb");
+
+        let circuit = input.circuit::<Fp>();
+        let mut instances = input.instances::<Fp>();
+        MockProver::run(17, &circuit, instances.clone())
+            .unwrap()
+            .assert_satisfied();
+
+        instances[0][31] += Fp::ONE;
+        assert!(MockProver::run(17, &circuit, instances)
+            .unwrap()
+            .verify()
+            .is_err());
+    }
+}
@@ -1,6 +1,44 @@
-mod decompose_range_check;
-mod is_zero;
-mod range_check;
+//! Circuits and reusable chips on halo2-axiom.
+//!
+//! The chips are public, each with its configuration, and implement halo2's
+//! `Chip` trait; [`prelude`] gathers them. The range checks of
+//! [`range_check`] all name their chip `RangeCheckChip`, so they are imported
+//! from their example module.
+
+#![allow(non_snake_case)]
+
+pub mod backend;
+pub mod batch;
+pub mod bitwise;
+pub mod cell;
+pub mod decompose_range_check;
+pub mod division;
+pub mod ecc;
+pub mod evm;
+pub mod fibo_circuit;
+pub mod fibo_circuit2;
+pub mod fibo_circuit3;
+pub mod field;
+pub mod fixed_point;
+pub mod horner;
+pub mod input;
+pub mod instance;
+pub mod integer;
+pub mod is_zero;
+pub mod keccak;
+pub mod matmul;
+pub mod pasta;
+pub mod pedersen;
+pub mod planner;
+pub mod pow;
+pub mod prelude;
+pub mod proof;
+pub mod range_check;
+pub mod schnorr;
+pub mod sha256;
+pub mod sort;
+pub mod table;
+pub mod transfer;
 
 #[cfg(test)]
 pub mod test {}
@@ -0,0 +1,229 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{Chip, Layouter},
+    plonk::{Advice, Column, ConstraintSystem, Error, Fixed, Selector},
+    poly::Rotation,
+};
+
+use crate::cell::{AssignedCell, RegionExt};
+use crate::field::FieldExt;
+use crate::horner::Coefficient;
+
+/// Inner products and the matrix products built on them.
+pub trait InnerProductInstructions<F: FieldExt>: Chip<F> {
+    /// `Σ a_i·b_i`, in `ceil(n / W) + 1` rows for vectors of length `n`.
+    /// Instance columns holding entries need equality enabled.
+    fn inner_product(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &[Coefficient<F>],
+        b: &[Coefficient<F>],
+    ) -> Result<AssignedCell<F>, Error>;
+
+    /// The product of an `m × n` matrix `a` and an `n × p` matrix `b`, both
+    /// given row by row, as `m × p` inner products. Every entry is assigned
+    /// once, by the first inner product using it, and copied into the others.
+    fn matmul(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &[Vec<Coefficient<F>>],
+        b: &[Vec<Coefficient<F>>],
+    ) -> Result<Vec<Vec<AssignedCell<F>>>, Error>;
+
+    /// `M·v` for an `m × n` matrix given row by row.
+    fn matvec(
+        &self,
+        layouter: impl Layouter<F>,
+        matrix: &[Vec<Coefficient<F>>],
+        vector: &[Coefficient<F>],
+    ) -> Result<Vec<AssignedCell<F>>, Error>;
+}
+
+#[derive(Clone, Debug)]
+pub struct InnerProductConfig<F: FieldExt, const W: usize> {
+    a: [Column<Advice>; W],
+    b: [Column<Advice>; W],
+    acc: Column<Advice>,
+    q_inner_product: Selector,
+    _marker: PhantomData<F>,
+}
+
+/// Inner products of `W` terms per row, accumulated down the rows, and the
+/// matrix products built on them.
+pub struct InnerProductChip<F: FieldExt, const W: usize> {
+    config: InnerProductConfig<F, W>,
+}
+
+impl<F: FieldExt, const W: usize> Chip<F> for InnerProductChip<F, W> {
+    type Config = InnerProductConfig<F, W>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt, const W: usize> InnerProductChip<F, W> {
+    pub fn construct(config: InnerProductConfig<F, W>) -> Self {
+        Self { config }
+    }
+
+    /// `constants` must be a fixed column, which holds the zero every sum
+    /// starts from, the zeros padding the last row and any fixed entries.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        a: [Column<Advice>; W],
+        b: [Column<Advice>; W],
+        acc: Column<Advice>,
+        constants: Column<Fixed>,
+    ) -> InnerProductConfig<F, W> {
+        assert!(W >= 1, "a row must hold at least one term");
+
+        let q_inner_product = meta.selector();
+        for column in a.into_iter().chain(b).chain([acc]) {
+            meta.enable_equality(column);
+        }
+        meta.enable_constant(constants);
+
+        // a_0 | .. | a_{W-1} | b_0 | .. | b_{W-1} | acc | q_inner_product
+        // a_0   ..   a_{W-1}   b_0   ..   b_{W-1}   0     1
+        // a_W   ..   a_{2W-1}  b_W   ..   b_{2W-1}  acc   1
+        //                                           a·b
+        meta.create_gate("inner product", |meta| {
+            let q = meta.query_selector(q_inner_product);
+            let a = a.map(|column| meta.query_advice(column, Rotation::cur()));
+            let b = b.map(|column| meta.query_advice(column, Rotation::cur()));
+            let cur = meta.query_advice(acc, Rotation::cur());
+            let next = meta.query_advice(acc, Rotation::next());
+            let sum = a.into_iter().zip(b).fold(cur, |sum, (a, b)| sum + a * b);
+            vec![q * (next - sum)]
+        });
+
+        InnerProductConfig {
+            a,
+            b,
+            acc,
+            q_inner_product,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the sum and the cells holding the entries of `a` and `b`.
+    #[allow(clippy::type_complexity)]
+    fn assign_inner_product(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &[Coefficient<F>],
+        b: &[Coefficient<F>],
+    ) -> Result<(AssignedCell<F>, Vec<AssignedCell<F>>, Vec<AssignedCell<F>>), Error> {
+        assert_eq!(a.len(), b.len(), "the vectors must have the same length");
+        let config = &self.config;
+        let zero = Coefficient::Fixed(F::ZERO);
+
+        layouter.assign_region(
+            || "inner product",
+            |mut region| {
+                let mut acc = region.assign_constant(config.acc, 0, F::ZERO)?;
+                let mut a_cells = Vec::with_capacity(a.len());
+                let mut b_cells = Vec::with_capacity(b.len());
+
+                for row in 0..a.len().div_ceil(W) {
+                    config.q_inner_product.enable(&mut region, row)?;
+                    let mut sum = acc.field_value();
+                    for i in 0..W {
+                        // the last row is padded with zeros, fixed so that
+                        // they add nothing to the sum
+                        let index = row * W + i;
+                        let (a_i, b_i) = match (a.get(index), b.get(index)) {
+                            (Some(a_i), Some(b_i)) => (a_i, b_i),
+                            _ => (&zero, &zero),
+                        };
+                        let a_i = a_i.assign(&mut region, config.a[i], row)?;
+                        let b_i = b_i.assign(&mut region, config.b[i], row)?;
+                        sum = sum
+                            .zip(a_i.field_value())
+                            .zip(b_i.field_value())
+                            .map(|((sum, a), b)| sum + a * b);
+                        if index < a.len() {
+                            a_cells.push(a_i);
+                            b_cells.push(b_i);
+                        }
+                    }
+                    acc = region.assign(config.acc, row + 1, sum);
+                }
+                Ok((acc, a_cells, b_cells))
+            },
+        )
+    }
+}
+
+impl<F: FieldExt, const W: usize> InnerProductInstructions<F> for InnerProductChip<F, W> {
+    fn inner_product(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &[Coefficient<F>],
+        b: &[Coefficient<F>],
+    ) -> Result<AssignedCell<F>, Error> {
+        self.assign_inner_product(layouter, a, b)
+            .map(|(sum, _, _)| sum)
+    }
+
+    fn matmul(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &[Vec<Coefficient<F>>],
+        b: &[Vec<Coefficient<F>>],
+    ) -> Result<Vec<Vec<AssignedCell<F>>>, Error> {
+        let n = b.len();
+        let p = b.first().map_or(0, Vec::len);
+        assert!(
+            a.iter().all(|row| row.len() == n),
+            "the rows of a must be as long as b is high"
+        );
+        assert!(
+            b.iter().all(|row| row.len() == p),
+            "the rows of b must have the same length"
+        );
+
+        let mut a = a.to_vec();
+        let mut b = b.to_vec();
+        let mut product = Vec::with_capacity(a.len());
+        for (i, a_row) in a.iter_mut().enumerate() {
+            let mut row = Vec::with_capacity(p);
+            for j in 0..p {
+                let column: Vec<_> = b.iter().map(|row| row[j].clone()).collect();
+                let (sum, a_cells, b_cells) = self.assign_inner_product(
+                    layouter.namespace(|| format!("c[{}][{}]", i, j)),
+                    a_row,
+                    &column,
+                )?;
+                // only the cells of the final pass of the layouter are kept
+                for (entry, cell) in a_row.iter_mut().zip(a_cells) {
+                    *entry = Coefficient::Cell(cell);
+                }
+                for (row, cell) in b.iter_mut().zip(b_cells) {
+                    row[j] = Coefficient::Cell(cell);
+                }
+                row.push(sum);
+            }
+            product.push(row);
+        }
+        Ok(product)
+    }
+
+    fn matvec(
+        &self,
+        layouter: impl Layouter<F>,
+        matrix: &[Vec<Coefficient<F>>],
+        vector: &[Coefficient<F>],
+    ) -> Result<Vec<AssignedCell<F>>, Error> {
+        let column: Vec<_> = vector.iter().map(|v| vec![v.clone()]).collect();
+        let product = self.matmul(layouter, matrix, &column)?;
+        Ok(product.into_iter().flatten().collect())
+    }
+}
@@ -0,0 +1,45 @@
+use halo2_proofs::halo2curves::pasta::Fp;
+use zkG::backend::{Backend, GenericCircuit};
+use zkG::matmul::MatVecInput;
+use zkG::planner::SimpleFloorPlanner;
+use zkG::planner::{min_k, rows_used};
+
+/// The size of the matrix.
+const N: usize = 16;
+
+/// Prints the advice columns and rows a `N × N` matrix-vector product uses
+/// with `W` terms per row, the `k` they fit in and the size of an IPA proof
+/// over Vesta.
+fn report<const W: usize>() {
+    let input = MatVecInput::<N, N, W> {
+        matrix: [[3; N]; N],
+        vector: [-2; N],
+    };
+    let circuit = input.circuit::<Fp>();
+    let rows = rows_used::<Fp, SimpleFloorPlanner, _>(&circuit).unwrap();
+    let k = min_k(&circuit).unwrap();
+
+    let proof = Backend::IpaVesta.prove(&input, Some(k)).unwrap().len();
+    println!(
+        "{:>6} {:>14} {:>7} {:>4} {:>10}",
+        W,
+        2 * W + 1,
+        rows,
+        k,
+        proof
+    );
+}
+
+fn main() {
+    println!("{}×{} matrix times a vector", N, N);
+    println!(
+        "{:>6} {:>14} {:>7} {:>4} {:>10}",
+        "width", "advice columns", "rows", "k", "proof (B)"
+    );
+
+    report::<1>();
+    report::<2>();
+    report::<4>();
+    report::<8>();
+    report::<16>();
+}
@@ -0,0 +1,342 @@
+//! Inner products and matrix products, e.g. the layers of a small model
+//! evaluated on a public input.
+//!
+//! [`InnerProductChip`] multiplies `W` pairs of entries per row and adds them
+//! to an accumulator in the next row, so an inner product of length `n`
+//! takes `ceil(n / W) + 1` rows and `2W + 1` advice columns:
+//!
+//! ```text
+//!  a_0 .. a_{W-1} | b_0 .. b_{W-1} | acc
+//!  a_0 .. a_{W-1}   b_0 .. b_{W-1}   0      acc' = acc + Σ a_i·b_i
+//!  a_W .. a_{2W-1}  b_W .. b_{2W-1}  ..
+//!                                    a·b
+//! ```
+//!
+//! Entries are [`Coefficient`]s, as in [`crate::horner`]: constants, public
+//! inputs, private values or cells of other chips. A matrix product is one
+//! inner product per entry of the result. [`MatVecCircuit`] proves
+//! `M·v = y` for a private `M` and public `v` and `y`; `matmul_cost` compares
+//! its rows, `k` and proof size across widths.
+
+mod inner_product_chip;
+
+pub use self::inner_product_chip::{
+    InnerProductChip, InnerProductConfig, InnerProductInstructions,
+};
+
+use halo2_proofs::circuit::{Layouter, Value};
+use halo2_proofs::plonk::{Circuit, ConstraintSystem, Error};
+
+use crate::backend::GenericCircuit;
+use crate::field::FieldExt;
+use crate::fixed_point::signed;
+use crate::horner::Coefficient;
+use crate::instance::{InstanceColumns, InstanceLayout, NamedInstances};
+use crate::planner::SimpleFloorPlanner;
+
+/// The longest vector and result [`MatVecCircuit`] takes.
+pub const MAX_LEN: usize = 16;
+
+#[rustfmt::skip]
+const VECTOR_NAMES: [&str; MAX_LEN] = [
+    "v0", "v1", "v2", "v3", "v4", "v5", "v6", "v7",
+    "v8", "v9", "v10", "v11", "v12", "v13", "v14", "v15",
+];
+
+#[rustfmt::skip]
+const RESULT_NAMES: [&str; MAX_LEN] = [
+    "y0", "y1", "y2", "y3", "y4", "y5", "y6", "y7",
+    "y8", "y9", "y10", "y11", "y12", "y13", "y14", "y15",
+];
+
+/// `Σ a_i·b_i`.
+pub fn inner_product<F: FieldExt>(a: &[F], b: &[F]) -> F {
+    a.iter().zip(b).fold(F::ZERO, |sum, (a, b)| sum + *a * b)
+}
+
+/// The product of an `m × n` and an `n × p` matrix, both given row by row.
+pub fn matmul<F: FieldExt>(a: &[Vec<F>], b: &[Vec<F>]) -> Vec<Vec<F>> {
+    let p = b.first().map_or(0, Vec::len);
+    a.iter()
+        .map(|row| {
+            (0..p)
+                .map(|j| {
+                    let column: Vec<_> = b.iter().map(|row| row[j]).collect();
+                    inner_product(row, &column)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// `M·v` for a matrix given row by row.
+pub fn matvec<F: FieldExt>(matrix: &[Vec<F>], vector: &[F]) -> Vec<F> {
+    matrix
+        .iter()
+        .map(|row| inner_product(row, vector))
+        .collect()
+}
+
+/// Knowledge of an `M × N` matrix taking a public vector to a public result,
+/// with `W` terms per row.
+#[derive(Default)]
+pub struct MatVecCircuit<F: FieldExt, const M: usize, const N: usize, const W: usize> {
+    pub matrix: Value<[[F; N]; M]>,
+}
+
+impl<F: FieldExt, const M: usize, const N: usize, const W: usize> MatVecCircuit<F, M, N, W> {
+    /// The public inputs: the vector in the first column, the result in the second.
+    pub const LAYOUT: InstanceLayout =
+        InstanceLayout::new(&[VECTOR_NAMES.split_at(N).0, RESULT_NAMES.split_at(M).0]);
+
+    /// The public inputs of `matrix · vector`.
+    pub fn instances(matrix: &[Vec<F>], vector: &[F]) -> Vec<Vec<F>> {
+        let result = matvec(matrix, vector);
+        let values: Vec<(&str, F)> = VECTOR_NAMES
+            .iter()
+            .zip(vector)
+            .chain(RESULT_NAMES.iter().zip(&result))
+            .map(|(name, value)| (*name, *value))
+            .collect();
+        Self::LAYOUT.assemble(&values).unwrap()
+    }
+}
+
+impl<F: FieldExt, const M: usize, const N: usize, const W: usize> NamedInstances
+    for MatVecCircuit<F, M, N, W>
+{
+    fn instance_layout() -> InstanceLayout {
+        Self::LAYOUT
+    }
+}
+
+impl<F: FieldExt, const M: usize, const N: usize, const W: usize> Circuit<F>
+    for MatVecCircuit<F, M, N, W>
+{
+    type Config = (InnerProductConfig<F, W>, InstanceColumns);
+    type FloorPlanner = SimpleFloorPlanner;
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            matrix: Value::unknown(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        assert!(
+            N <= MAX_LEN && M <= MAX_LEN,
+            "at most {} rows and columns",
+            MAX_LEN
+        );
+
+        let a = [(); W].map(|_| meta.advice_column());
+        let b = [(); W].map(|_| meta.advice_column());
+        let acc = meta.advice_column();
+        let constants = meta.fixed_column();
+        let instance = InstanceColumns::configure(meta, Self::LAYOUT);
+        (
+            InnerProductChip::configure(meta, a, b, acc, constants),
+            instance,
+        )
+    }
+
+    fn synthesize(
+        &self,
+        (config, instance): Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = InnerProductChip::construct(config);
+        let matrix: Vec<Vec<_>> = (0..M)
+            .map(|i| {
+                (0..N)
+                    .map(|j| Coefficient::Witness(self.matrix.map(|matrix| matrix[i][j])))
+                    .collect()
+            })
+            .collect();
+        let vector = VECTOR_NAMES[..N]
+            .iter()
+            .map(|name| {
+                let (column, row) = instance.get(name)?;
+                Ok(Coefficient::Instance(column, row))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let result = chip.matvec(layouter.namespace(|| "M·v"), &matrix, &vector)?;
+        for (y, name) in result.iter().zip(RESULT_NAMES) {
+            instance.expose(layouter.namespace(|| name), y.cell(), name)?;
+        }
+        Ok(())
+    }
+}
+
+/// The inputs of [`MatVecCircuit`], as signed integers.
+#[derive(Clone, Debug)]
+pub struct MatVecInput<const M: usize, const N: usize, const W: usize> {
+    pub matrix: [[i64; N]; M],
+    pub vector: [i64; N],
+}
+
+impl<const M: usize, const N: usize, const W: usize> GenericCircuit for MatVecInput<M, N, W> {
+    type Circuit<F: FieldExt> = MatVecCircuit<F, M, N, W>;
+
+    fn circuit<F: FieldExt>(&self) -> Self::Circuit<F> {
+        MatVecCircuit {
+            matrix: Value::known(self.matrix.map(|row| row.map(|v| signed(v as i128)))),
+        }
+    }
+
+    fn instances<F: FieldExt>(&self) -> Vec<Vec<F>> {
+        let matrix: Vec<Vec<F>> = self
+            .matrix
+            .iter()
+            .map(|row| row.iter().map(|v| signed(*v as i128)).collect())
+            .collect();
+        let vector: Vec<F> = self.vector.iter().map(|v| signed(*v as i128)).collect();
+        MatVecCircuit::<F, M, N, W>::instances(&matrix, &vector)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::{
+        arithmetic::Field,
+        dev::MockProver,
+        halo2curves::pasta::Fp,
+        plonk::{Column, Instance},
+    };
+
+    use super::*;
+    use crate::planner::rows_used;
+
+    const MATRIX: [[i64; 5]; 3] = [[1, -2, 3, 0, 4], [5, 6, -7, 8, 9], [-1, 0, 0, 2, -3]];
+    const VECTOR: [i64; 5] = [2, 3, -1, 4, 5];
+
+    fn prove<const W: usize>(input: &MatVecInput<3, 5, W>, instances: Vec<Vec<Fp>>) -> bool {
+        MockProver::run(5, &input.circuit::<Fp>(), instances)
+            .unwrap()
+            .verify()
+            .is_ok()
+    }
+
+    fn matvec_test_with<const W: usize>() {
+        let input = MatVecInput::<3, 5, W> {
+            matrix: MATRIX,
+            vector: VECTOR,
+        };
+        let instances = input.instances::<Fp>();
+        assert_eq!(instances[1], [13, 112, -9].map(|v| signed::<Fp>(v as i128)));
+        assert!(prove(&input, instances.clone()));
+
+        // another result
+        let mut wrong = instances.clone();
+        wrong[1][1] += Fp::ONE;
+        assert!(!prove(&input, wrong));
+
+        // another vector with the same result
+        let mut wrong = instances;
+        wrong[0][3] += Fp::ONE;
+        assert!(!prove(&input, wrong));
+    }
+
+    #[test]
+    fn matvec_test() {
+        // 5 terms fill whole rows only for W = 1 and 5
+        matvec_test_with::<1>();
+        matvec_test_with::<2>();
+        matvec_test_with::<3>();
+        matvec_test_with::<5>();
+        matvec_test_with::<8>();
+    }
+
+    #[test]
+    fn rows_test() {
+        fn rows<const W: usize>() -> usize {
+            let circuit = MatVecCircuit::<Fp, 3, 5, W>::default();
+            rows_used::<Fp, SimpleFloorPlanner, _>(&circuit).unwrap()
+        }
+
+        // three regions of ceil(5 / W) + 1 rows
+        assert_eq!(rows::<1>(), 18);
+        assert_eq!(rows::<2>(), 12);
+        assert_eq!(rows::<3>(), 9);
+    }
+
+    /// `A·B = C` for a private `A`, a constant `B` and a public `C`.
+    #[derive(Default)]
+    struct MatMulCircuit {
+        a: Value<[[Fp; 3]; 2]>,
+    }
+
+    const B: [[u64; 2]; 3] = [[1, 2], [3, 4], [5, 6]];
+
+    impl Circuit<Fp> for MatMulCircuit {
+        type Config = (InnerProductConfig<Fp, 2>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let a = [(); 2].map(|_| meta.advice_column());
+            let b = [(); 2].map(|_| meta.advice_column());
+            let acc = meta.advice_column();
+            let constants = meta.fixed_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (
+                InnerProductChip::configure(meta, a, b, acc, constants),
+                instance,
+            )
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = InnerProductChip::construct(config);
+            let a: Vec<Vec<_>> = (0..2)
+                .map(|i| {
+                    (0..3)
+                        .map(|j| Coefficient::Witness(self.a.map(|a| a[i][j])))
+                        .collect()
+                })
+                .collect();
+            let b: Vec<Vec<_>> = B
+                .iter()
+                .map(|row| row.map(|v| Coefficient::Fixed(Fp::from(v))).to_vec())
+                .collect();
+
+            let c = chip.matmul(layouter.namespace(|| "A·B"), &a, &b)?;
+            for (i, c) in c.iter().flatten().enumerate() {
+                layouter.constrain_instance(c.cell(), instance, i);
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn matmul_test() {
+        let a = [[1, 0, 2], [3, 1, 1]].map(|row| row.map(Fp::from));
+        let b: Vec<Vec<Fp>> = B.iter().map(|row| row.map(Fp::from).to_vec()).collect();
+        let c = matmul(&a.map(|row| row.to_vec()), &b);
+        assert_eq!(
+            c,
+            [[11, 14], [11, 16]].map(|row| row.map(Fp::from).to_vec())
+        );
+
+        let circuit = MatMulCircuit { a: Value::known(a) };
+        let instances = vec![c.concat()];
+        MockProver::run(5, &circuit, instances.clone())
+            .unwrap()
+            .assert_satisfied();
+
+        let mut wrong = instances;
+        wrong[0][3] -= Fp::ONE;
+        let prover = MockProver::run(5, &circuit, wrong).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
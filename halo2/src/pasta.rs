@@ -0,0 +1,460 @@
+//! The Pasta curves, with scalar fields that halo2-axiom can prove over.
+//!
+//! halo2-axiom's prover keys its lookups by field element, so it needs the
+//! scalar field of the commitment curve to be `Hash`, which the fields of
+//! `pasta_curves` are not. [`Fp`] and [`Fq`] wrap them with a `Hash` impl on
+//! their canonical encoding, and [`vesta`] and [`pallas`] wrap the curves so that
+//! these are their scalar fields. Everything else forwards to `pasta_curves`.
+//!
+//! The base fields are left as they are: IPA over Vesta proves circuits over
+//! [`Fp`], the base field of Pallas, which is what the [`ecc`](crate::ecc)
+//! gadgets work in.
+
+use std::borrow::Borrow;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::{Product, Sum};
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use halo2_proofs::halo2curves::{
+    ff::{Field, FromUniformBytes, PrimeField, WithSmallOrderMulGroup},
+    pasta,
+};
+use rand_core::RngCore;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq, CtOption};
+
+macro_rules! impl_binop {
+    ($t:ident, $rhs:ty, $output:ty, $op:ident, $op_fn:ident, $assign:ident, $assign_fn:ident) => {
+        impl $op<$rhs> for $t {
+            type Output = $output;
+
+            fn $op_fn(self, rhs: $rhs) -> $output {
+                <$output>::from(self.0.$op_fn(rhs.0))
+            }
+        }
+
+        impl $op<&$rhs> for $t {
+            type Output = $output;
+
+            fn $op_fn(self, rhs: &$rhs) -> $output {
+                <$output>::from(self.0.$op_fn(rhs.0))
+            }
+        }
+
+        impl $assign<$rhs> for $t {
+            fn $assign_fn(&mut self, rhs: $rhs) {
+                self.0.$assign_fn(rhs.0);
+            }
+        }
+
+        impl $assign<&$rhs> for $t {
+            fn $assign_fn(&mut self, rhs: &$rhs) {
+                self.0.$assign_fn(rhs.0);
+            }
+        }
+    };
+}
+
+macro_rules! impl_wrapper {
+    ($t:ident, $inner:ty) => {
+        impl From<$inner> for $t {
+            fn from(inner: $inner) -> Self {
+                $t(inner)
+            }
+        }
+
+        impl From<$t> for $inner {
+            fn from(outer: $t) -> Self {
+                outer.0
+            }
+        }
+
+        impl ConstantTimeEq for $t {
+            fn ct_eq(&self, other: &Self) -> Choice {
+                self.0.ct_eq(&other.0)
+            }
+        }
+
+        impl ConditionallySelectable for $t {
+            fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+                $t(<$inner>::conditional_select(&a.0, &b.0, choice))
+            }
+        }
+
+        impl Neg for $t {
+            type Output = $t;
+
+            fn neg(self) -> $t {
+                $t(-self.0)
+            }
+        }
+    };
+}
+
+macro_rules! field {
+    ($name:ident, $inner:ty) => {
+        #[doc = concat!("`pasta_curves::", stringify!($name), "`, hashable.")]
+        #[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+        pub struct $name(pub $inner);
+
+        impl_wrapper!($name, $inner);
+        impl_binop!($name, $name, $name, Add, add, AddAssign, add_assign);
+        impl_binop!($name, $name, $name, Sub, sub, SubAssign, sub_assign);
+        impl_binop!($name, $name, $name, Mul, mul, MulAssign, mul_assign);
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+
+        impl Hash for $name {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.0.to_repr().hash(state);
+            }
+        }
+
+        impl From<u64> for $name {
+            fn from(value: u64) -> Self {
+                $name(<$inner>::from(value))
+            }
+        }
+
+        impl<T: Borrow<$name>> Sum<T> for $name {
+            fn sum<I: Iterator<Item = T>>(iter: I) -> Self {
+                iter.fold(Self::ZERO, |acc, x| acc + x.borrow())
+            }
+        }
+
+        impl<T: Borrow<$name>> Product<T> for $name {
+            fn product<I: Iterator<Item = T>>(iter: I) -> Self {
+                iter.fold(Self::ONE, |acc, x| acc * x.borrow())
+            }
+        }
+
+        impl Field for $name {
+            const ZERO: Self = $name(<$inner>::ZERO);
+            const ONE: Self = $name(<$inner>::ONE);
+
+            fn random(rng: impl RngCore) -> Self {
+                $name(<$inner>::random(rng))
+            }
+
+            fn square(&self) -> Self {
+                $name(self.0.square())
+            }
+
+            fn double(&self) -> Self {
+                $name(self.0.double())
+            }
+
+            fn invert(&self) -> CtOption<Self> {
+                self.0.invert().map($name)
+            }
+
+            fn sqrt(&self) -> CtOption<Self> {
+                self.0.sqrt().map($name)
+            }
+
+            fn sqrt_ratio(num: &Self, div: &Self) -> (Choice, Self) {
+                let (is_square, root) = <$inner>::sqrt_ratio(&num.0, &div.0);
+                (is_square, $name(root))
+            }
+        }
+
+        impl PrimeField for $name {
+            type Repr = [u8; 32];
+
+            const MODULUS: &'static str = <$inner>::MODULUS;
+            const NUM_BITS: u32 = <$inner>::NUM_BITS;
+            const CAPACITY: u32 = <$inner>::CAPACITY;
+            const TWO_INV: Self = $name(<$inner>::TWO_INV);
+            const MULTIPLICATIVE_GENERATOR: Self = $name(<$inner>::MULTIPLICATIVE_GENERATOR);
+            const S: u32 = <$inner>::S;
+            const ROOT_OF_UNITY: Self = $name(<$inner>::ROOT_OF_UNITY);
+            const ROOT_OF_UNITY_INV: Self = $name(<$inner>::ROOT_OF_UNITY_INV);
+            const DELTA: Self = $name(<$inner>::DELTA);
+
+            fn from_repr(repr: Self::Repr) -> CtOption<Self> {
+                <$inner>::from_repr(repr).map($name)
+            }
+
+            fn to_repr(&self) -> Self::Repr {
+                self.0.to_repr()
+            }
+
+            fn is_odd(&self) -> Choice {
+                self.0.is_odd()
+            }
+        }
+
+        impl FromUniformBytes<64> for $name {
+            fn from_uniform_bytes(bytes: &[u8; 64]) -> Self {
+                $name(<$inner>::from_uniform_bytes(bytes))
+            }
+        }
+
+        impl WithSmallOrderMulGroup<3> for $name {
+            const ZETA: Self = $name(<$inner>::ZETA);
+        }
+    };
+}
+
+field!(Fp, pasta::Fp);
+field!(Fq, pasta::Fq);
+
+macro_rules! curve {
+    ($curve:ident, $name:literal, $scalar:ident) => {
+        #[doc = concat!("The ", $name, " curve, with the scalar field [`", stringify!($scalar), "`](super::", stringify!($scalar), ").")]
+        pub mod $curve {
+            use super::*;
+
+            use halo2_proofs::halo2curves::{
+                group::{
+                    prime::{PrimeCurve, PrimeCurveAffine, PrimeGroup},
+                    Curve, Group, GroupEncoding,
+                },
+                Coordinates, CurveAffine, CurveExt,
+            };
+
+            use pasta::$curve as inner;
+
+            pub type Base = inner::Base;
+            pub type Scalar = super::$scalar;
+
+            #[doc = concat!("A point of ", $name, " in projective coordinates.")]
+            #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+            pub struct Point(pub inner::Point);
+
+            #[doc = concat!("A point of ", $name, " in affine coordinates.")]
+            #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+            pub struct Affine(pub inner::Affine);
+
+            impl_wrapper!(Point, inner::Point);
+            impl_wrapper!(Affine, inner::Affine);
+            impl_binop!(Point, Point, Point, Add, add, AddAssign, add_assign);
+            impl_binop!(Point, Point, Point, Sub, sub, SubAssign, sub_assign);
+            impl_binop!(Point, Affine, Point, Add, add, AddAssign, add_assign);
+            impl_binop!(Point, Affine, Point, Sub, sub, SubAssign, sub_assign);
+            impl_binop!(Point, Scalar, Point, Mul, mul, MulAssign, mul_assign);
+
+            impl Add for Affine {
+                type Output = Point;
+
+                fn add(self, rhs: Affine) -> Point {
+                    Point(self.0 + rhs.0)
+                }
+            }
+
+            impl Sub for Affine {
+                type Output = Point;
+
+                fn sub(self, rhs: Affine) -> Point {
+                    Point(self.0 - rhs.0)
+                }
+            }
+
+            impl Mul<Scalar> for Affine {
+                type Output = Point;
+
+                fn mul(self, rhs: Scalar) -> Point {
+                    Point(self.0 * rhs.0)
+                }
+            }
+
+            impl Mul<&Scalar> for Affine {
+                type Output = Point;
+
+                fn mul(self, rhs: &Scalar) -> Point {
+                    Point(self.0 * rhs.0)
+                }
+            }
+
+            impl From<Affine> for Point {
+                fn from(p: Affine) -> Point {
+                    Point(p.0.into())
+                }
+            }
+
+            impl From<Point> for Affine {
+                fn from(p: Point) -> Affine {
+                    Affine(p.0.into())
+                }
+            }
+
+            impl<T: Borrow<Point>> Sum<T> for Point {
+                fn sum<I: Iterator<Item = T>>(iter: I) -> Self {
+                    iter.fold(Point::identity(), |acc, p| acc + p.borrow())
+                }
+            }
+
+            impl Group for Point {
+                type Scalar = Scalar;
+
+                fn random(rng: impl RngCore) -> Self {
+                    Point(inner::Point::random(rng))
+                }
+
+                fn identity() -> Self {
+                    Point(inner::Point::identity())
+                }
+
+                fn generator() -> Self {
+                    Point(inner::Point::generator())
+                }
+
+                fn is_identity(&self) -> Choice {
+                    self.0.is_identity()
+                }
+
+                fn double(&self) -> Self {
+                    Point(self.0.double())
+                }
+            }
+
+            impl Curve for Point {
+                type AffineRepr = Affine;
+
+                fn batch_normalize(p: &[Self], q: &mut [Affine]) {
+                    let p: Vec<_> = p.iter().map(|p| p.0).collect();
+                    let mut affine = vec![inner::Affine::default(); p.len()];
+                    inner::Point::batch_normalize(&p, &mut affine);
+                    for (q, affine) in q.iter_mut().zip(affine) {
+                        *q = Affine(affine);
+                    }
+                }
+
+                fn to_affine(&self) -> Affine {
+                    Affine(self.0.to_affine())
+                }
+            }
+
+            impl GroupEncoding for Point {
+                type Repr = <inner::Point as GroupEncoding>::Repr;
+
+                fn from_bytes(bytes: &Self::Repr) -> CtOption<Self> {
+                    inner::Point::from_bytes(bytes).map(Point)
+                }
+
+                fn from_bytes_unchecked(bytes: &Self::Repr) -> CtOption<Self> {
+                    inner::Point::from_bytes_unchecked(bytes).map(Point)
+                }
+
+                fn to_bytes(&self) -> Self::Repr {
+                    self.0.to_bytes()
+                }
+            }
+
+            impl PrimeGroup for Point {}
+
+            impl PrimeCurve for Point {
+                type Affine = Affine;
+            }
+
+            impl CurveExt for Point {
+                type ScalarExt = Scalar;
+                type Base = Base;
+                type AffineExt = Affine;
+
+                const CURVE_ID: &'static str = inner::Point::CURVE_ID;
+
+                fn endo(&self) -> Self {
+                    Point(self.0.endo())
+                }
+
+                fn jacobian_coordinates(&self) -> (Base, Base, Base) {
+                    self.0.jacobian_coordinates()
+                }
+
+                fn hash_to_curve<'a>(domain_prefix: &'a str) -> Box<dyn Fn(&[u8]) -> Self + 'a> {
+                    let hasher = inner::Point::hash_to_curve(domain_prefix);
+                    Box::new(move |message| Point(hasher(message)))
+                }
+
+                fn is_on_curve(&self) -> Choice {
+                    self.0.is_on_curve()
+                }
+
+                fn a() -> Base {
+                    inner::Point::a()
+                }
+
+                fn b() -> Base {
+                    inner::Point::b()
+                }
+
+                fn new_jacobian(x: Base, y: Base, z: Base) -> CtOption<Self> {
+                    inner::Point::new_jacobian(x, y, z).map(Point)
+                }
+            }
+
+            impl GroupEncoding for Affine {
+                type Repr = <inner::Affine as GroupEncoding>::Repr;
+
+                fn from_bytes(bytes: &Self::Repr) -> CtOption<Self> {
+                    inner::Affine::from_bytes(bytes).map(Affine)
+                }
+
+                fn from_bytes_unchecked(bytes: &Self::Repr) -> CtOption<Self> {
+                    inner::Affine::from_bytes_unchecked(bytes).map(Affine)
+                }
+
+                fn to_bytes(&self) -> Self::Repr {
+                    self.0.to_bytes()
+                }
+            }
+
+            impl PrimeCurveAffine for Affine {
+                type Scalar = Scalar;
+                type Curve = Point;
+
+                fn identity() -> Self {
+                    Affine(inner::Affine::identity())
+                }
+
+                fn generator() -> Self {
+                    Affine(inner::Affine::generator())
+                }
+
+                fn is_identity(&self) -> Choice {
+                    self.0.is_identity()
+                }
+
+                fn to_curve(&self) -> Point {
+                    Point(self.0.to_curve())
+                }
+            }
+
+            impl CurveAffine for Affine {
+                type ScalarExt = Scalar;
+                type Base = Base;
+                type CurveExt = Point;
+
+                fn coordinates(&self) -> CtOption<Coordinates<Self>> {
+                    self.0
+                        .coordinates()
+                        .and_then(|c| Coordinates::from_xy(*c.x(), *c.y()))
+                }
+
+                fn from_xy(x: Base, y: Base) -> CtOption<Self> {
+                    inner::Affine::from_xy(x, y).map(Affine)
+                }
+
+                fn is_on_curve(&self) -> Choice {
+                    self.0.is_on_curve()
+                }
+
+                fn a() -> Base {
+                    inner::Affine::a()
+                }
+
+                fn b() -> Base {
+                    inner::Affine::b()
+                }
+            }
+        }
+    };
+}
+
+curve!(vesta, "Vesta", Fp);
+curve!(pallas, "Pallas", Fq);
@@ -0,0 +1,298 @@
+//! Pedersen commitments to `u64` values over Pallas.
+//!
+//! The commitment to `v` with the blinding `r` is `C = [v]G + [r]H`. It hides
+//! `v` because `[r]H` is a uniformly random point, and binds it because
+//! opening `C` to another value would reveal the discrete logarithm of `H`
+//! with respect to `G`, which nobody knows: `H` is hashed to the curve.
+//! Commitments are homomorphic, `C(v, r) + C(w, s) = C(v + w, r + s)`, so sums
+//! of hidden balances or votes can be checked on the commitments alone.
+//!
+//! [`PedersenChip`] computes `C` with one fixed-base multiplication per
+//! generator, sharing the accumulator, and the value recombined from its
+//! [`VALUE_BITS`] bits is range checked as a side effect.
+
+use group::ff::PrimeField;
+use group::Curve;
+use halo2_proofs::arithmetic::{CurveAffine, CurveExt, Field};
+use halo2_proofs::circuit::{Chip, Layouter, Value};
+use halo2_proofs::halo2curves::pasta::pallas;
+use halo2_proofs::halo2curves::Coordinates;
+use halo2_proofs::plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Fixed};
+use rand_core::RngCore;
+use std::ops::Add;
+
+use crate::cell::AssignedCell;
+use crate::ecc::{scalar_bits, EccChip, EccConfig, EccInstructions, EccPoint};
+use crate::instance::{InstanceColumns, InstanceLayout, NamedInstances};
+use crate::pasta::Fp;
+use crate::planner::SimpleFloorPlanner;
+
+/// The bits of a committed value.
+pub const VALUE_BITS: usize = 64;
+
+/// `G` and `H`, both hashed to the curve so that neither is a known multiple
+/// of the other.
+pub fn generators() -> [pallas::Affine; 2] {
+    let hasher = pallas::Point::hash_to_curve("zkG:pedersen");
+    [hasher(b"G"), hasher(b"H")].map(|point| point.to_affine())
+}
+
+/// `[v]G + [r]H`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Commitment(pub pallas::Affine);
+
+impl Commitment {
+    /// Commits to `value` with a random blinding, and returns both.
+    pub fn new(value: u64, rng: impl RngCore) -> (Self, pallas::Scalar) {
+        let blinding = pallas::Scalar::random(rng);
+        (Self::commit(value, &blinding), blinding)
+    }
+
+    pub fn commit(value: u64, blinding: &pallas::Scalar) -> Self {
+        let [g, h] = generators();
+        Self((g * pallas::Scalar::from(value) + h * blinding).to_affine())
+    }
+
+    /// Whether this commits to `value` with `blinding`.
+    pub fn opens(&self, value: u64, blinding: &pallas::Scalar) -> bool {
+        *self == Self::commit(value, blinding)
+    }
+}
+
+/// The commitment to the sum of the values, with the sum of the blindings.
+impl Add for Commitment {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self((self.0 + other.0).to_affine())
+    }
+}
+
+/// Pedersen commitments to private values.
+pub trait PedersenInstructions: Chip<Fp> {
+    /// `[value]G + [blinding]H`, and `value` as a cell constrained to
+    /// [`VALUE_BITS`] bits. The commitment must not be the identity, which an
+    /// honest prover only runs into with negligible probability.
+    fn commit(
+        &self,
+        layouter: impl Layouter<Fp>,
+        value: Value<u64>,
+        blinding: Value<pallas::Scalar>,
+    ) -> Result<(EccPoint, AssignedCell<Fp>), Error>;
+}
+
+/// Commits to private values in a circuit.
+pub struct PedersenChip {
+    ecc: EccChip,
+}
+
+impl Chip<Fp> for PedersenChip {
+    type Config = EccConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        self.ecc.config()
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl PedersenChip {
+    pub fn construct(config: EccConfig) -> Self {
+        Self {
+            ecc: EccChip::construct(config),
+        }
+    }
+
+    /// Takes the columns of [`EccChip::configure`]; the commitments need no
+    /// gates of their own.
+    pub fn configure(
+        meta: &mut ConstraintSystem<Fp>,
+        advice: [Column<Advice>; 11],
+        constants: Column<Fixed>,
+    ) -> EccConfig {
+        EccChip::configure(meta, advice, constants)
+    }
+
+    pub fn ecc(&self) -> &EccChip {
+        &self.ecc
+    }
+}
+
+impl PedersenInstructions for PedersenChip {
+    fn commit(
+        &self,
+        layouter: impl Layouter<Fp>,
+        value: Value<u64>,
+        blinding: Value<pallas::Scalar>,
+    ) -> Result<(EccPoint, AssignedCell<Fp>), Error> {
+        let value_bits: Vec<Value<bool>> = (0..VALUE_BITS)
+            .rev()
+            .map(|i| value.map(|v| (v >> i) & 1 == 1))
+            .collect();
+        let blinding_bits = blinding
+            .map(|r| scalar_bits(&r))
+            .transpose_vec(pallas::Scalar::NUM_BITS as usize);
+
+        let [g, h] = generators();
+        let (commitment, mut scalars) = self
+            .ecc
+            .mul_fixed_sum(layouter, &[(g, &value_bits), (h, &blinding_bits)])?;
+        Ok((commitment, scalars.remove(0)))
+    }
+}
+
+const COMMITMENT_NAMES: [&str; 2] = ["c_x", "c_y"];
+
+/// Proves knowledge of an opening of a public commitment.
+#[derive(Clone, Debug, Default)]
+pub struct PedersenOpeningCircuit {
+    pub value: Value<u64>,
+    pub blinding: Value<pallas::Scalar>,
+}
+
+impl PedersenOpeningCircuit {
+    /// The public inputs: the commitment's coordinates.
+    pub const LAYOUT: InstanceLayout = InstanceLayout::new(&[&COMMITMENT_NAMES]);
+
+    pub fn new(value: u64, blinding: pallas::Scalar) -> Self {
+        Self {
+            value: Value::known(value),
+            blinding: Value::known(blinding),
+        }
+    }
+
+    pub fn instances(commitment: &Commitment) -> Vec<Vec<Fp>> {
+        let coordinates: Option<Coordinates<_>> = commitment.0.coordinates().into();
+        let c = coordinates.expect("a commitment is not the identity");
+        Self::LAYOUT
+            .assemble(&[("c_x", Fp(*c.x())), ("c_y", Fp(*c.y()))])
+            .unwrap()
+    }
+}
+
+impl NamedInstances for PedersenOpeningCircuit {
+    fn instance_layout() -> InstanceLayout {
+        Self::LAYOUT
+    }
+}
+
+impl Circuit<Fp> for PedersenOpeningCircuit {
+    type Config = (EccConfig, InstanceColumns);
+    type FloorPlanner = SimpleFloorPlanner;
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let advice = [(); 11].map(|_| meta.advice_column());
+        let constants = meta.fixed_column();
+        let instance = InstanceColumns::configure(meta, Self::LAYOUT);
+
+        (PedersenChip::configure(meta, advice, constants), instance)
+    }
+
+    fn synthesize(
+        &self,
+        (config, instance): Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let chip = PedersenChip::construct(config);
+        let (commitment, _) =
+            chip.commit(layouter.namespace(|| "commit"), self.value, self.blinding)?;
+        instance.expose(layouter.namespace(|| "c_x"), commitment.x.cell(), "c_x")?;
+        instance.expose(layouter.namespace(|| "c_y"), commitment.y.cell(), "c_y")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::dev::MockProver;
+    use rand_core::OsRng;
+
+    use crate::pasta::vesta;
+    use crate::proof::{self, keygen, verify, Ipa};
+
+    use super::*;
+
+    const K: u32 = 9;
+
+    fn prove(circuit: &PedersenOpeningCircuit, commitment: &Commitment) -> Result<(), ()> {
+        let instances = PedersenOpeningCircuit::instances(commitment);
+        MockProver::run(K, circuit, instances)
+            .unwrap()
+            .verify()
+            .map_err(|_| ())
+    }
+
+    #[test]
+    fn native_test() {
+        let (commitment, r) = Commitment::new(42, OsRng);
+        assert!(commitment.opens(42, &r));
+        assert!(!commitment.opens(43, &r));
+        assert!(!commitment.opens(42, &(r + pallas::Scalar::ONE)));
+
+        // the same value with another blinding
+        let (other, s) = Commitment::new(42, OsRng);
+        assert_ne!(commitment, other);
+
+        // homomorphic
+        assert_eq!(
+            commitment + Commitment::commit(8, &s),
+            Commitment::commit(50, &(r + s))
+        );
+
+        let [g, h] = generators();
+        assert_ne!(g, h);
+    }
+
+    #[test]
+    fn opening_test() {
+        for value in [0, 1, 42, u64::MAX] {
+            let (commitment, r) = Commitment::new(value, OsRng);
+            let circuit = PedersenOpeningCircuit::new(value, r);
+            assert_eq!(prove(&circuit, &commitment), Ok(()));
+        }
+    }
+
+    #[test]
+    fn wrong_opening_test() {
+        let (commitment, r) = Commitment::new(42, OsRng);
+
+        // another value
+        let circuit = PedersenOpeningCircuit::new(43, r);
+        assert!(prove(&circuit, &commitment).is_err());
+
+        // another blinding
+        let circuit = PedersenOpeningCircuit::new(42, r + pallas::Scalar::ONE);
+        assert!(prove(&circuit, &commitment).is_err());
+
+        // the opening of another commitment
+        let (other, s) = Commitment::new(42, OsRng);
+        let circuit = PedersenOpeningCircuit::new(42, s);
+        assert_eq!(prove(&circuit, &other), Ok(()));
+        assert!(prove(&circuit, &commitment).is_err());
+    }
+
+    #[test]
+    fn ipa_proof_test() {
+        let (commitment, r) = Commitment::new(42, OsRng);
+        let circuit = PedersenOpeningCircuit::new(42, r);
+        let instances = PedersenOpeningCircuit::instances(&commitment);
+        let instances: Vec<&[Fp]> = instances.iter().map(Vec::as_slice).collect();
+
+        let (params, pk) = keygen::<Ipa<vesta::Affine>, _>(&circuit, Some(K)).unwrap();
+        let proof =
+            proof::prove::<Ipa<vesta::Affine>, _>(&params, &pk, circuit, &instances).unwrap();
+        assert!(verify::<Ipa<vesta::Affine>>(&params, pk.get_vk(), &proof, &instances).is_ok());
+
+        let (other, _) = Commitment::new(42, OsRng);
+        let other = PedersenOpeningCircuit::instances(&other);
+        let other: Vec<&[Fp]> = other.iter().map(Vec::as_slice).collect();
+        assert!(verify::<Ipa<vesta::Affine>>(&params, pk.get_vk(), &proof, &other).is_err());
+    }
+}
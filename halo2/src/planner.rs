@@ -0,0 +1,771 @@
+//! Floor planners chosen per run instead of per circuit.
+//!
+//! Every circuit names its planner in `Circuit::FloorPlanner`. [`Planned`] wraps
+//! a circuit to lay it out with another planner, [`rows_used`] measures how
+//! many rows a planner needs for it, and [`min_k`] turns that into the smallest
+//! `k` a circuit can be proven with. [`SimpleFloorPlanner`] starts each region
+//! below the last region sharing a column with it; [`V1`] also fills the gaps
+//! that leaves.
+//!
+//! halo2-axiom's own `SimpleFloorPlanner` starts every region at row 0 and
+//! leaves the offsets to the circuit, and it has no `V1`. The planners here
+//! measure every region in a first synthesis without a witness, and then run
+//! the real synthesis through it with each region moved to its planned rows.
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::{
+        self,
+        layouter::{RegionColumn, RegionLayouter, SyncDeps},
+        AssignedCell, Cell, Layouter, Region, Table, Value,
+    },
+    plonk::{
+        Advice, Any, Assigned, Assignment, Challenge, Circuit, Column, ConstraintSystem, Error,
+        Fixed, FloorPlanner, Instance, Selector,
+    },
+};
+
+use crate::instance::{InstanceLayout, NamedInstances};
+
+/// Lays out each region at the first row below every region before it that
+/// shares a column with it.
+#[derive(Debug)]
+pub struct SimpleFloorPlanner;
+
+impl FloorPlanner for SimpleFloorPlanner {
+    fn synthesize<F: Field, CS: Assignment<F> + SyncDeps, C: Circuit<F>>(
+        cs: &mut CS,
+        circuit: &C,
+        config: C::Config,
+        constants: Vec<Column<Fixed>>,
+    ) -> Result<(), Error> {
+        synthesize_planned(cs, circuit, config, constants, |shapes| {
+            let mut next_row = HashMap::new();
+            shapes
+                .iter()
+                .map(|shape| {
+                    let start = shape
+                        .columns
+                        .iter()
+                        .map(|column| next_row.get(column).copied().unwrap_or(0))
+                        .max()
+                        .unwrap_or(0);
+                    for column in &shape.columns {
+                        next_row.insert(*column, start + shape.rows);
+                    }
+                    start
+                })
+                .collect()
+        })
+    }
+}
+
+/// Lays out the regions with the most advice cells first, each at the first
+/// row where all its columns are free, so smaller regions fill the gaps.
+#[derive(Debug)]
+pub struct V1;
+
+impl FloorPlanner for V1 {
+    fn synthesize<F: Field, CS: Assignment<F> + SyncDeps, C: Circuit<F>>(
+        cs: &mut CS,
+        circuit: &C,
+        config: C::Config,
+        constants: Vec<Column<Fixed>>,
+    ) -> Result<(), Error> {
+        synthesize_planned(cs, circuit, config, constants, |shapes| {
+            let advice_area = |shape: &RegionShape| {
+                let advice = shape
+                    .columns
+                    .iter()
+                    .filter(|column| {
+                        matches!(column, RegionColumn::Column(c) if matches!(c.column_type(), Any::Advice(_)))
+                    })
+                    .count();
+                advice * shape.rows
+            };
+            let mut order: Vec<usize> = (0..shapes.len()).collect();
+            order.sort_by_key(|&i| std::cmp::Reverse(advice_area(&shapes[i])));
+
+            // the rows taken in every column, as `start..end` ranges
+            let mut taken: HashMap<RegionColumn, Vec<(usize, usize)>> = HashMap::new();
+            let mut starts = vec![0; shapes.len()];
+            for i in order {
+                let shape = &shapes[i];
+                let fits = |start: usize| {
+                    shape.columns.iter().all(|column| {
+                        taken.get(column).is_none_or(|ranges| {
+                            ranges
+                                .iter()
+                                .all(|&(s, e)| start + shape.rows <= s || e <= start)
+                        })
+                    })
+                };
+                // a region starts at row 0 or right below another one
+                let mut candidates: Vec<usize> = shape
+                    .columns
+                    .iter()
+                    .filter_map(|column| taken.get(column))
+                    .flatten()
+                    .map(|&(_, end)| end)
+                    .chain([0])
+                    .collect();
+                candidates.sort_unstable();
+                let start = candidates.into_iter().find(|&c| fits(c)).unwrap();
+
+                for column in &shape.columns {
+                    taken
+                        .entry(*column)
+                        .or_default()
+                        .push((start, start + shape.rows));
+                }
+                starts[i] = start;
+            }
+            starts
+        })
+    }
+}
+
+/// The columns a region uses and its height.
+#[derive(Debug, Default)]
+struct RegionShape {
+    columns: HashSet<RegionColumn>,
+    rows: usize,
+}
+
+/// Synthesizes `circuit` once without a witness to measure its regions, and
+/// again into `cs` with every region at the row `plan` picks for it.
+fn synthesize_planned<F: Field, CS: Assignment<F> + SyncDeps, C: Circuit<F>>(
+    cs: &mut CS,
+    circuit: &C,
+    config: C::Config,
+    constants: Vec<Column<Fixed>>,
+    plan: impl FnOnce(&[RegionShape]) -> Vec<usize>,
+) -> Result<(), Error> {
+    let measured = Shifted::new(circuit, None);
+    circuit::SimpleFloorPlanner::synthesize(
+        &mut RowCounter::default(),
+        &measured,
+        config.clone(),
+        constants.clone(),
+    )?;
+    let starts = plan(&measured.shapes.into_inner());
+
+    let shifted = Shifted::new(circuit, Some(starts));
+    circuit::SimpleFloorPlanner::synthesize(cs, &shifted, config, constants)
+}
+
+/// `circuit` with its regions moved to `starts`, or all at row 0 when measuring.
+struct Shifted<'c, C> {
+    circuit: &'c C,
+    starts: Option<Vec<usize>>,
+    shapes: RefCell<Vec<RegionShape>>,
+}
+
+impl<'c, C> Shifted<'c, C> {
+    fn new(circuit: &'c C, starts: Option<Vec<usize>>) -> Self {
+        Self {
+            circuit,
+            starts,
+            shapes: RefCell::new(vec![]),
+        }
+    }
+}
+
+// Only ever synthesized by `synthesize_planned`, with the configuration of
+// the circuit it wraps.
+impl<'c, F: Field, C: Circuit<F>> Circuit<F> for Shifted<'c, C> {
+    type Config = C::Config;
+    type FloorPlanner = circuit::SimpleFloorPlanner;
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        unreachable!("a shifted circuit is only synthesized")
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        C::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+        let layouter = ShiftedLayouter {
+            inner: layouter,
+            starts: self.starts.as_deref(),
+            shapes: &mut self.shapes.borrow_mut(),
+            _marker: PhantomData,
+        };
+        self.circuit.synthesize(config, layouter)
+    }
+}
+
+struct ShiftedLayouter<'a, F, L> {
+    inner: L,
+    starts: Option<&'a [usize]>,
+    shapes: &'a mut Vec<RegionShape>,
+    _marker: PhantomData<F>,
+}
+
+impl<'a, F: Field, L: Layouter<F>> Layouter<F> for ShiftedLayouter<'a, F, L> {
+    type Root = Self;
+
+    fn assign_region<A, AR, N, NR>(&mut self, name: N, assignment: A) -> Result<AR, Error>
+    where
+        A: FnOnce(Region<'_, F>) -> Result<AR, Error>,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        let start = self.starts.map_or(0, |starts| starts[self.shapes.len()]);
+        self.shapes.push(RegionShape::default());
+        let shape = self.shapes.last_mut().unwrap();
+
+        self.inner.assign_region(name, |inner| {
+            let mut region = ShiftedRegion {
+                inner,
+                start,
+                shape,
+            };
+            let region: &mut dyn RegionLayouter<F> = &mut region;
+            assignment(region.into())
+        })
+    }
+
+    fn assign_table<A, N, NR>(&mut self, name: N, assignment: A) -> Result<(), Error>
+    where
+        A: FnMut(Table<'_, F>) -> Result<(), Error>,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        self.inner.assign_table(name, assignment)
+    }
+
+    fn constrain_instance(&mut self, cell: Cell, column: Column<Instance>, row: usize) {
+        self.inner.constrain_instance(cell, column, row);
+    }
+
+    fn next_phase(&mut self) {
+        self.inner.next_phase()
+    }
+
+    fn get_challenge(&self, challenge: Challenge) -> Value<F> {
+        self.inner.get_challenge(challenge)
+    }
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.inner.get_root().push_namespace(name_fn)
+    }
+
+    fn pop_namespace(&mut self, gadget_name: Option<String>) {
+        self.inner.get_root().pop_namespace(gadget_name)
+    }
+}
+
+/// A region of the inner layouter, which starts at row 0, with every offset
+/// moved down by `start`. The cells it returns hold absolute rows, so copies
+/// between regions need no further translation.
+struct ShiftedRegion<'r, 's, F: Field> {
+    inner: Region<'r, F>,
+    start: usize,
+    shape: &'s mut RegionShape,
+}
+
+impl<'r, 's, F: Field> ShiftedRegion<'r, 's, F> {
+    /// Records `offset` of `column` in the region's shape and returns its row.
+    fn row(&mut self, column: RegionColumn, offset: usize) -> usize {
+        self.shape.columns.insert(column);
+        self.shape.rows = self.shape.rows.max(offset + 1);
+        self.start + offset
+    }
+}
+
+impl<'r, 's, F: Field> fmt::Debug for ShiftedRegion<'r, 's, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShiftedRegion")
+            .field("start", &self.start)
+            .field("shape", &self.shape)
+            .finish()
+    }
+}
+
+impl<'r, 's, F: Field> RegionLayouter<F> for ShiftedRegion<'r, 's, F> {
+    fn enable_selector<'v>(
+        &'v mut self,
+        _: &'v (dyn Fn() -> String + 'v),
+        selector: &Selector,
+        offset: usize,
+    ) -> Result<(), Error> {
+        let row = self.row((*selector).into(), offset);
+        selector.enable(&mut self.inner, row)
+    }
+
+    fn name_column<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Any>,
+    ) {
+        self.inner.name_column(annotation, column)
+    }
+
+    fn assign_advice<'v>(
+        &mut self,
+        column: Column<Advice>,
+        offset: usize,
+        to: Value<Assigned<F>>,
+    ) -> AssignedCell<&'v Assigned<F>, F> {
+        let row = self.row(Column::<Any>::from(column).into(), offset);
+        self.inner.assign_advice(column, row, to)
+    }
+
+    fn assign_advice_from_constant<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Advice>,
+        offset: usize,
+        constant: Assigned<F>,
+    ) -> Result<Cell, Error> {
+        let row = self.row(Column::<Any>::from(column).into(), offset);
+        self.inner
+            .assign_advice_from_constant(annotation, column, row, constant)
+            .map(|cell| cell.cell())
+    }
+
+    fn assign_advice_from_instance<'v>(
+        &mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        instance: Column<Instance>,
+        row: usize,
+        advice: Column<Advice>,
+        offset: usize,
+    ) -> Result<(Cell, Value<F>), Error> {
+        let offset = self.row(Column::<Any>::from(advice).into(), offset);
+        self.inner
+            .assign_advice_from_instance(annotation, instance, row, advice, offset)
+            .map(|cell| (cell.cell(), cell.value().copied()))
+    }
+
+    fn instance_value(
+        &mut self,
+        instance: Column<Instance>,
+        row: usize,
+    ) -> Result<Value<F>, Error> {
+        self.inner.instance_value(instance, row)
+    }
+
+    fn assign_fixed(&mut self, column: Column<Fixed>, offset: usize, to: Assigned<F>) -> Cell {
+        let row = self.row(Column::<Any>::from(column).into(), offset);
+        self.inner.assign_fixed(column, row, to)
+    }
+
+    fn constrain_constant(&mut self, cell: Cell, constant: Assigned<F>) -> Result<(), Error> {
+        self.inner.constrain_constant(cell, constant)
+    }
+
+    fn constrain_equal(&mut self, left: Cell, right: Cell) {
+        self.inner.constrain_equal(left, right)
+    }
+
+    fn get_challenge(&self, challenge: Challenge) -> Value<F> {
+        self.inner.get_challenge(challenge)
+    }
+
+    fn next_phase(&mut self) {
+        self.inner.next_phase()
+    }
+}
+
+/// `C` laid out with the floor planner `P`.
+pub struct Planned<C, P: FloorPlanner> {
+    pub circuit: C,
+    _planner: PhantomData<P>,
+}
+
+impl<C, P: FloorPlanner> Planned<C, P> {
+    pub fn new(circuit: C) -> Self {
+        Self {
+            circuit,
+            _planner: PhantomData,
+        }
+    }
+}
+
+impl<F: Field, C: Circuit<F>, P: FloorPlanner> Circuit<F> for Planned<C, P> {
+    type Config = C::Config;
+    type FloorPlanner = P;
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(self.circuit.without_witnesses())
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        C::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+        self.circuit.synthesize(config, layouter)
+    }
+}
+
+impl<C: NamedInstances, P: FloorPlanner> NamedInstances for Planned<C, P> {
+    fn instance_layout() -> InstanceLayout {
+        C::instance_layout()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Planner {
+    Simple,
+    V1,
+}
+
+impl Planner {
+    pub const ALL: [Planner; 2] = [Planner::Simple, Planner::V1];
+
+    /// The number of rows `circuit` uses when laid out with this planner.
+    pub fn rows_used<F: Field, C: Circuit<F>>(&self, circuit: &C) -> Result<usize, Error> {
+        match self {
+            Planner::Simple => rows_used::<F, SimpleFloorPlanner, C>(circuit),
+            Planner::V1 => rows_used::<F, V1, C>(circuit),
+        }
+    }
+
+    /// The smallest `k` that fits `circuit` when laid out with this planner.
+    pub fn min_k<F: Field, C: Circuit<F>>(&self, circuit: &C) -> Result<u32, Error> {
+        match self {
+            Planner::Simple => min_k_with::<F, SimpleFloorPlanner, C>(circuit),
+            Planner::V1 => min_k_with::<F, V1, C>(circuit),
+        }
+    }
+}
+
+impl FromStr for Planner {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "simple" => Ok(Planner::Simple),
+            "v1" => Ok(Planner::V1),
+            _ => Err(format!(
+                "unknown floor planner `{}`, expected `simple` or `v1`",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Planner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Planner::Simple => write!(f, "simple"),
+            Planner::V1 => write!(f, "v1"),
+        }
+    }
+}
+
+/// The number of rows `circuit` uses when laid out with the planner `P`: one
+/// more than the last row any cell is assigned, any selector enabled or any
+/// public input exposed in.
+///
+/// No witness is computed, so this works on `without_witnesses()` circuits too.
+/// halo2 does not expose the columns a circuit enabled for constants, so they
+/// are laid out in a fixed column of their own, which is how circuits usually
+/// configure them.
+pub fn rows_used<F: Field, P: FloorPlanner, C: Circuit<F>>(circuit: &C) -> Result<usize, Error> {
+    Ok(count_rows::<F, P, C>(circuit)?.rows)
+}
+
+/// Like [`rows_used`], but only counts the rows holding advice cells or enabled
+/// selectors, leaving out lookup tables, which may be much longer than the
+/// circuit itself.
+pub fn advice_rows_used<F: Field, P: FloorPlanner, C: Circuit<F>>(
+    circuit: &C,
+) -> Result<usize, Error> {
+    Ok(count_rows::<F, P, C>(circuit)?.advice_rows)
+}
+
+/// The smallest `k` whose `2^k` rows fit `circuit`, laid out with its own floor
+/// planner: its regions, lookup tables and public inputs, and the rows halo2
+/// reserves at the end of every column for blinding. Like [`rows_used`], this
+/// needs no witness.
+pub fn min_k<F: Field, C: Circuit<F>>(circuit: &C) -> Result<u32, Error> {
+    min_k_with::<F, C::FloorPlanner, C>(circuit)
+}
+
+/// [`min_k`] with the planner `P` instead of the circuit's own.
+pub fn min_k_with<F: Field, P: FloorPlanner, C: Circuit<F>>(circuit: &C) -> Result<u32, Error> {
+    let rows = rows_used::<F, P, C>(circuit)?;
+
+    let mut meta = ConstraintSystem::default();
+    C::configure(&mut meta);
+    let needed = (rows + meta.blinding_factors() + 1).max(meta.minimum_rows());
+
+    Ok(needed.next_power_of_two().trailing_zeros())
+}
+
+fn count_rows<F: Field, P: FloorPlanner, C: Circuit<F>>(circuit: &C) -> Result<RowCounter, Error> {
+    let mut meta = ConstraintSystem::default();
+    let config = C::configure(&mut meta);
+    let constants = meta.fixed_column();
+
+    let mut counter = RowCounter::default();
+    P::synthesize(&mut counter, circuit, config, vec![constants])?;
+
+    Ok(counter)
+}
+
+#[derive(Default)]
+struct RowCounter {
+    rows: usize,
+    advice_rows: usize,
+}
+
+impl RowCounter {
+    fn used(&mut self, row: usize) {
+        self.rows = self.rows.max(row + 1);
+    }
+
+    fn used_by_advice(&mut self, row: usize) {
+        self.used(row);
+        self.advice_rows = self.advice_rows.max(row + 1);
+    }
+}
+
+impl<F: Field> Assignment<F> for RowCounter {
+    fn enter_region<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn annotate_column<A, AR>(&mut self, _: A, _: Column<Any>)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+    }
+
+    fn exit_region(&mut self) {}
+
+    fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, row: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.used_by_advice(row);
+        Ok(())
+    }
+
+    fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Value<F>, Error> {
+        Ok(Value::unknown())
+    }
+
+    fn assign_advice<'v>(
+        &mut self,
+        _: Column<Advice>,
+        row: usize,
+        _: Value<Assigned<F>>,
+    ) -> Value<&'v Assigned<F>> {
+        self.used_by_advice(row);
+        Value::unknown()
+    }
+
+    fn assign_fixed(&mut self, _: Column<Fixed>, row: usize, _: Assigned<F>) {
+        self.used(row);
+    }
+
+    fn copy(&mut self, _: Column<Any>, left_row: usize, _: Column<Any>, right_row: usize) {
+        // `constrain_instance` copies into the instance column, which is the
+        // only place its rows show up
+        self.used(left_row);
+        self.used(right_row);
+    }
+
+    fn fill_from_row(
+        &mut self,
+        _: Column<Fixed>,
+        _: usize,
+        _: Value<Assigned<F>>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn get_challenge(&self, _: Challenge) -> Value<F> {
+        Value::unknown()
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {}
+}
+
+#[cfg(test)]
+mod test {
+    use std::panic::{self, AssertUnwindSafe};
+
+    use halo2_proofs::{dev::MockProver, halo2curves::pasta::Fp, plonk::Advice};
+
+    use crate::backend::GenericCircuit;
+    use crate::fibo_circuit::FiboInput;
+    use crate::fibo_circuit2;
+    use crate::range_check::eg2_lookup;
+
+    use super::*;
+
+    /// Regions of the given advice columns and heights, in synthesis order.
+    const REGIONS: [(&[usize], usize); 3] = [(&[0], 3), (&[0, 1], 1), (&[1], 2)];
+
+    #[derive(Default)]
+    struct Regions;
+
+    impl Circuit<Fp> for Regions {
+        type Config = [Column<Advice>; 2];
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            [meta.advice_column(), meta.advice_column()]
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            for (columns, height) in REGIONS {
+                layouter.assign_region(
+                    || "region",
+                    |mut region| {
+                        for column in columns {
+                            for row in 0..height {
+                                region.assign_advice(config[*column], row, Value::known(Fp::ONE));
+                            }
+                        }
+                        Ok(())
+                    },
+                )?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn rows_used_test() {
+        // the simple planner starts the last region below the second, V1 slots
+        // it into the gap next to the first
+        assert_eq!(Planner::Simple.rows_used(&Regions).unwrap(), 6);
+        assert_eq!(Planner::V1.rows_used(&Regions).unwrap(), 4);
+        assert_eq!(
+            advice_rows_used::<Fp, SimpleFloorPlanner, _>(&Regions).unwrap(),
+            6
+        );
+
+        let input = FiboInput::<10> { a: 1, b: 1 };
+        let circuit = input.circuit::<Fp>();
+
+        // one row per region, and the fibo regions all use the same columns
+        assert_eq!(Planner::Simple.rows_used(&circuit).unwrap(), 8);
+        assert_eq!(Planner::V1.rows_used(&circuit).unwrap(), 8);
+
+        let circuit = fibo_circuit2::FiboInput::<10> { a: 1, b: 1 }.circuit::<Fp>();
+        assert_eq!(Planner::Simple.rows_used(&circuit).unwrap(), 10);
+    }
+
+    /// Whether `MockProver` lays out `circuit` in `2^k` rows. halo2-axiom
+    /// panics on some cells past the last usable row instead of failing.
+    fn runs<C: Circuit<Fp>>(k: u32, circuit: &C, instances: Vec<Vec<Fp>>) -> bool {
+        panic::catch_unwind(AssertUnwindSafe(|| {
+            MockProver::run(k, circuit, instances).is_ok()
+        }))
+        .unwrap_or(false)
+    }
+
+    #[test]
+    fn simple_floor_planner_test() {
+        // one region per row, all in the same columns: at row 0 each, they would
+        // overwrite each other
+        let input = FiboInput::<10> { a: 1, b: 1 };
+        MockProver::run(4, &input.circuit::<Fp>(), input.instances())
+            .unwrap()
+            .assert_satisfied();
+
+        let mut instances = input.instances::<Fp>();
+        instances[0][2] += Fp::ONE;
+        assert!(MockProver::run(4, &input.circuit::<Fp>(), instances)
+            .unwrap()
+            .verify()
+            .is_err());
+    }
+
+    #[test]
+    fn min_k_test() {
+        let input = FiboInput::<10> { a: 1, b: 1 };
+        let circuit = input.circuit::<Fp>();
+        let k = min_k(&circuit).unwrap();
+        assert_eq!(k, 4);
+        MockProver::run(k, &circuit, input.instances())
+            .unwrap()
+            .assert_satisfied();
+        assert!(!runs(k - 1, &circuit, input.instances()));
+
+        // the 256-row lookup table does not fit in 2^8 rows with the blinding rows
+        let input = eg2_lookup::RangeCheckInput::<8> { value: 5 };
+        let circuit = input.circuit::<Fp>();
+        let k = min_k(&circuit).unwrap();
+        assert_eq!(k, 9);
+        MockProver::run(k, &circuit, input.instances())
+            .unwrap()
+            .assert_satisfied();
+        assert!(!runs(k - 1, &circuit, input.instances()));
+
+        // the public inputs need rows of their own
+        let input = FiboInput::<40> { a: 1, b: 1 };
+        assert_eq!(Planner::V1.min_k(&input.circuit::<Fp>()).unwrap(), 6);
+    }
+
+    #[test]
+    fn planned_test() {
+        let input = FiboInput::<10> { a: 1, b: 1 };
+
+        let circuit = Planned::<_, V1>::new(input.circuit::<Fp>());
+        MockProver::run(4, &circuit, input.instances())
+            .unwrap()
+            .assert_satisfied();
+
+        let mut instances = input.instances::<Fp>();
+        instances[0][2] += Fp::ONE;
+        assert!(MockProver::run(4, &circuit, instances)
+            .unwrap()
+            .verify()
+            .is_err());
+    }
+
+    #[test]
+    fn parse_planner_test() {
+        for planner in Planner::ALL {
+            assert_eq!(planner.to_string().parse(), Ok(planner));
+        }
+        assert!("v2".parse::<Planner>().is_err());
+    }
+}
@@ -0,0 +1,85 @@
+use std::time::Instant;
+
+use halo2_proofs::plonk::FloorPlanner;
+use zkG::backend::GenericCircuit;
+use zkG::pasta::{vesta, Fp};
+use zkG::planner::{min_k, rows_used, Planned, Planner};
+use zkG::planner::{SimpleFloorPlanner, V1};
+use zkG::proof::{keygen, prove, verify, Ipa};
+use zkG::range_check::{eg1_simple, eg2_lookup, eg3_numbits};
+use zkG::{fibo_circuit, fibo_circuit2, fibo_circuit3};
+
+/// Lays out and proves `input` with the planner `P`, returning the rows used,
+/// the `k` they fit in and the proving time in milliseconds.
+fn measure<P: FloorPlanner, G: GenericCircuit>(input: &G) -> (usize, u32, u128) {
+    let circuit = Planned::<_, P>::new(input.circuit::<Fp>());
+    let rows = rows_used::<Fp, P, _>(&circuit).unwrap();
+    let k = min_k(&circuit).unwrap();
+
+    let instances = input.instances::<Fp>();
+    let instances: Vec<&[Fp]> = instances.iter().map(Vec::as_slice).collect();
+
+    let (params, pk) = keygen::<Ipa<vesta::Affine>, _>(&circuit, Some(k)).unwrap();
+    let start = Instant::now();
+    let proof = prove::<Ipa<vesta::Affine>, _>(&params, &pk, circuit, &instances).unwrap();
+    let millis = start.elapsed().as_millis();
+    verify::<Ipa<vesta::Affine>>(&params, pk.get_vk(), &proof, &instances).unwrap();
+
+    (rows, k, millis)
+}
+
+fn compare(name: &str, input: &impl GenericCircuit) {
+    for planner in Planner::ALL {
+        let (rows, k, millis) = match planner {
+            Planner::Simple => measure::<SimpleFloorPlanner, _>(input),
+            Planner::V1 => measure::<V1, _>(input),
+        };
+        println!(
+            "{:<28} {:<8} {:>6} {:>4} {:>10}",
+            name,
+            planner.to_string(),
+            rows,
+            k,
+            millis
+        );
+    }
+}
+
+fn main() {
+    println!(
+        "{:<28} {:<8} {:>6} {:>4} {:>10}",
+        "circuit", "planner", "rows", "k", "prove (ms)"
+    );
+
+    compare(
+        "fibo_circuit (N=10)",
+        &fibo_circuit::FiboInput::<10> { a: 1, b: 1 },
+    );
+    compare(
+        "fibo_circuit (N=40)",
+        &fibo_circuit::FiboInput::<40> { a: 1, b: 1 },
+    );
+    compare(
+        "fibo_circuit2 (N=40)",
+        &fibo_circuit2::FiboInput::<40> { a: 1, b: 1 },
+    );
+    compare(
+        "fibo_circuit3 (N=64, W=4)",
+        &fibo_circuit3::FiboInput::<64, 4> { a: 1, b: 1 },
+    );
+    compare(
+        "eg1_simple (RANGE=8)",
+        &eg1_simple::RangeCheckInput::<8> { value: 5 },
+    );
+    compare(
+        "eg2_lookup (RANGE=8)",
+        &eg2_lookup::RangeCheckInput::<8> { value: 5 },
+    );
+    compare(
+        "eg3_numbits",
+        &eg3_numbits::RangeCheckInput {
+            value: 5,
+            num_bits: 3,
+        },
+    );
+}
@@ -0,0 +1,400 @@
+//! `x^e` for exponents fixed when the circuit is written and for private ones.
+//!
+//! A fixed exponent is computed along its binary addition chain: `x`, then
+//! for every bit after the highest one a squaring, followed by a
+//! multiplication by `x` if the bit is set. Each link is a row of the `mul`
+//! gate, `a·b = c`, with `a` and `b` copied from earlier links, so `x^e` takes
+//! `floor(log2 e) + popcount(e) - 1` rows.
+//!
+//! A private exponent is square-and-multiply over its bits, highest first,
+//! one row per bit:
+//!
+//! ```text
+//!  acc     | bit | x | e
+//!  1         b_0   x   0         q_step: acc' = acc²·(1 + b·(x - 1)),
+//!  x^e_1     b_1   x   e_1               e' = 2e + b, b boolean
+//!  ...
+//!  x^e                 e
+//! ```
+//!
+//! where `e_i` is the exponent's `i` highest bits. `e` in the last row is
+//! copied from the exponent's cell, which so has at most the given number of
+//! bits.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{Chip, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+
+use crate::cell::{AssignedCell, RegionExt};
+use crate::field::FieldExt;
+
+/// Exponentiation by fixed and by private exponents.
+pub trait PowInstructions<F: FieldExt>: Chip<F> {
+    /// Assigns a private value.
+    fn witness(
+        &self,
+        layouter: impl Layouter<F>,
+        value: Value<F>,
+    ) -> Result<AssignedCell<F>, Error>;
+
+    /// `x^exponent`, for an exponent given as little-endian 64-bit limbs like
+    /// `Field::pow_vartime`'s.
+    fn pow_fixed<S: AsRef<[u64]>>(
+        &self,
+        layouter: impl Layouter<F>,
+        x: &AssignedCell<F>,
+        exponent: S,
+    ) -> Result<AssignedCell<F>, Error>;
+
+    /// `x^exponent` for a private exponent of at most `num_bits` bits, which
+    /// the circuit enforces.
+    fn pow(
+        &self,
+        layouter: impl Layouter<F>,
+        x: &AssignedCell<F>,
+        exponent: &AssignedCell<F>,
+        num_bits: usize,
+    ) -> Result<AssignedCell<F>, Error>;
+}
+
+#[derive(Clone, Debug)]
+pub struct PowConfig<F: FieldExt> {
+    /// `a, b, c` of a multiplication, or `acc, bit, x, e` of a step
+    advice: [Column<Advice>; 4],
+    q_mul: Selector,
+    q_step: Selector,
+    _marker: PhantomData<F>,
+}
+
+pub struct PowChip<F: FieldExt> {
+    config: PowConfig<F>,
+}
+
+impl<F: FieldExt> Chip<F> for PowChip<F> {
+    type Config = PowConfig<F>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> PowChip<F> {
+    pub fn construct(config: PowConfig<F>) -> Self {
+        Self { config }
+    }
+
+    /// `constants` must be a fixed column, which holds the ones and zeros that
+    /// start the square-and-multiply.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 4],
+        constants: Column<Fixed>,
+    ) -> PowConfig<F> {
+        let q_mul = meta.selector();
+        let q_step = meta.selector();
+        for column in advice {
+            meta.enable_equality(column);
+        }
+        meta.enable_constant(constants);
+
+        meta.create_gate("mul", |meta| {
+            let q_mul = meta.query_selector(q_mul);
+            let [a, b, c] = [0, 1, 2].map(|i| meta.query_advice(advice[i], Rotation::cur()));
+            vec![q_mul * (a * b - c)]
+        });
+
+        meta.create_gate("square and multiply", |meta| {
+            let q_step = meta.query_selector(q_step);
+            let [acc, bit, x, e] = advice.map(|column| meta.query_advice(column, Rotation::cur()));
+            let next_acc = meta.query_advice(advice[0], Rotation::next());
+            let next_e = meta.query_advice(advice[3], Rotation::next());
+            let one = Expression::Constant(F::ONE);
+
+            vec![
+                q_step.clone() * bit.clone() * (one.clone() - bit.clone()),
+                q_step.clone()
+                    * (next_acc - acc.clone() * acc * (one.clone() + bit.clone() * (x - one))),
+                q_step * (next_e - (e * F::from(2) + bit)),
+            ]
+        });
+
+        PowConfig {
+            advice,
+            q_mul,
+            q_step,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: FieldExt> PowInstructions<F> for PowChip<F> {
+    fn witness(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Value<F>,
+    ) -> Result<AssignedCell<F>, Error> {
+        layouter.assign_region(
+            || "witness",
+            |mut region| Ok(region.assign(self.config.advice[0], 0, value)),
+        )
+    }
+
+    fn pow_fixed<S: AsRef<[u64]>>(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: &AssignedCell<F>,
+        exponent: S,
+    ) -> Result<AssignedCell<F>, Error> {
+        let chain = match addition_chain(exponent.as_ref()) {
+            Some(chain) => chain,
+            None => {
+                return layouter.assign_region(
+                    || "x^0",
+                    |mut region| region.assign_constant(self.config.advice[2], 0, F::ONE),
+                )
+            }
+        };
+        if chain.is_empty() {
+            return Ok(x.clone());
+        }
+
+        let [a, b, c, _] = self.config.advice;
+        layouter.assign_region(
+            || "addition chain",
+            |mut region| {
+                let mut powers = vec![x.clone()];
+                for (row, (i, j)) in chain.iter().enumerate() {
+                    self.config.q_mul.enable(&mut region, row)?;
+                    let left = powers[*i].copy_to(&mut region, a, row);
+                    let right = powers[*j].copy_to(&mut region, b, row);
+                    let product = left
+                        .field_value()
+                        .zip(right.field_value())
+                        .map(|(l, r)| l * r);
+                    powers.push(region.assign(c, row, product));
+                }
+                Ok(powers.pop().unwrap())
+            },
+        )
+    }
+
+    fn pow(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: &AssignedCell<F>,
+        exponent: &AssignedCell<F>,
+        num_bits: usize,
+    ) -> Result<AssignedCell<F>, Error> {
+        assert!(
+            num_bits < F::NUM_BITS as usize,
+            "the exponent's bits must not wrap around the modulus"
+        );
+        let [acc_column, bit_column, x_column, e_column] = self.config.advice;
+
+        // highest first
+        let bits: Value<Vec<bool>> = exponent.field_value().map(|e| {
+            let repr = e.to_repr();
+            let bytes = repr.as_ref();
+            (0..num_bits)
+                .rev()
+                .map(|i| (bytes[i / 8] >> (i % 8)) & 1 == 1)
+                .collect()
+        });
+
+        layouter.assign_region(
+            || "square and multiply",
+            |mut region| {
+                let mut acc = region.assign_constant(acc_column, 0, F::ONE)?;
+                let mut e = region.assign_constant(e_column, 0, F::ZERO)?;
+
+                for row in 0..num_bits {
+                    self.config.q_step.enable(&mut region, row)?;
+                    let bit = bits.as_ref().map(|bits| bits[row]);
+                    region.assign(bit_column, row, bit.map(|b| F::from(b as u64)));
+                    x.copy_to(&mut region, x_column, row);
+
+                    let next_acc =
+                        acc.field_value()
+                            .zip(x.field_value())
+                            .zip(bit)
+                            .map(|((acc, x), bit)| {
+                                let square = acc.square();
+                                if bit {
+                                    square * x
+                                } else {
+                                    square
+                                }
+                            });
+                    let next_e = e
+                        .field_value()
+                        .zip(bit)
+                        .map(|(e, bit)| e.double() + F::from(bit as u64));
+                    acc = region.assign(acc_column, row + 1, next_acc);
+                    e = region.assign(e_column, row + 1, next_e);
+                }
+
+                region.constrain_equal(e.cell(), exponent.cell());
+                Ok(acc)
+            },
+        )
+    }
+}
+
+/// The binary addition chain of `exponent`, as the pairs of earlier links each
+/// link adds, `x` being link 0; `None` for a zero exponent.
+fn addition_chain(exponent: &[u64]) -> Option<Vec<(usize, usize)>> {
+    let bits: Vec<bool> = exponent
+        .iter()
+        .flat_map(|limb| (0..64).map(move |i| (limb >> i) & 1 == 1))
+        .collect();
+    let highest = bits.iter().rposition(|bit| *bit)?;
+
+    let mut chain = vec![];
+    for bit in bits[..highest].iter().rev() {
+        chain.push((chain.len(), chain.len()));
+        if *bit {
+            chain.push((chain.len(), 0));
+        }
+    }
+    Some(chain)
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::{
+        arithmetic::Field,
+        dev::MockProver,
+        halo2curves::pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    use super::*;
+
+    use crate::planner::SimpleFloorPlanner;
+
+    const FIXED: [u64; 9] = [0, 1, 2, 3, 5, 16, 255, 65537, u64::MAX];
+    const NUM_BITS: usize = 16;
+
+    /// `x` to each of the fixed exponents and to the private one, exposed in
+    /// that order.
+    #[derive(Default)]
+    struct PowCircuit {
+        x: Value<Fp>,
+        exponent: Value<Fp>,
+    }
+
+    impl Circuit<Fp> for PowCircuit {
+        type Config = (PowConfig<Fp>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 4].map(|_| meta.advice_column());
+            let constants = meta.fixed_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (PowChip::configure(meta, advice, constants), instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = PowChip::construct(config);
+            let x = chip.witness(layouter.namespace(|| "x"), self.x)?;
+            let exponent = chip.witness(layouter.namespace(|| "exponent"), self.exponent)?;
+
+            for (row, e) in FIXED.iter().enumerate() {
+                let power = chip.pow_fixed(layouter.namespace(|| "fixed"), &x, [*e])?;
+                layouter.constrain_instance(power.cell(), instance, row);
+            }
+            let power = chip.pow(layouter.namespace(|| "private"), &x, &exponent, NUM_BITS)?;
+            layouter.constrain_instance(power.cell(), instance, FIXED.len());
+            Ok(())
+        }
+    }
+
+    fn prove(x: Fp, exponent: u64, powers: Vec<Fp>) -> Result<(), ()> {
+        let circuit = PowCircuit {
+            x: Value::known(x),
+            exponent: Value::known(Fp::from(exponent)),
+        };
+        MockProver::run(8, &circuit, vec![powers])
+            .unwrap()
+            .verify()
+            .map_err(|_| ())
+    }
+
+    fn powers(x: Fp, exponent: u64) -> Vec<Fp> {
+        FIXED
+            .iter()
+            .chain([exponent].iter())
+            .map(|e| x.pow_vartime([*e]))
+            .collect()
+    }
+
+    #[test]
+    fn addition_chain_test() {
+        assert_eq!(addition_chain(&[0]), None);
+        assert_eq!(addition_chain(&[1]), Some(vec![]));
+        // x, x^2, x^3
+        assert_eq!(addition_chain(&[3]), Some(vec![(0, 0), (1, 0)]));
+
+        for e in [2u64, 5, 6, 255, 1 << 40, u64::MAX] {
+            let chain = addition_chain(&[e]).unwrap();
+            let mut links = vec![1u128];
+            for (i, j) in &chain {
+                links.push(links[*i] + links[*j]);
+            }
+            assert_eq!(*links.last().unwrap(), e as u128);
+            assert_eq!(
+                chain.len() as u32,
+                63 - e.leading_zeros() + e.count_ones() - 1
+            );
+        }
+
+        // limbs above the first
+        let chain = addition_chain(&[0, 1]).unwrap();
+        assert_eq!(chain.len(), 64);
+    }
+
+    #[test]
+    fn pow_test() {
+        let x = Fp::from(7);
+        for exponent in [0, 1, 2, 3, 12345, (1 << NUM_BITS) - 1] {
+            assert_eq!(prove(x, exponent, powers(x, exponent)), Ok(()));
+        }
+        // 0^0 = 1
+        assert_eq!(prove(Fp::ZERO, 0, powers(Fp::ZERO, 0)), Ok(()));
+        assert_eq!(prove(-Fp::ONE, 3, powers(-Fp::ONE, 3)), Ok(()));
+    }
+
+    #[test]
+    fn wrong_power_test() {
+        let x = Fp::from(7);
+
+        for i in 0..=FIXED.len() {
+            let mut wrong = powers(x, 100);
+            wrong[i] += Fp::ONE;
+            assert!(prove(x, 100, wrong).is_err());
+        }
+
+        // the exponent has more bits than the circuit allows
+        let exponent = 1 << NUM_BITS;
+        assert!(prove(x, exponent, powers(x, exponent)).is_err());
+    }
+}
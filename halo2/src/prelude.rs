@@ -0,0 +1,40 @@
+//! The chips, their configurations and instruction traits, for
+//! `use zkG::prelude::*`.
+//!
+//! Every chip implements halo2's [`Chip`], with its lookup tables, if any, as
+//! the `Loaded` type. Chips are built the same way: `configure` takes the
+//! columns and returns the configuration, `construct` takes the configuration,
+//! and chips with tables have a `load_table` or `load_tables` to call once per
+//! circuit.
+
+pub use halo2_proofs::circuit::Chip;
+
+pub use crate::bitwise::{
+    BitwiseChip, BitwiseConfig, BitwiseInstructions, BitwiseOp, BitwiseTable,
+};
+pub use crate::decompose_range_check::{DecomposeChip, DecomposeConfig, DecomposeInstructions};
+pub use crate::division::{DivisionChip, DivisionConfig, DivisionError, DivisionInstructions};
+pub use crate::ecc::{EccChip, EccConfig, EccInstructions, EccPoint};
+pub use crate::fibo_circuit::FiboRowInstructions;
+pub use crate::fibo_circuit2::FiboColumnInstructions;
+pub use crate::fibo_circuit3::FiboWideInstructions;
+pub use crate::fixed_point::{
+    AssignedFixed, FixedPoint, FixedPointChip, FixedPointConfig, FixedPointInstructions,
+};
+pub use crate::horner::{Coefficient, HornerChip, HornerConfig, HornerInstructions};
+pub use crate::instance::{InstanceColumns, InstanceLayout};
+pub use crate::integer::{
+    AssignedU32, AssignedU64, AssignedU8, AssignedUint, ByteTable, IntOp, IntegerChip,
+    IntegerConfig, IntegerInstructions, Overflow, Overflowing,
+};
+pub use crate::is_zero::{IsZeroChip, IsZeroConfig, IsZeroInstructions};
+pub use crate::keccak::{KeccakChip, KeccakConfig, KeccakInstructions};
+pub use crate::matmul::{InnerProductChip, InnerProductConfig, InnerProductInstructions};
+pub use crate::pedersen::{Commitment, PedersenChip, PedersenInstructions};
+pub use crate::pow::{PowChip, PowConfig, PowInstructions};
+pub use crate::range_check::{BitLengthInstructions, RangeCheckInstructions};
+pub use crate::schnorr::{SchnorrChip, SchnorrConfig, SchnorrInstructions};
+pub use crate::sha256::{Sha256Chip, Sha256Config, Sha256Instructions, Word};
+pub use crate::sort::{SortChip, SortConfig, SortInstructions};
+pub use crate::table::{TableChip, TableConfig, TableInstructions};
+pub use crate::transfer::{TransferChip, TransferConfig, TransferInstructions};
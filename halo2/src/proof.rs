@@ -0,0 +1,306 @@
+//! Real (non-mock) proving for zkG circuits, with either commitment scheme of
+//! halo2-axiom: IPA ([`Ipa`]), over any curve whose scalar field the chips work
+//! in, or KZG over BN254 ([`Kzg`]).
+//!
+//! [`prove`] checks the instance vectors against the circuit's
+//! [`InstanceLayout`](crate::instance::InstanceLayout) first, so a missing or
+//! extra public input fails with an [`InstanceError`] naming it rather than
+//! with a proof that does not verify.
+
+use std::fmt;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::CurveAffine,
+    halo2curves::{
+        bn256::{Bn256, G1Affine},
+        ff::WithSmallOrderMulGroup,
+    },
+    plonk::{
+        create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, Error, ProvingKey, VerifyingKey,
+    },
+    poly::{
+        commitment::{Params, ParamsProver},
+        ipa::{
+            commitment::{IPACommitmentScheme, ParamsIPA},
+            multiopen::{ProverIPA, VerifierIPA},
+            strategy::SingleStrategy as IpaStrategy,
+        },
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::{ProverSHPLONK, VerifierSHPLONK},
+            strategy::SingleStrategy as KzgStrategy,
+        },
+        VerificationStrategy,
+    },
+    transcript::{
+        Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+    },
+};
+use rand::{rngs::StdRng, SeedableRng};
+use rand_core::OsRng;
+
+use crate::field::FieldExt;
+use crate::instance::{InstanceError, NamedInstances};
+use crate::planner;
+
+#[derive(Debug)]
+pub enum ProofError {
+    /// The instance vectors do not match the circuit's layout.
+    Instances(InstanceError),
+    Plonk(Error),
+}
+
+impl fmt::Display for ProofError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProofError::Instances(e) => write!(f, "{}", e),
+            ProofError::Plonk(e) => write!(f, "{:?}", e),
+        }
+    }
+}
+
+impl std::error::Error for ProofError {}
+
+impl From<InstanceError> for ProofError {
+    fn from(e: InstanceError) -> Self {
+        ProofError::Instances(e)
+    }
+}
+
+impl From<Error> for ProofError {
+    fn from(e: Error) -> Self {
+        ProofError::Plonk(e)
+    }
+}
+
+/// A commitment scheme proofs are created with.
+pub trait Scheme {
+    /// The curve of the commitments, whose scalar field the circuit is over.
+    type Curve: CurveAffine<ScalarExt: FieldExt + Hash + WithSmallOrderMulGroup<3>>;
+    type Params: for<'params> Params<'params, Self::Curve> + Sync;
+
+    /// The public parameters for `2^k` rows.
+    fn setup(k: u32) -> Self::Params;
+
+    fn create_proof<Circ: Circuit<Scalar<Self>>>(
+        params: &Self::Params,
+        pk: &ProvingKey<Self::Curve>,
+        circuit: Circ,
+        instances: &[&[Scalar<Self>]],
+    ) -> Result<Vec<u8>, Error>;
+
+    fn verify_proof(
+        params: &Self::Params,
+        vk: &VerifyingKey<Self::Curve>,
+        proof: &[u8],
+        instances: &[&[Scalar<Self>]],
+    ) -> Result<(), Error>;
+}
+
+/// The scalar field of a scheme's curve.
+pub type Scalar<S> = <<S as Scheme>::Curve as CurveAffine>::ScalarExt;
+
+/// IPA commitments over the curve `C`, with a transparent setup.
+#[derive(Clone, Copy, Debug)]
+pub struct Ipa<C>(PhantomData<C>);
+
+impl<C: CurveAffine<ScalarExt: FieldExt + Hash + WithSmallOrderMulGroup<3>>> Scheme for Ipa<C> {
+    type Curve = C;
+    type Params = ParamsIPA<C>;
+
+    fn setup(k: u32) -> Self::Params {
+        ParamsIPA::new(k)
+    }
+
+    fn create_proof<Circ: Circuit<C::Scalar>>(
+        params: &Self::Params,
+        pk: &ProvingKey<C>,
+        circuit: Circ,
+        instances: &[&[C::Scalar]],
+    ) -> Result<Vec<u8>, Error> {
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof::<IPACommitmentScheme<C>, ProverIPA<_>, _, _, _, _>(
+            params,
+            pk,
+            &[circuit],
+            &[instances],
+            OsRng,
+            &mut transcript,
+        )?;
+
+        Ok(transcript.finalize())
+    }
+
+    fn verify_proof(
+        params: &Self::Params,
+        vk: &VerifyingKey<C>,
+        proof: &[u8],
+        instances: &[&[C::Scalar]],
+    ) -> Result<(), Error> {
+        let strategy = IpaStrategy::new(params);
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+
+        verify_proof::<IPACommitmentScheme<C>, VerifierIPA<_>, _, _, _>(
+            params,
+            vk,
+            strategy,
+            &[instances],
+            &mut transcript,
+        )
+    }
+}
+
+/// KZG commitments over BN254, opened with SHPLONK.
+///
+/// The parameters come from a setup whose secret is drawn from a fixed seed, so
+/// anyone can rebuild them from `k`, and anyone can forge proofs with them. They
+/// are fit for tests and examples only; a deployment loads the output of a
+/// trusted setup ceremony instead.
+#[derive(Clone, Copy, Debug)]
+pub struct Kzg;
+
+/// The seed of the [`Kzg`] test setup.
+const KZG_SEED: u64 = 0x7a6b47;
+
+impl Scheme for Kzg {
+    type Curve = G1Affine;
+    type Params = ParamsKZG<Bn256>;
+
+    fn setup(k: u32) -> Self::Params {
+        ParamsKZG::setup(k, StdRng::seed_from_u64(KZG_SEED))
+    }
+
+    fn create_proof<Circ: Circuit<Scalar<Self>>>(
+        params: &Self::Params,
+        pk: &ProvingKey<G1Affine>,
+        circuit: Circ,
+        instances: &[&[Scalar<Self>]],
+    ) -> Result<Vec<u8>, Error> {
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<_>, _, _, _, _>(
+            params,
+            pk,
+            &[circuit],
+            &[instances],
+            OsRng,
+            &mut transcript,
+        )?;
+
+        Ok(transcript.finalize())
+    }
+
+    fn verify_proof(
+        params: &Self::Params,
+        vk: &VerifyingKey<G1Affine>,
+        proof: &[u8],
+        instances: &[&[Scalar<Self>]],
+    ) -> Result<(), Error> {
+        let strategy = KzgStrategy::new(params);
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+
+        verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<_>, _, _, _>(
+            params.verifier_params(),
+            vk,
+            strategy,
+            &[instances],
+            &mut transcript,
+        )
+    }
+}
+
+/// Generates the public parameters for `2^k` rows and the proving key of
+/// `circuit`, with `k` from [`planner::min_k`] unless one is given.
+pub fn keygen<S: Scheme, Circ: Circuit<Scalar<S>>>(
+    circuit: &Circ,
+    k: Option<u32>,
+) -> Result<(S::Params, ProvingKey<S::Curve>), Error> {
+    let k = match k {
+        Some(k) => k,
+        None => planner::min_k(circuit)?,
+    };
+    let params = S::setup(k);
+    let vk = keygen_vk(&params, &circuit.without_witnesses())?;
+    let pk = keygen_pk(&params, vk, &circuit.without_witnesses())?;
+
+    Ok((params, pk))
+}
+
+/// Creates a proof for `circuit`, one slice of `instances` per instance column.
+pub fn prove<S: Scheme, Circ: Circuit<Scalar<S>> + NamedInstances>(
+    params: &S::Params,
+    pk: &ProvingKey<S::Curve>,
+    circuit: Circ,
+    instances: &[&[Scalar<S>]],
+) -> Result<Vec<u8>, ProofError> {
+    Circ::instance_layout().check(instances)?;
+
+    Ok(S::create_proof(params, pk, circuit, instances)?)
+}
+
+/// Checks `proof` against the verifying key and the public `instances`.
+pub fn verify<S: Scheme>(
+    params: &S::Params,
+    vk: &VerifyingKey<S::Curve>,
+    proof: &[u8],
+    instances: &[&[Scalar<S>]],
+) -> Result<(), Error> {
+    S::verify_proof(params, vk, proof, instances)
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::{circuit::Value, halo2curves::bn256::Fr};
+
+    use crate::fibo_circuit::FiboCircuit;
+    use crate::pasta::{pallas, vesta};
+
+    use super::*;
+
+    fn check_prove_and_verify<S: Scheme>() {
+        let circuit = FiboCircuit::<Scalar<S>> {
+            a: Value::known(Scalar::<S>::from(1)),
+            b: Value::known(Scalar::<S>::from(1)),
+        };
+        let public_input = [1, 1, 55].map(Scalar::<S>::from);
+
+        let (params, pk) = keygen::<S, _>(&circuit, None).unwrap();
+        let proof = prove::<S, _>(&params, &pk, circuit, &[&public_input]).unwrap();
+
+        assert!(verify::<S>(&params, pk.get_vk(), &proof, &[&public_input]).is_ok());
+
+        let wrong_input = [1, 1, 56].map(Scalar::<S>::from);
+        assert!(verify::<S>(&params, pk.get_vk(), &proof, &[&wrong_input]).is_err());
+    }
+
+    #[test]
+    fn prove_and_verify_test() {
+        check_prove_and_verify::<Ipa<vesta::Affine>>();
+        check_prove_and_verify::<Ipa<pallas::Affine>>();
+        check_prove_and_verify::<Kzg>();
+    }
+
+    #[test]
+    fn prove_checks_layout_test() {
+        let circuit = || FiboCircuit::<Fr> {
+            a: Value::known(Fr::from(1)),
+            b: Value::known(Fr::from(1)),
+        };
+        let (params, pk) = keygen::<Kzg, _>(&circuit(), None).unwrap();
+
+        // the output is missing
+        let err =
+            prove::<Kzg, _>(&params, &pk, circuit(), &[&[Fr::from(1), Fr::from(1)]]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "instance column 0 should hold 3 value(s) [\"a\", \"b\", \"out\"], got 2"
+        );
+
+        let err = prove::<Kzg, _>(&params, &pk, circuit(), &[]).unwrap_err();
+        assert!(matches!(
+            err,
+            ProofError::Instances(InstanceError::ColumnCount { .. })
+        ));
+    }
+}
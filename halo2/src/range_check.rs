@@ -1,3 +1,54 @@
-mod eg1_simple;
-mod eg2_lookup;
-mod eg3_numbits;
+//! Range checks, from a polynomial that vanishes on the range to lookups in a
+//! table of values and their bit lengths.
+//!
+//! The bit length of `v` is the number of bits up to its highest set bit:
+//! `floor(log2 v) + 1`, and 0 for 0.
+
+use halo2_proofs::{
+    circuit::{Chip, Layouter, Value},
+    plonk::Error,
+};
+
+use crate::field::FieldExt;
+
+pub mod eg1_simple;
+pub mod eg2_lookup;
+pub mod eg3_numbits;
+
+/// Constrains private values to `[0, RANGE)`.
+pub trait RangeCheckInstructions<F: FieldExt>: Chip<F> {
+    /// A value in the range.
+    type Num;
+
+    /// Loads the lookup table, if the chip has one; call once per circuit.
+    fn load_table(&self, layouter: impl Layouter<F>) -> Result<(), Error>;
+
+    /// Assigns a private value, constrained to the range.
+    fn witness(&self, layouter: impl Layouter<F>, value: Value<F>) -> Result<Self::Num, Error>;
+}
+
+/// Constrains private values to their claimed bit length.
+pub trait BitLengthInstructions<F: FieldExt>: Chip<F> {
+    /// A value of the claimed bit length.
+    type Num;
+
+    /// Loads the table of values and their bit lengths; call once per circuit.
+    fn load_table(&self, layouter: impl Layouter<F>) -> Result<(), Error>;
+
+    /// Assigns a private value and its bit length, looked up together.
+    fn witness(
+        &self,
+        layouter: impl Layouter<F>,
+        value: Value<F>,
+        num_bits: Value<u64>,
+    ) -> Result<Self::Num, Error>;
+
+    /// Constrains `value` to be exactly `num_bits` bits long, even when it is
+    /// wider than the table.
+    fn assert_bit_length(
+        &self,
+        layouter: impl Layouter<F>,
+        value: &Self::Num,
+        num_bits: usize,
+    ) -> Result<(), Error>;
+}
@@ -1,92 +1,184 @@
 // this is sopposed to check if a given value is less than a given range
 
 use halo2_proofs::{
-    arithmetic::FieldExt,
-    circuit::Layouter,
-    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
+    circuit::{Chip, Layouter, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
     poly::Rotation,
 };
-
-#[derive(Clone)]
-struct RangeCheckConfig<F: FieldExt, const RANGE: usize> {
+use serde::Deserialize;
+
+use super::RangeCheckInstructions;
+use crate::backend::GenericCircuit;
+use crate::cell::{AssignedCell, RegionExt};
+use crate::field::FieldExt;
+use crate::input::{CircuitInput, CircuitWithInstances, FieldValue, InputError, NoPublicInputs};
+use crate::instance::{InstanceLayout, NamedInstances};
+use crate::planner::SimpleFloorPlanner;
+
+#[derive(Clone, Debug)]
+pub struct RangeCheckConfig<F: FieldExt, const RANGE: usize> {
     value: Column<Advice>,
     selector: Selector,
     _marker: std::marker::PhantomData<F>,
 }
 
-impl<F: FieldExt, const RANGE: usize> RangeCheckConfig<F, RANGE> {
-    fn configure(meta: &mut ConstraintSystem<F>, value: Column<Advice>) -> Self {
+pub struct RangeCheckChip<F: FieldExt, const RANGE: usize> {
+    config: RangeCheckConfig<F, RANGE>,
+}
+
+impl<F: FieldExt, const RANGE: usize> RangeCheckChip<F, RANGE> {
+    pub fn construct(config: RangeCheckConfig<F, RANGE>) -> Self {
+        Self { config }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        value: Column<Advice>,
+    ) -> RangeCheckConfig<F, RANGE> {
         let selector = meta.selector();
 
         meta.create_gate("Range Check", |meta| {
             let value = meta.query_advice(value, Rotation::cur());
             let q_range_check = meta.query_selector(selector);
             let range_check = |value: Expression<F>| {
-                (0..RANGE).into_iter().fold(value.clone(), |acc, el| {
+                (0..RANGE).fold(value.clone(), |acc, el| {
                     acc * (value.clone() - Expression::Constant(F::from(el as u64)))
                 })
             };
             Constraints::with_selector(q_range_check, [("range check", range_check(value))])
         });
+        // halo2-axiom caps the degree it takes from the gates at 5, which this
+        // gate of degree `RANGE + 2` exceeds
+        meta.set_minimum_degree(RANGE + 2);
 
-        Self {
+        RangeCheckConfig {
             value,
             selector,
             _marker: std::marker::PhantomData,
         }
     }
+}
+
+impl<F: FieldExt, const RANGE: usize> Chip<F> for RangeCheckChip<F, RANGE> {
+    type Config = RangeCheckConfig<F, RANGE>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt, const RANGE: usize> RangeCheckInstructions<F> for RangeCheckChip<F, RANGE> {
+    type Num = AssignedCell<F>;
+
+    fn load_table(&self, _layouter: impl Layouter<F>) -> Result<(), Error> {
+        Ok(())
+    }
 
-    fn assign(&self, mut layouter: impl Layouter<F>, value: F) -> Result<(), Error> {
+    fn witness(&self, mut layouter: impl Layouter<F>, value: Value<F>) -> Result<Self::Num, Error> {
         layouter.assign_region(
             || "assign value",
             |mut r| {
-                self.selector.enable(&mut r, 0)?;
-                r.assign_advice(|| "assign value", self.value, 0, || Ok(value))?;
-                Ok(())
+                self.config.selector.enable(&mut r, 0)?;
+                Ok(r.assign(self.config.value, 0, value))
             },
-        )?;
+        )
+    }
+}
 
-        Ok(())
+#[derive(Default)]
+pub struct RangeCheckCircuit<F: FieldExt, const RANGE: usize> {
+    pub value: Value<F>,
+}
+
+impl<F: FieldExt, const RANGE: usize> NamedInstances for RangeCheckCircuit<F, RANGE> {
+    fn instance_layout() -> InstanceLayout {
+        InstanceLayout::empty()
     }
 }
 
-#[cfg(test)]
-mod test {
+impl<F: FieldExt, const RANGE: usize> Circuit<F> for RangeCheckCircuit<F, RANGE> {
+    type Config = RangeCheckConfig<F, RANGE>;
+    type FloorPlanner = SimpleFloorPlanner;
+    type Params = ();
 
-    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
 
-    use super::*;
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let range_check_advice = meta.advice_column();
+        RangeCheckChip::configure(meta, range_check_advice)
+    }
 
-    #[derive(Default)]
-    struct RangeCheckCircuit<F: FieldExt, const RANGE: usize> {
-        value: Option<F>,
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = RangeCheckChip::construct(config);
+        chip.witness(layouter.namespace(|| "range check syn"), self.value)?;
+        Ok(())
     }
+}
 
-    impl<F: FieldExt, const RANGE: usize> Circuit<F> for RangeCheckCircuit<F, RANGE> {
-        type Config = RangeCheckConfig<F, RANGE>;
-        type FloorPlanner = SimpleFloorPlanner;
+/// The inputs of [`RangeCheckCircuit`], independent of the field it is built over.
+#[derive(Clone, Debug)]
+pub struct RangeCheckInput<const RANGE: usize> {
+    pub value: u64,
+}
 
-        fn without_witnesses(&self) -> Self {
-            Self::default()
-        }
+impl<const RANGE: usize> GenericCircuit for RangeCheckInput<RANGE> {
+    type Circuit<F: FieldExt> = RangeCheckCircuit<F, RANGE>;
 
-        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-            let range_check_advice = meta.advice_column();
-            RangeCheckConfig::configure(meta, range_check_advice)
+    fn circuit<F: FieldExt>(&self) -> Self::Circuit<F> {
+        RangeCheckCircuit {
+            value: Value::known(F::from(self.value)),
         }
+    }
 
-        fn synthesize(
-            &self,
-            config: Self::Config,
-            mut layouter: impl Layouter<F>,
-        ) -> Result<(), Error> {
-            config.assign(
-                layouter.namespace(|| "range check syn"),
-                self.value.unwrap(),
-            )?;
-            Ok(())
-        }
+    fn instances<F: FieldExt>(&self) -> Vec<Vec<F>> {
+        vec![]
+    }
+}
+
+/// The JSON inputs of [`RangeCheckCircuit`], see [`crate::input`].
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RangeCheckInputFile<const RANGE: usize> {
+    pub private: RangeCheckPrivateInputs,
+    #[serde(default)]
+    pub public: NoPublicInputs,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RangeCheckPrivateInputs {
+    pub value: FieldValue,
+}
+
+impl<const RANGE: usize> CircuitInput for RangeCheckInputFile<RANGE> {
+    type Circuit<F: FieldExt> = RangeCheckCircuit<F, RANGE>;
+
+    fn build<F: FieldExt>(&self) -> Result<CircuitWithInstances<Self, F>, InputError> {
+        let circuit = RangeCheckCircuit {
+            value: Value::known(self.private.value.to_field("private.value")?),
+        };
+
+        Ok((circuit, vec![]))
     }
+}
+
+#[cfg(test)]
+mod test {
+
+    use halo2_proofs::{dev::MockProver, halo2curves::pasta::Fp};
+
+    use super::*;
 
     #[test]
     fn range_check_test() {
@@ -94,7 +186,7 @@ mod test {
 
         for i in 0..RANGE {
             let circuit = RangeCheckCircuit::<Fp, RANGE> {
-                value: Some(Fp::from(i as u64)),
+                value: Value::known(Fp::from(i as u64)),
             };
             let prover = MockProver::run(4, &circuit, vec![]).unwrap();
             prover.assert_satisfied()
@@ -2,148 +2,360 @@
 // uses lookup table for large ranges
 
 use halo2_proofs::{
-    arithmetic::FieldExt,
-    circuit::Layouter,
-    plonk::{Advice, Column, ConstraintSystem, Error, Selector},
+    circuit::{Chip, Layouter, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Fixed, Selector},
     poly::Rotation,
 };
+use serde::Deserialize;
 
-use self::table::RangeCheckTable;
+use super::BitLengthInstructions;
+use crate::backend::GenericCircuit;
+use crate::cell::{AssignedCell, RegionExt};
+use crate::decompose_range_check::{DecomposeChip, DecomposeConfig, DecomposeInstructions};
+use crate::field::FieldExt;
+use crate::input::{CircuitInput, CircuitWithInstances, FieldValue, InputError, NoPublicInputs};
+use crate::instance::{InstanceLayout, NamedInstances};
+use crate::planner::SimpleFloorPlanner;
+use crate::table::{TableChip, TableConfig, TableInstructions};
 
-#[derive(Clone)]
-struct RangeCheckConfig<F: FieldExt, const RANGE: usize> {
+/// The bits of the values in the lookup table.
+const TABLE_BITS: usize = 3;
+
+#[derive(Clone, Debug)]
+pub struct RangeCheckConfig<F: FieldExt, const RANGE: usize> {
     value: Column<Advice>,
     num_bits: Column<Advice>,
     selector: Selector,
-    table: RangeCheckTable<F, 3>,
-    _marker: std::marker::PhantomData<F>,
+    table: TableConfig<F, 2>,
+    decompose: DecomposeConfig<F, TABLE_BITS>,
+}
+
+pub struct RangeCheckChip<F: FieldExt, const RANGE: usize> {
+    config: RangeCheckConfig<F, RANGE>,
 }
 
-mod table;
+impl<F: FieldExt, const RANGE: usize> RangeCheckChip<F, RANGE> {
+    pub fn construct(config: RangeCheckConfig<F, RANGE>) -> Self {
+        Self { config }
+    }
 
-impl<F: FieldExt, const RANGE: usize> RangeCheckConfig<F, RANGE> {
-    fn configure(
+    /// `constants` must be a fixed column; [`BitLengthInstructions::assert_bit_length`]
+    /// takes the bit length from it, and decomposes wide values in `value`.
+    pub fn configure(
         meta: &mut ConstraintSystem<F>,
         value: Column<Advice>,
         num_bits: Column<Advice>,
-    ) -> Self {
+        constants: Column<Fixed>,
+    ) -> RangeCheckConfig<F, RANGE> {
         let selector = meta.complex_selector();
-        let table: RangeCheckTable<F, 3> = RangeCheckTable::configure(meta);
+        let table = TableChip::configure(meta);
+        let decompose = DecomposeChip::configure(meta, value, constants);
+        meta.enable_equality(num_bits);
 
-        meta.lookup(|meta| {
+        meta.lookup("num bits", |meta| {
             let q_lookup = meta.query_selector(selector);
             let value = meta.query_advice(value, Rotation::cur());
             let num_bits = meta.query_advice(num_bits, Rotation::cur());
 
-            vec![
-                (q_lookup.clone() * value, table.value),
-                (q_lookup * num_bits, table.num_bits),
-            ]
+            table.lookup(q_lookup, &[value, num_bits])
         });
 
-        Self {
+        RangeCheckConfig {
             value,
             num_bits,
             selector,
             table,
-            _marker: std::marker::PhantomData,
+            decompose,
         }
     }
+}
+
+impl<F: FieldExt, const RANGE: usize> Chip<F> for RangeCheckChip<F, RANGE> {
+    type Config = RangeCheckConfig<F, RANGE>;
+    type Loaded = TableConfig<F, 2>;
 
-    fn assign(
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &self.config.table
+    }
+}
+
+impl<F: FieldExt, const RANGE: usize> BitLengthInstructions<F> for RangeCheckChip<F, RANGE> {
+    type Num = AssignedCell<F>;
+
+    fn load_table(&self, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        TableChip::construct(self.config.table.clone()).load_fn(
+            layouter.namespace(|| "bit lengths"),
+            1 << TABLE_BITS,
+            |i| [F::from(i as u64), F::from(bit_length(i as u64))],
+        )?;
+        DecomposeChip::construct(self.config.decompose.clone())
+            .load_table(layouter.namespace(|| "chunks"))
+    }
+
+    fn witness(
         &self,
         mut layouter: impl Layouter<F>,
-        value: F,
-        num_bits: usize,
-    ) -> Result<(), Error> {
+        value: Value<F>,
+        num_bits: Value<u64>,
+    ) -> Result<Self::Num, Error> {
         layouter.assign_region(
             || "assign value",
             |mut r| {
-                self.selector.enable(&mut r, 0)?;
-                r.assign_advice(|| "assign value", self.value, 0, || Ok(value))?;
-                r.assign_advice(
-                    || "assign num bits",
-                    self.num_bits,
+                self.config.selector.enable(&mut r, 0)?;
+                r.assign(self.config.num_bits, 0, num_bits.map(F::from));
+                Ok(r.assign(self.config.value, 0, value))
+            },
+        )
+    }
+
+    fn assert_bit_length(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: &Self::Num,
+        num_bits: usize,
+    ) -> Result<(), Error> {
+        if num_bits == 0 {
+            return layouter.assign_region(
+                || "zero",
+                |mut region| {
+                    let value = value.copy_to(&mut region, self.config.value, 0);
+                    region.constrain_constant(value.cell(), F::ZERO)
+                },
+            );
+        }
+
+        // the chunks below the one holding the highest bit are only range
+        // checked; the top chunk is looked up with its bit length
+        let low_bits = (num_bits - 1) / TABLE_BITS * TABLE_BITS;
+        let top = DecomposeChip::construct(self.config.decompose.clone()).decompose_low(
+            layouter.namespace(|| "low chunks"),
+            value,
+            low_bits,
+        )?;
+
+        layouter.assign_region(
+            || "top chunk",
+            |mut region| {
+                self.config.selector.enable(&mut region, 0)?;
+                top.copy_to(&mut region, self.config.value, 0);
+                region.assign_constant(
+                    self.config.num_bits,
                     0,
-                    || Ok(F::from(num_bits as u64)),
+                    F::from((num_bits - low_bits) as u64),
                 )?;
                 Ok(())
             },
-        )?;
+        )
+    }
+}
+
+/// The number of bits of `v` up to its highest set bit; 0 for 0.
+pub fn bit_length(v: u64) -> u64 {
+    (u64::BITS - v.leading_zeros()) as u64
+}
+
+#[derive(Default)]
+pub struct RangeCheckCircuit<F: FieldExt, const RANGE: usize> {
+    pub value: F,
+    pub num_bits: usize,
+}
 
+impl<F: FieldExt, const RANGE: usize> NamedInstances for RangeCheckCircuit<F, RANGE> {
+    fn instance_layout() -> InstanceLayout {
+        InstanceLayout::empty()
+    }
+}
+
+impl<F: FieldExt, const RANGE: usize> Circuit<F> for RangeCheckCircuit<F, RANGE> {
+    type Config = RangeCheckConfig<F, RANGE>;
+    type FloorPlanner = SimpleFloorPlanner;
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let range_check_advice = meta.advice_column();
+        let num_bits = meta.advice_column();
+        let constants = meta.fixed_column();
+        RangeCheckChip::configure(meta, range_check_advice, num_bits, constants)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = RangeCheckChip::construct(config);
+        chip.load_table(layouter.namespace(|| "range table"))?;
+        chip.witness(
+            layouter.namespace(|| "range check syn"),
+            Value::known(self.value),
+            Value::known(self.num_bits as u64),
+        )?;
         Ok(())
     }
 }
 
+/// The inputs of [`RangeCheckCircuit`], independent of the field it is built over.
+#[derive(Clone, Debug)]
+pub struct RangeCheckInput {
+    pub value: u64,
+    pub num_bits: usize,
+}
+
+impl GenericCircuit for RangeCheckInput {
+    type Circuit<F: FieldExt> = RangeCheckCircuit<F, 8>;
+
+    fn circuit<F: FieldExt>(&self) -> Self::Circuit<F> {
+        RangeCheckCircuit {
+            value: F::from(self.value),
+            num_bits: self.num_bits,
+        }
+    }
+
+    fn instances<F: FieldExt>(&self) -> Vec<Vec<F>> {
+        vec![]
+    }
+}
+
+/// The JSON inputs of [`RangeCheckCircuit`], see [`crate::input`].
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RangeCheckInputFile {
+    pub private: RangeCheckPrivateInputs,
+    #[serde(default)]
+    pub public: NoPublicInputs,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RangeCheckPrivateInputs {
+    pub value: FieldValue,
+    pub num_bits: usize,
+}
+
+impl CircuitInput for RangeCheckInputFile {
+    type Circuit<F: FieldExt> = RangeCheckCircuit<F, 8>;
+
+    fn build<F: FieldExt>(&self) -> Result<CircuitWithInstances<Self, F>, InputError> {
+        let circuit = RangeCheckCircuit {
+            value: self.private.value.to_field("private.value")?,
+            num_bits: self.private.num_bits,
+        };
+
+        Ok((circuit, vec![]))
+    }
+}
+
 #[cfg(test)]
 mod test {
 
-    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit};
+    use group::ff::PrimeField;
+    use halo2_proofs::{arithmetic::Field, dev::MockProver, halo2curves::pasta::Fp};
 
     use super::*;
 
+    #[test]
+    fn bit_length_test() {
+        assert_eq!(bit_length(0), 0);
+        assert_eq!(bit_length(1), 1);
+        assert_eq!(bit_length(5), 3);
+        assert_eq!(bit_length(8), 4);
+        assert_eq!(bit_length(u64::MAX), 64);
+    }
+
+    #[test]
+    fn range_check_test() {
+        const RANGE: usize = 8;
+
+        for i in 0..RANGE {
+            let circuit = RangeCheckCircuit::<Fp, RANGE> {
+                value: Fp::from(i as u64),
+                num_bits: bit_length(i as u64) as usize,
+            };
+            let prover = MockProver::run(10, &circuit, vec![]).unwrap();
+            prover.assert_satisfied()
+        }
+
+        // 1 is one bit long, not zero
+        let circuit = RangeCheckCircuit::<Fp, RANGE> {
+            value: Fp::ONE,
+            num_bits: 0,
+        };
+        let prover = MockProver::run(10, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    /// Asserts the bit length of a value that may be wider than the table.
     #[derive(Default)]
-    struct RangeCheckCircuit<F: FieldExt, const RANGE: usize> {
-        value: F,
+    struct BitLengthCircuit {
+        value: Value<Fp>,
         num_bits: usize,
     }
 
-    impl<F: FieldExt, const RANGE: usize> Circuit<F> for RangeCheckCircuit<F, RANGE> {
-        type Config = RangeCheckConfig<F, RANGE>;
+    impl Circuit<Fp> for BitLengthCircuit {
+        type Config = RangeCheckConfig<Fp, 8>;
         type FloorPlanner = SimpleFloorPlanner;
+        type Params = ();
 
         fn without_witnesses(&self) -> Self {
-            Self::default()
+            Self {
+                value: Value::unknown(),
+                num_bits: self.num_bits,
+            }
         }
 
-        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-            let range_check_advice = meta.advice_column();
-            let num_bits = meta.advice_column();
-            RangeCheckConfig::configure(meta, range_check_advice, num_bits)
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            RangeCheckCircuit::<Fp, 8>::configure(meta)
         }
 
         fn synthesize(
             &self,
             config: Self::Config,
-            mut layouter: impl Layouter<F>,
+            mut layouter: impl Layouter<Fp>,
         ) -> Result<(), Error> {
-            config
-                .table
-                .load(layouter.namespace(|| "range check syn"))?;
-            config.assign(
-                layouter.namespace(|| "range check syn"),
-                self.value,
-                self.num_bits,
+            let column = config.value;
+            let chip = RangeCheckChip::construct(config);
+            chip.load_table(layouter.namespace(|| "tables"))?;
+
+            let value = layouter.assign_region(
+                || "value",
+                |mut region| Ok(region.assign(column, 0, self.value)),
             )?;
-            Ok(())
+            chip.assert_bit_length(layouter.namespace(|| "bit length"), &value, self.num_bits)
         }
     }
 
-    fn log2(x: u64) -> u64 {
-        // implement log 2 of x
-        // https://stackoverflow.com/questions/11376288/fast-computing-of-log2-for-64-bit-integers
-        let mut x = x;
-        let mut result = 0;
-
-        while x > 1 {
-            x >>= 1;
-            result += 1;
-        }
-
-        result
+    fn prove(value: u128, num_bits: usize) -> Result<(), ()> {
+        let circuit = BitLengthCircuit {
+            value: Value::known(Fp::from_u128(value)),
+            num_bits,
+        };
+        MockProver::run(6, &circuit, vec![])
+            .unwrap()
+            .verify()
+            .map_err(|_| ())
     }
 
     #[test]
-    fn range_check_test() {
-        const RANGE: usize = 8;
+    fn assert_bit_length_test() {
+        assert_eq!(prove(0, 0), Ok(()));
+        assert!(prove(1, 0).is_err());
 
-        for i in 0..RANGE {
-            let circuit = RangeCheckCircuit::<Fp, RANGE> {
-                value: Fp::from(i as u64),
-                num_bits: log2(i as u64) as usize,
-            };
-            let prover = MockProver::run(10, &circuit, vec![]).unwrap();
-            prover.assert_satisfied()
+        for num_bits in 1..=100 {
+            let smallest = 1u128 << (num_bits - 1);
+            let largest = (1u128 << num_bits) - 1;
+            for value in [smallest, largest] {
+                assert_eq!(prove(value, num_bits), Ok(()));
+                assert!(prove(value, num_bits - 1).is_err());
+                assert!(prove(value, num_bits + 1).is_err());
+            }
         }
     }
 }
@@ -0,0 +1,377 @@
+//! Schnorr signatures over Pallas, verified by a key hidden in a public set.
+//!
+//! A signature on `m` by the key `P = [x]G` is `(R, s)` with `R = [k]G` for a
+//! random `k`, `e = H(R, m)` and `s = k + e·x`; it is valid if `[s]G == R + [e]P`.
+//! The challenge is SHA-256 of `R` and the message, reduced to [`CHALLENGE_BITS`]
+//! bits with the top one set, so it is smaller than both Pasta moduli.
+//!
+//! [`SchnorrSetCircuit`] proves that `(R, s)` is a valid signature by one of a
+//! set of keys, keeping `s` and the key private: `s` would reveal the key,
+//! since `[e]P = [s]G - R`. The verifier computes `e` from `R` and the message
+//! itself. The challenge does not commit to the key, which is what lets it stay
+//! hidden. The keys themselves are private too: the verifier only sees
+//! [`key_set_commitment`], a SHA-256 digest of them, which binds the set but
+//! does not hide it from anyone who can guess it.
+
+mod schnorr_chip;
+
+pub use self::schnorr_chip::{SchnorrChip, SchnorrConfig, SchnorrInstructions};
+
+use group::ff::{Field, PrimeField};
+use group::{Curve, Group};
+use halo2_proofs::arithmetic::CurveAffine;
+use halo2_proofs::circuit::{Layouter, Value};
+use halo2_proofs::halo2curves::pasta::pallas;
+use halo2_proofs::halo2curves::Coordinates;
+use halo2_proofs::plonk::{Circuit, ConstraintSystem, Error};
+use rand_core::RngCore;
+
+use crate::ecc::EccInstructions;
+use crate::instance::{InstanceColumns, InstanceLayout, NamedInstances};
+use crate::pasta::Fp;
+use crate::planner::SimpleFloorPlanner;
+use crate::sha256::{pack, sha256};
+
+/// The bits of a challenge.
+pub const CHALLENGE_BITS: usize = 250;
+
+/// The digest words of a key set commitment, 224 bits.
+pub const KEY_SET_WORDS: usize = 7;
+
+#[derive(Clone, Debug)]
+pub struct SigningKey(pallas::Scalar);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VerifyingKey(pub pallas::Affine);
+
+#[derive(Clone, Copy, Debug)]
+pub struct Signature {
+    pub r: pallas::Affine,
+    pub s: pallas::Scalar,
+}
+
+impl SigningKey {
+    pub fn random(rng: impl RngCore) -> Self {
+        Self(pallas::Scalar::random(rng))
+    }
+
+    pub fn verifying_key(&self) -> VerifyingKey {
+        VerifyingKey((pallas::Point::generator() * self.0).to_affine())
+    }
+
+    pub fn sign(&self, message: &[u8], rng: impl RngCore) -> Signature {
+        let k = pallas::Scalar::random(rng);
+        let r = (pallas::Point::generator() * k).to_affine();
+        let e = challenge(&r, message);
+        Signature {
+            r,
+            s: k + e * self.0,
+        }
+    }
+}
+
+impl VerifyingKey {
+    pub fn verify(&self, message: &[u8], signature: &Signature) -> bool {
+        let e = challenge(&signature.r, message);
+        pallas::Point::generator() * signature.s == signature.r + self.0 * e
+    }
+}
+
+/// `H(R, m)`: SHA-256 of the coordinates of `R` and the message, truncated to
+/// [`CHALLENGE_BITS`] bits with the top one set.
+pub fn challenge(r: &pallas::Affine, message: &[u8]) -> pallas::Scalar {
+    let mut bytes = vec![];
+    let coordinates: Option<Coordinates<_>> = r.coordinates().into();
+    if let Some(c) = coordinates {
+        bytes.extend_from_slice(c.x().to_repr().as_ref());
+        bytes.extend_from_slice(c.y().to_repr().as_ref());
+    }
+    bytes.extend_from_slice(message);
+
+    // the digest as a little-endian integer
+    let mut repr = [0u8; 32];
+    for (chunk, word) in repr.chunks_mut(4).zip(sha256(&bytes)) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+    let top = CHALLENGE_BITS - 1;
+    repr[top / 8] &= (1 << (top % 8)) - 1;
+    repr[top / 8] |= 1 << (top % 8);
+    for byte in &mut repr[top / 8 + 1..] {
+        *byte = 0;
+    }
+
+    pallas::Scalar::from_repr(repr).unwrap()
+}
+
+/// The commitment to a set of keys: SHA-256 of their coordinates, each a
+/// 32-byte big-endian integer, `x` before `y`, truncated to [`KEY_SET_WORDS`]
+/// words.
+pub fn key_set_commitment(keys: &[VerifyingKey]) -> Fp {
+    let mut bytes = vec![];
+    for key in keys {
+        let c = key.0.coordinates().unwrap();
+        for coordinate in [c.x(), c.y()] {
+            let mut repr = coordinate.to_repr();
+            repr.reverse();
+            bytes.extend_from_slice(&repr);
+        }
+    }
+    pack(&sha256(&bytes)[..KEY_SET_WORDS])
+}
+
+/// `e` in the circuit field, where it has the same value.
+fn challenge_base(e: &pallas::Scalar) -> Fp {
+    Fp::from_repr(e.to_repr()).unwrap()
+}
+
+const INSTANCE_NAMES: [&str; 4] = ["r_x", "r_y", "e", "key_set"];
+
+/// The public inputs of [`SchnorrSetCircuit`]: the signature's `R`, the
+/// challenge and the commitment to the keys.
+pub const SIGNATURE_LAYOUT: InstanceLayout = InstanceLayout::new(&[&INSTANCE_NAMES]);
+
+/// The public inputs of [`SchnorrSetCircuit`], as the verifier computes them
+/// from the commitment to the key set.
+pub fn signature_instances(key_set: Fp, message: &[u8], r: &pallas::Affine) -> Vec<Vec<Fp>> {
+    let c = r.coordinates().unwrap();
+    let e = challenge_base(&challenge(r, message));
+    let values = [
+        ("r_x", Fp(*c.x())),
+        ("r_y", Fp(*c.y())),
+        ("e", e),
+        ("key_set", key_set),
+    ];
+    SIGNATURE_LAYOUT.assemble(&values).unwrap()
+}
+
+/// Proves that a message was signed by one of `N` committed keys, without
+/// revealing which one.
+#[derive(Clone, Debug)]
+pub struct SchnorrSetCircuit<const N: usize> {
+    pub keys: Value<[VerifyingKey; N]>,
+    pub index: Value<usize>,
+    pub signature: Value<Signature>,
+    pub challenge: Value<pallas::Scalar>,
+}
+
+impl<const N: usize> Default for SchnorrSetCircuit<N> {
+    fn default() -> Self {
+        Self {
+            keys: Value::unknown(),
+            index: Value::unknown(),
+            signature: Value::unknown(),
+            challenge: Value::unknown(),
+        }
+    }
+}
+
+impl<const N: usize> SchnorrSetCircuit<N> {
+    pub fn new(
+        keys: [VerifyingKey; N],
+        index: usize,
+        message: &[u8],
+        signature: Signature,
+    ) -> Self {
+        Self {
+            keys: Value::known(keys),
+            index: Value::known(index),
+            signature: Value::known(signature),
+            challenge: Value::known(challenge(&signature.r, message)),
+        }
+    }
+}
+
+impl<const N: usize> NamedInstances for SchnorrSetCircuit<N> {
+    fn instance_layout() -> InstanceLayout {
+        SIGNATURE_LAYOUT
+    }
+}
+
+impl<const N: usize> Circuit<Fp> for SchnorrSetCircuit<N> {
+    type Config = (SchnorrConfig, InstanceColumns);
+    type FloorPlanner = SimpleFloorPlanner;
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let bits = [(); 32].map(|_| meta.advice_column());
+        let word = meta.advice_column();
+        let carry = [(); 3].map(|_| meta.advice_column());
+        let constants = meta.fixed_column();
+        let instance = InstanceColumns::configure(meta, SIGNATURE_LAYOUT);
+
+        (
+            SchnorrChip::configure(meta, bits, word, carry, constants),
+            instance,
+        )
+    }
+
+    fn synthesize(
+        &self,
+        (config, instance): Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let chip = SchnorrChip::construct(config);
+
+        let values: Vec<_> = (0..N).map(|i| self.keys.map(|keys| keys[i].0)).collect();
+        let (keys, key_set) = chip.load_keys(layouter.namespace(|| "keys"), &values)?;
+        instance.expose(layouter.namespace(|| "key_set"), key_set.cell(), "key_set")?;
+        let key = chip.select_key(layouter.namespace(|| "key"), &keys, self.index)?;
+
+        let r = chip.ecc().witness_point(
+            layouter.namespace(|| "R"),
+            self.signature.map(|signature| signature.r),
+        )?;
+        instance.expose(layouter.namespace(|| "r_x"), r.x.cell(), "r_x")?;
+        instance.expose(layouter.namespace(|| "r_y"), r.y.cell(), "r_y")?;
+
+        let e = chip.verify(
+            layouter.namespace(|| "verify"),
+            &key,
+            &r,
+            self.signature.map(|signature| signature.s),
+            self.challenge,
+        )?;
+        instance.expose(layouter.namespace(|| "e"), e.cell(), "e")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::dev::MockProver;
+    use rand_core::OsRng;
+
+    use crate::pasta::vesta;
+    use crate::proof::{keygen, prove, verify, Ipa};
+
+    use super::*;
+
+    const MESSAGE: &[u8] = b"transfer 10 tokens to bob";
+
+    /// Fits a set of four keys, whose commitment hashes five blocks.
+    const K: u32 = 14;
+
+    fn keys() -> ([SigningKey; 4], [VerifyingKey; 4]) {
+        let signing = [(); 4].map(|_| SigningKey::random(OsRng));
+        let verifying = [0, 1, 2, 3].map(|i| signing[i].verifying_key());
+        (signing, verifying)
+    }
+
+    #[test]
+    fn native_test() {
+        let (signing, verifying) = keys();
+        let signature = signing[0].sign(MESSAGE, OsRng);
+        assert!(verifying[0].verify(MESSAGE, &signature));
+
+        assert!(!verifying[0].verify(b"transfer 99 tokens to bob", &signature));
+        assert!(!verifying[1].verify(MESSAGE, &signature));
+        let forged = Signature {
+            s: signature.s + pallas::Scalar::ONE,
+            ..signature
+        };
+        assert!(!verifying[0].verify(MESSAGE, &forged));
+
+        // the challenge has exactly CHALLENGE_BITS bits
+        let bits = crate::ecc::scalar_bits(&challenge(&signature.r, MESSAGE));
+        assert_eq!(
+            bits.iter().position(|bit| *bit),
+            Some(bits.len() - CHALLENGE_BITS)
+        );
+    }
+
+    #[test]
+    fn signed_by_set_member_test() {
+        let (signing, verifying) = keys();
+        let signature = signing[2].sign(MESSAGE, OsRng);
+
+        let circuit = SchnorrSetCircuit::new(verifying, 2, MESSAGE, signature);
+        let instances = signature_instances(key_set_commitment(&verifying), MESSAGE, &signature.r);
+        assert_eq!(crate::planner::min_k(&circuit).unwrap(), K);
+        MockProver::run(K, &circuit, instances)
+            .unwrap()
+            .assert_satisfied();
+
+        // the proof is for the message the verifier has in mind
+        let other = signature_instances(
+            key_set_commitment(&verifying),
+            b"transfer 99 tokens",
+            &signature.r,
+        );
+        assert!(MockProver::run(K, &circuit, other)
+            .unwrap()
+            .verify()
+            .is_err());
+    }
+
+    #[test]
+    fn ipa_proof_test() {
+        let (signing, verifying) = keys();
+        let signature = signing[1].sign(MESSAGE, OsRng);
+
+        let circuit = SchnorrSetCircuit::new(verifying, 1, MESSAGE, signature);
+        let instances = signature_instances(key_set_commitment(&verifying), MESSAGE, &signature.r);
+        let (params, pk) = keygen::<Ipa<vesta::Affine>, _>(&circuit, Some(K)).unwrap();
+        let proof =
+            prove::<Ipa<vesta::Affine>, _>(&params, &pk, circuit, &[&instances[0]]).unwrap();
+        assert!(
+            verify::<Ipa<vesta::Affine>>(&params, pk.get_vk(), &proof, &[&instances[0]]).is_ok()
+        );
+
+        let other = signature_instances(
+            key_set_commitment(&verifying),
+            b"transfer 99 tokens",
+            &signature.r,
+        );
+        assert!(verify::<Ipa<vesta::Affine>>(&params, pk.get_vk(), &proof, &[&other[0]]).is_err());
+    }
+
+    #[test]
+    fn forged_signature_test() {
+        let (signing, verifying) = keys();
+        let instances = |signature: &Signature| {
+            signature_instances(key_set_commitment(&verifying), MESSAGE, &signature.r)
+        };
+
+        // a valid signature, claimed to be by another key of the set
+        let signature = signing[1].sign(MESSAGE, OsRng);
+        let circuit = SchnorrSetCircuit::new(verifying, 3, MESSAGE, signature);
+        assert!(MockProver::run(K, &circuit, instances(&signature))
+            .unwrap()
+            .verify()
+            .is_err());
+
+        // a valid signature by a key outside the set
+        let outsider = SigningKey::random(OsRng);
+        let signature = outsider.sign(MESSAGE, OsRng);
+        for index in 0..4 {
+            let circuit = SchnorrSetCircuit::new(verifying, index, MESSAGE, signature);
+            assert!(MockProver::run(K, &circuit, instances(&signature))
+                .unwrap()
+                .verify()
+                .is_err());
+        }
+
+        // a valid signature by a key swapped into the set
+        let mut swapped = verifying;
+        swapped[0] = outsider.verifying_key();
+        let circuit = SchnorrSetCircuit::new(swapped, 0, MESSAGE, signature);
+        assert!(MockProver::run(K, &circuit, instances(&signature))
+            .unwrap()
+            .verify()
+            .is_err());
+
+        // a made-up s
+        let forged = Signature {
+            r: signing[0].sign(MESSAGE, OsRng).r,
+            s: pallas::Scalar::random(OsRng),
+        };
+        let circuit = SchnorrSetCircuit::new(verifying, 0, MESSAGE, forged);
+        assert!(MockProver::run(K, &circuit, instances(&forged))
+            .unwrap()
+            .verify()
+            .is_err());
+    }
+}
@@ -0,0 +1,330 @@
+use group::ff::{Field, PrimeField};
+use group::{Curve, Group};
+use halo2_proofs::circuit::{Chip, Layouter, Value};
+use halo2_proofs::halo2curves::pasta::pallas;
+use halo2_proofs::plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector};
+use halo2_proofs::poly::Rotation;
+
+use super::{CHALLENGE_BITS, KEY_SET_WORDS};
+use crate::cell::{AssignedCell, RegionExt};
+use crate::ecc::{scalar_bits, EccChip, EccConfig, EccInstructions, EccPoint};
+use crate::pasta::Fp;
+use crate::sha256::{pad, Sha256Chip, Sha256Config, Sha256Instructions, Word};
+
+/// Schnorr signature verification by a key of a committed set.
+pub trait SchnorrInstructions: Chip<Fp> {
+    /// Assigns a private set of keys, and returns them with the commitment to
+    /// the set, see [`key_set_commitment`](super::key_set_commitment).
+    fn load_keys(
+        &self,
+        layouter: impl Layouter<Fp>,
+        keys: &[Value<pallas::Affine>],
+    ) -> Result<(Vec<EccPoint>, AssignedCell<Fp>), Error>;
+
+    /// The key at `index` in `keys`, constrained to be exactly one of them
+    /// without revealing which.
+    fn select_key(
+        &self,
+        layouter: impl Layouter<Fp>,
+        keys: &[EccPoint],
+        index: Value<usize>,
+    ) -> Result<EccPoint, Error>;
+
+    /// Checks `[s]G == R + [e]key` and returns the challenge `e` as a cell, to
+    /// be compared with the one the verifier computes from `R` and the message.
+    fn verify(
+        &self,
+        layouter: impl Layouter<Fp>,
+        key: &EccPoint,
+        r: &EccPoint,
+        s: Value<pallas::Scalar>,
+        e: Value<pallas::Scalar>,
+    ) -> Result<AssignedCell<Fp>, Error>;
+}
+
+#[derive(Clone, Debug)]
+pub struct SchnorrConfig {
+    ecc: EccConfig,
+    sha256: Sha256Config,
+    /// a coordinate of a key, and the packed high and low halves of its words
+    coordinate: [Column<Advice>; 3],
+    q_coordinate: Selector,
+    /// a key of the set
+    key: [Column<Advice>; 2],
+    /// whether it is the selected key
+    bit: Column<Advice>,
+    /// the sum of the selected keys' coordinates and the number of keys selected
+    acc: [Column<Advice>; 3],
+    q_select: Selector,
+}
+
+/// Verifies Schnorr signatures by a key hidden in a committed set of keys.
+pub struct SchnorrChip {
+    config: SchnorrConfig,
+    ecc: EccChip,
+    sha256: Sha256Chip<Fp>,
+}
+
+impl Chip<Fp> for SchnorrChip {
+    type Config = SchnorrConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl SchnorrChip {
+    pub fn construct(config: SchnorrConfig) -> Self {
+        Self {
+            ecc: EccChip::construct(config.ecc.clone()),
+            sha256: Sha256Chip::construct(config.sha256.clone()),
+            config,
+        }
+    }
+
+    /// Takes the columns of [`Sha256Chip::configure`], and reuses the first
+    /// bit columns for the [`EccChip`], to select the key and to check the
+    /// words of its coordinates.
+    pub fn configure(
+        meta: &mut ConstraintSystem<Fp>,
+        bits: [Column<Advice>; 32],
+        word: Column<Advice>,
+        carry: [Column<Advice>; 3],
+        constants: Column<Fixed>,
+    ) -> SchnorrConfig {
+        let sha256 = Sha256Chip::configure(meta, bits, word, carry, constants);
+        let advice: [Column<Advice>; 11] = bits[..11].try_into().unwrap();
+        let ecc = EccChip::configure(meta, advice, constants);
+        // all of these already have equality enabled by the ECC chip
+        let key = [advice[0], advice[1]];
+        let acc = [advice[2], advice[3], advice[4]];
+        let bit = advice[9];
+        let coordinate = [advice[0], advice[1], advice[2]];
+        let q_select = meta.selector();
+        let q_coordinate = meta.selector();
+
+        meta.create_gate("select key", |meta| {
+            let q_select = meta.query_selector(q_select);
+            let bit = meta.query_advice(bit, Rotation::cur());
+            let one = Expression::Constant(Fp::ONE);
+            let [kx, ky] = key.map(|column| meta.query_advice(column, Rotation::cur()));
+            let terms = [kx, ky, one.clone()];
+
+            let mut constraints = vec![bit.clone() * (one - bit.clone())];
+            for (column, term) in acc.iter().zip(terms) {
+                let cur = meta.query_advice(*column, Rotation::cur());
+                let next = meta.query_advice(*column, Rotation::next());
+                constraints.push(next - (cur + bit.clone() * term));
+            }
+            constraints
+                .into_iter()
+                .map(|poly| q_select.clone() * poly)
+                .collect::<Vec<_>>()
+        });
+
+        // the words of a coordinate, packed into their high and low 128 bits;
+        // this holds mod p, so the words are only the canonical ones if they
+        // are also what the commitment hashed
+        meta.create_gate("coordinate", |meta| {
+            let q_coordinate = meta.query_selector(q_coordinate);
+            let [value, high, low] =
+                coordinate.map(|column| meta.query_advice(column, Rotation::cur()));
+            vec![q_coordinate * (value - (high * Fp::from_u128(1 << 64).square() + low))]
+        });
+
+        SchnorrConfig {
+            ecc,
+            sha256,
+            coordinate,
+            q_coordinate,
+            key,
+            bit,
+            acc,
+            q_select,
+        }
+    }
+
+    pub fn ecc(&self) -> &EccChip {
+        &self.ecc
+    }
+
+    /// The eight words of a coordinate, most significant first.
+    fn coordinate_words(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        coordinate: &AssignedCell<Fp>,
+    ) -> Result<Vec<Word<Fp>>, Error> {
+        let values: Vec<Value<u32>> = (0..8)
+            .map(|i| {
+                coordinate.field_value().map(|value| {
+                    let mut bytes = value.to_repr();
+                    bytes.reverse();
+                    u32::from_be_bytes(bytes[4 * i..4 * i + 4].try_into().unwrap())
+                })
+            })
+            .collect();
+        let words = self
+            .sha256
+            .load_words(layouter.namespace(|| "words"), &values)?;
+        let high = self
+            .sha256
+            .pack(layouter.namespace(|| "high"), &words[..4])?;
+        let low = self
+            .sha256
+            .pack(layouter.namespace(|| "low"), &words[4..])?;
+
+        layouter.assign_region(
+            || "coordinate",
+            |mut region| {
+                self.config.q_coordinate.enable(&mut region, 0)?;
+                let [value_column, high_column, low_column] = self.config.coordinate;
+                coordinate.copy_to(&mut region, value_column, 0);
+                high.copy_to(&mut region, high_column, 0);
+                low.copy_to(&mut region, low_column, 0);
+                Ok(())
+            },
+        )?;
+        Ok(words)
+    }
+
+    /// SHA-256 of the words, truncated to fit in a field element.
+    fn hash(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        words: &[Word<Fp>],
+    ) -> Result<AssignedCell<Fp>, Error> {
+        let padded = pad(&vec![0; 4 * words.len()]).concat();
+        let mut message = words.to_vec();
+        message.extend(
+            self.sha256
+                .load_constants(layouter.namespace(|| "padding"), &padded[words.len()..])?,
+        );
+
+        let mut state = self.sha256.initial_state(layouter.namespace(|| "iv"))?;
+        for (i, block) in message.chunks(16).enumerate() {
+            state = self.sha256.compress(
+                layouter.namespace(|| format!("compress {}", i)),
+                &state,
+                &block.to_vec().try_into().unwrap(),
+            )?;
+        }
+        self.sha256
+            .pack(layouter.namespace(|| "pack"), &state[..KEY_SET_WORDS])
+    }
+}
+
+impl SchnorrInstructions for SchnorrChip {
+    fn load_keys(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        keys: &[Value<pallas::Affine>],
+    ) -> Result<(Vec<EccPoint>, AssignedCell<Fp>), Error> {
+        let mut points = vec![];
+        let mut words = vec![];
+        for (i, key) in keys.iter().enumerate() {
+            let mut layouter = layouter.namespace(|| format!("key {}", i));
+            let point = self
+                .ecc
+                .witness_point(layouter.namespace(|| "point"), *key)?;
+            words.extend(self.coordinate_words(layouter.namespace(|| "x"), &point.x)?);
+            words.extend(self.coordinate_words(layouter.namespace(|| "y"), &point.y)?);
+            points.push(point);
+        }
+        let commitment = self.hash(layouter.namespace(|| "key set"), &words)?;
+        Ok((points, commitment))
+    }
+
+    fn select_key(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        keys: &[EccPoint],
+        index: Value<usize>,
+    ) -> Result<EccPoint, Error> {
+        layouter.assign_region(
+            || "select key",
+            |mut region| {
+                let mut acc = [Value::known(Fp::ZERO); 3];
+                let mut acc_cells = self
+                    .config
+                    .acc
+                    .iter()
+                    .map(|column| region.assign_constant(*column, 0, Fp::ZERO))
+                    .collect::<Result<Vec<_>, Error>>()?;
+
+                for (row, key) in keys.iter().enumerate() {
+                    self.config.q_select.enable(&mut region, row)?;
+                    key.x.copy_to(&mut region, self.config.key[0], row);
+                    key.y.copy_to(&mut region, self.config.key[1], row);
+
+                    let bit = index.map(|index| Fp::from((index == row) as u64));
+                    region.assign(self.config.bit, row, bit);
+
+                    let terms = [
+                        key.x.field_value(),
+                        key.y.field_value(),
+                        Value::known(Fp::ONE),
+                    ];
+                    acc_cells.clear();
+                    for ((column, acc), term) in
+                        self.config.acc.iter().zip(acc.iter_mut()).zip(terms)
+                    {
+                        *acc = acc
+                            .zip(bit)
+                            .zip(term)
+                            .map(|((acc, bit), term)| acc + bit * term);
+                        let value = *acc;
+                        acc_cells.push(region.assign(*column, row + 1, value));
+                    }
+                }
+
+                // exactly one key is selected
+                region.constrain_constant(acc_cells[2].cell(), Fp::ONE)?;
+
+                Ok(EccPoint {
+                    x: acc_cells[0].clone(),
+                    y: acc_cells[1].clone(),
+                })
+            },
+        )
+    }
+
+    fn verify(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        key: &EccPoint,
+        r: &EccPoint,
+        s: Value<pallas::Scalar>,
+        e: Value<pallas::Scalar>,
+    ) -> Result<AssignedCell<Fp>, Error> {
+        let s_bits = s
+            .map(|s| scalar_bits(&s))
+            .transpose_vec(pallas::Scalar::NUM_BITS as usize);
+        // the top bit of a challenge is always set, which `mul` takes for granted
+        let e_bits = e
+            .map(|e| {
+                let bits = scalar_bits(&e);
+                bits[bits.len() - CHALLENGE_BITS + 1..].to_vec()
+            })
+            .transpose_vec(CHALLENGE_BITS - 1);
+
+        let (sg, _) = self.ecc.mul_fixed(
+            layouter.namespace(|| "[s]G"),
+            pallas::Point::generator().to_affine(),
+            &s_bits,
+        )?;
+        let (ek, e) = self
+            .ecc
+            .mul(layouter.namespace(|| "[e]key"), key, &e_bits)?;
+        let rhs = self.ecc.add(layouter.namespace(|| "R + [e]key"), r, &ek)?;
+
+        self.ecc
+            .constrain_equal(layouter.namespace(|| "[s]G == R + [e]key"), &sg, &rhs)?;
+
+        Ok(e)
+    }
+}
@@ -0,0 +1,500 @@
+//! SHA-256 (FIPS 180-4) in a circuit.
+//!
+//! Every word the chip computes with lives in a row of its 32 bits, so the
+//! bitwise functions are gates on single bits and rotations only pick other
+//! columns; additions are mod `2^32` with the carry in three bit columns. One
+//! block takes about 2200 rows, so a message of up to 55 bytes fits in `k = 12`.
+//!
+//! Padding is computed natively. [`Sha256PreimageCircuit`] fixes the message
+//! length, which makes every padding word a constant of the circuit.
+
+mod sha256_chip;
+
+pub use self::sha256_chip::{Sha256Chip, Sha256Config, Sha256Instructions, Word};
+
+use crate::backend::GenericCircuit;
+use crate::field::FieldExt;
+use crate::instance::{InstanceColumns, InstanceLayout, NamedInstances};
+use crate::planner::SimpleFloorPlanner;
+use halo2_proofs::circuit::{Layouter, Value};
+use halo2_proofs::plonk::{Circuit, ConstraintSystem, Error};
+use std::marker::PhantomData;
+
+/// The initial hash value.
+pub const IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// The round constants.
+pub const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// The four functions that mix the bits of a single word.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sigma {
+    /// `Σ0(x) = ROTR2(x) ^ ROTR13(x) ^ ROTR22(x)`
+    UpperSigma0,
+    /// `Σ1(x) = ROTR6(x) ^ ROTR11(x) ^ ROTR25(x)`
+    UpperSigma1,
+    /// `σ0(x) = ROTR7(x) ^ ROTR18(x) ^ SHR3(x)`
+    LowerSigma0,
+    /// `σ1(x) = ROTR17(x) ^ ROTR19(x) ^ SHR10(x)`
+    LowerSigma1,
+}
+
+/// The third term of a Σ or σ function.
+enum Shift {
+    Rotate(usize),
+    Shift(usize),
+}
+
+impl Sigma {
+    const ALL: [Sigma; 4] = [
+        Sigma::UpperSigma0,
+        Sigma::UpperSigma1,
+        Sigma::LowerSigma0,
+        Sigma::LowerSigma1,
+    ];
+
+    fn index(&self) -> usize {
+        match self {
+            Sigma::UpperSigma0 => 0,
+            Sigma::UpperSigma1 => 1,
+            Sigma::LowerSigma0 => 2,
+            Sigma::LowerSigma1 => 3,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Sigma::UpperSigma0 => "Σ0",
+            Sigma::UpperSigma1 => "Σ1",
+            Sigma::LowerSigma0 => "σ0",
+            Sigma::LowerSigma1 => "σ1",
+        }
+    }
+
+    fn rotations(&self) -> [usize; 2] {
+        match self {
+            Sigma::UpperSigma0 => [2, 13],
+            Sigma::UpperSigma1 => [6, 11],
+            Sigma::LowerSigma0 => [7, 18],
+            Sigma::LowerSigma1 => [17, 19],
+        }
+    }
+
+    fn third(&self) -> Shift {
+        match self {
+            Sigma::UpperSigma0 => Shift::Rotate(22),
+            Sigma::UpperSigma1 => Shift::Rotate(25),
+            Sigma::LowerSigma0 => Shift::Shift(3),
+            Sigma::LowerSigma1 => Shift::Shift(10),
+        }
+    }
+
+    pub fn apply(&self, x: u32) -> u32 {
+        let [r0, r1] = self.rotations();
+        let third = match self.third() {
+            Shift::Rotate(r) => x.rotate_right(r as u32),
+            Shift::Shift(s) => x >> s,
+        };
+        x.rotate_right(r0 as u32) ^ x.rotate_right(r1 as u32) ^ third
+    }
+}
+
+/// Pads `message` and splits it into blocks of big-endian words.
+pub fn pad(message: &[u8]) -> Vec<[u32; 16]> {
+    let mut bytes = message.to_vec();
+    bytes.push(0x80);
+    while bytes.len() % 64 != 56 {
+        bytes.push(0);
+    }
+    bytes.extend_from_slice(&((message.len() as u64) * 8).to_be_bytes());
+
+    bytes
+        .chunks(64)
+        .map(|block| {
+            let mut words = [0u32; 16];
+            for (word, bytes) in words.iter_mut().zip(block.chunks(4)) {
+                *word = u32::from_be_bytes(bytes.try_into().unwrap());
+            }
+            words
+        })
+        .collect()
+}
+
+/// The compression function on native integers.
+pub fn compress(state: [u32; 8], block: &[u32; 16]) -> [u32; 8] {
+    let mut w = block.to_vec();
+    for t in 16..64 {
+        w.push(
+            Sigma::LowerSigma1
+                .apply(w[t - 2])
+                .wrapping_add(w[t - 7])
+                .wrapping_add(Sigma::LowerSigma0.apply(w[t - 15]))
+                .wrapping_add(w[t - 16]),
+        );
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+    for t in 0..64 {
+        let ch = (e & f) ^ (!e & g);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t1 = h
+            .wrapping_add(Sigma::UpperSigma1.apply(e))
+            .wrapping_add(ch)
+            .wrapping_add(K[t])
+            .wrapping_add(w[t]);
+        let t2 = Sigma::UpperSigma0.apply(a).wrapping_add(maj);
+
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+
+    let out = [a, b, c, d, e, f, g, h];
+    let mut next = state;
+    for (word, out) in next.iter_mut().zip(out) {
+        *word = word.wrapping_add(out);
+    }
+    next
+}
+
+/// The digest of `message` as eight big-endian words.
+pub fn sha256(message: &[u8]) -> [u32; 8] {
+    pad(message).iter().fold(IV, compress)
+}
+
+/// Words as one field element, the first one most significant, as
+/// [`Sha256Chip::pack`] computes it.
+pub fn pack<F: FieldExt>(words: &[u32]) -> F {
+    words.iter().fold(F::ZERO, |acc, word| {
+        acc * F::from_u128(1 << 32) + F::from(*word as u64)
+    })
+}
+
+const DIGEST_NAMES: [&str; 8] = ["h0", "h1", "h2", "h3", "h4", "h5", "h6", "h7"];
+
+/// The public inputs of [`Sha256PreimageCircuit`]: the digest as eight words.
+pub const DIGEST_LAYOUT: InstanceLayout = InstanceLayout::new(&[&DIGEST_NAMES]);
+
+/// Proves knowledge of a `LEN`-byte message with a public SHA-256 digest.
+///
+/// The padded message is loaded as private words, then, if `LEN` is not a
+/// multiple of 4, the word holding the last bytes and the first padding
+/// bytes, then the rest of the padding as constants.
+#[derive(Default)]
+pub struct Sha256PreimageCircuit<F: FieldExt, const LEN: usize> {
+    pub preimage: Value<[u8; LEN]>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const LEN: usize> Sha256PreimageCircuit<F, LEN> {
+    pub fn new(preimage: [u8; LEN]) -> Self {
+        Self {
+            preimage: Value::known(preimage),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: FieldExt, const LEN: usize> NamedInstances for Sha256PreimageCircuit<F, LEN> {
+    fn instance_layout() -> InstanceLayout {
+        DIGEST_LAYOUT
+    }
+}
+
+impl<F: FieldExt, const LEN: usize> Circuit<F> for Sha256PreimageCircuit<F, LEN> {
+    type Config = (Sha256Config, InstanceColumns);
+    type FloorPlanner = SimpleFloorPlanner;
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let bits = [(); 32].map(|_| meta.advice_column());
+        let word = meta.advice_column();
+        let carry = [(); 3].map(|_| meta.advice_column());
+        let constants = meta.fixed_column();
+        let instance = InstanceColumns::configure(meta, DIGEST_LAYOUT);
+
+        (
+            Sha256Chip::configure(meta, bits, word, carry, constants),
+            instance,
+        )
+    }
+
+    fn synthesize(
+        &self,
+        (config, instance): Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = Sha256Chip::construct(config);
+
+        // the padding does not depend on the message, only on its length
+        let padding: Vec<u32> = pad(&[0; LEN]).concat();
+        let padded = self
+            .preimage
+            .map(|preimage| pad(&preimage).concat())
+            .transpose_vec(padding.len());
+
+        let mut words = chip.load_words(layouter.namespace(|| "preimage"), &padded[..LEN / 4])?;
+        let padding = match LEN % 4 {
+            0 => &padding[LEN / 4..],
+            len => {
+                let tail = padded[LEN / 4];
+                words.push(chip.load_tail(layouter.namespace(|| "tail"), tail, len)?);
+                &padding[LEN / 4 + 1..]
+            }
+        };
+        words.extend(chip.load_constants(layouter.namespace(|| "padding"), padding)?);
+
+        let mut state = chip.initial_state(layouter.namespace(|| "iv"))?;
+        for (i, block) in words.chunks(16).enumerate() {
+            state = chip.compress(
+                layouter.namespace(|| format!("compress {}", i)),
+                &state,
+                &block.to_vec().try_into().unwrap(),
+            )?;
+        }
+
+        for (word, name) in state.iter().zip(DIGEST_NAMES) {
+            instance.expose(layouter.namespace(|| name), word.0.cell(), name)?;
+        }
+        Ok(())
+    }
+}
+
+/// The inputs of [`Sha256PreimageCircuit`], independent of the field it is built over.
+#[derive(Clone, Debug)]
+pub struct Sha256PreimageInput<const LEN: usize> {
+    pub preimage: [u8; LEN],
+}
+
+impl<const LEN: usize> GenericCircuit for Sha256PreimageInput<LEN> {
+    type Circuit<F: FieldExt> = Sha256PreimageCircuit<F, LEN>;
+
+    fn circuit<F: FieldExt>(&self) -> Self::Circuit<F> {
+        Sha256PreimageCircuit::new(self.preimage)
+    }
+
+    fn instances<F: FieldExt>(&self) -> Vec<Vec<F>> {
+        let digest = sha256(&self.preimage);
+        let values: Vec<(&str, F)> = DIGEST_NAMES
+            .iter()
+            .zip(digest)
+            .map(|(name, word)| (*name, F::from(word as u64)))
+            .collect();
+        DIGEST_LAYOUT.assemble(&values).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::{
+        arithmetic::Field,
+        dev::MockProver,
+        halo2curves::pasta::Fp,
+        plonk::{Column, Instance},
+    };
+
+    use super::*;
+
+    /// (message, digest) pairs from the FIPS 180-4 examples.
+    const VECTORS: [(&str, &str); 3] = [
+        (
+            "",
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+        ),
+        (
+            "abc",
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+        ),
+        (
+            "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+        ),
+    ];
+
+    fn hex(digest: [u32; 8]) -> String {
+        digest.iter().map(|w| format!("{:08x}", w)).collect()
+    }
+
+    #[test]
+    fn native_test() {
+        for (message, digest) in VECTORS {
+            assert_eq!(hex(sha256(message.as_bytes())), digest);
+        }
+        assert_eq!(
+            hex(sha256(&[b'a'; 1_000_000])),
+            "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"
+        );
+    }
+
+    /// Hashes natively padded blocks and exposes the digest.
+    #[derive(Default)]
+    struct DigestCircuit {
+        blocks: Vec<[u32; 16]>,
+    }
+
+    impl Circuit<Fp> for DigestCircuit {
+        type Config = (Sha256Config, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let bits = [(); 32].map(|_| meta.advice_column());
+            let word = meta.advice_column();
+            let carry = [(); 3].map(|_| meta.advice_column());
+            let constants = meta.fixed_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (
+                Sha256Chip::configure(meta, bits, word, carry, constants),
+                instance,
+            )
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = Sha256Chip::construct(config);
+            let blocks: Vec<_> = self.blocks.iter().copied().map(Value::known).collect();
+            let digest = chip.digest(layouter.namespace(|| "sha256"), &blocks)?;
+            for (row, word) in digest.iter().enumerate() {
+                layouter.constrain_instance(word.0.cell(), instance, row);
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn known_answer_test() {
+        for (message, digest) in VECTORS {
+            let circuit = DigestCircuit {
+                blocks: pad(message.as_bytes()),
+            };
+            let k = if circuit.blocks.len() == 1 { 12 } else { 13 };
+            let digest: Vec<Fp> = (0..8)
+                .map(|i| {
+                    Fp::from(u32::from_str_radix(&digest[i * 8..i * 8 + 8], 16).unwrap() as u64)
+                })
+                .collect();
+
+            MockProver::run(k, &circuit, vec![digest.clone()])
+                .unwrap()
+                .assert_satisfied();
+
+            let mut wrong = digest;
+            wrong[7] += Fp::ONE;
+            assert!(MockProver::run(k, &circuit, vec![wrong])
+                .unwrap()
+                .verify()
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn preimage_test() {
+        let input = Sha256PreimageInput {
+            preimage: *b"a secret preimage of 32 bytes!!!",
+        };
+        let circuit = input.circuit::<Fp>();
+        let instances = input.instances::<Fp>();
+        MockProver::run(12, &circuit, instances.clone())
+            .unwrap()
+            .assert_satisfied();
+
+        let other = Sha256PreimageInput {
+            preimage: *b"another preimage of 32 bytes!!!!",
+        };
+        assert!(MockProver::run(12, &other.circuit::<Fp>(), instances)
+            .unwrap()
+            .verify()
+            .is_err());
+    }
+
+    #[test]
+    fn unaligned_preimage_test() {
+        fn check<const LEN: usize>(preimage: [u8; LEN]) {
+            let input = Sha256PreimageInput { preimage };
+            MockProver::run(12, &input.circuit::<Fp>(), input.instances::<Fp>())
+                .unwrap()
+                .assert_satisfied();
+        }
+        check(*b"");
+        check(*b"abc");
+        check(*b"a secret preimage of 33 bytes!!!!");
+        check(*b"a secret preimage of 54 bytes, the most in one block!!");
+    }
+
+    /// Loads the last word of a message with its padding.
+    #[derive(Default)]
+    struct TailCircuit {
+        value: u32,
+        len: usize,
+    }
+
+    impl Circuit<Fp> for TailCircuit {
+        type Config = Sha256Config;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let bits = [(); 32].map(|_| meta.advice_column());
+            let word = meta.advice_column();
+            let carry = [(); 3].map(|_| meta.advice_column());
+            let constants = meta.fixed_column();
+            Sha256Chip::configure(meta, bits, word, carry, constants)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = Sha256Chip::construct(config);
+            chip.load_tail(layouter, Value::known(self.value), self.len)?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn tail_padding_test() {
+        let tail = |value, len| MockProver::run(5, &TailCircuit { value, len }, vec![]).unwrap();
+
+        tail(0x61626380, 3).assert_satisfied();
+        tail(0x61628000, 2).assert_satisfied();
+        tail(0x61800000, 1).assert_satisfied();
+
+        // the padding bytes cannot be chosen by the prover
+        assert!(tail(0x61626300, 3).verify().is_err());
+        assert!(tail(0x61626381, 3).verify().is_err());
+        assert!(tail(0x61628000, 3).verify().is_err());
+    }
+}
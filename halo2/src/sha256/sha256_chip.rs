@@ -0,0 +1,626 @@
+use halo2_proofs::circuit::{Chip, Layouter, Region, Value};
+use halo2_proofs::plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector};
+use halo2_proofs::poly::Rotation;
+use std::marker::PhantomData;
+
+use super::{Shift, Sigma, IV, K};
+use crate::cell::{AssignedCell, RegionExt};
+use crate::field::FieldExt;
+
+/// A 32-bit word. Every word is range checked when it is assigned.
+#[derive(Clone, Debug)]
+pub struct Word<F: FieldExt>(pub AssignedCell<F>);
+
+impl<F: FieldExt> Word<F> {
+    pub fn value(&self) -> Value<u32> {
+        self.0.field_value().map(|v| v.get_lower_128() as u32)
+    }
+}
+
+/// The most operands a single addition takes: `h + Σ1(e) + Ch(e, f, g) + K + W`.
+const MAX_OPERANDS: usize = 5;
+
+/// The SHA-256 compression function and the word operations it is built from.
+pub trait Sha256Instructions<F: FieldExt>: Chip<F> {
+    /// Assigns private words, e.g. a message block.
+    fn load_words(
+        &self,
+        layouter: impl Layouter<F>,
+        values: &[Value<u32>],
+    ) -> Result<Vec<Word<F>>, Error>;
+
+    /// Assigns the padded word holding the last `len` bytes of a message, for
+    /// `len` from 1 to 3. Those bytes are private, and the padding after them
+    /// is fixed by the circuit.
+    fn load_tail(
+        &self,
+        layouter: impl Layouter<F>,
+        value: Value<u32>,
+        len: usize,
+    ) -> Result<Word<F>, Error>;
+
+    /// Assigns words fixed by the circuit, e.g. message padding.
+    fn load_constants(
+        &self,
+        layouter: impl Layouter<F>,
+        values: &[u32],
+    ) -> Result<Vec<Word<F>>, Error>;
+
+    /// The initial hash value.
+    fn initial_state(&self, layouter: impl Layouter<F>) -> Result<[Word<F>; 8], Error>;
+
+    fn sigma(
+        &self,
+        layouter: impl Layouter<F>,
+        sigma: Sigma,
+        x: &Word<F>,
+    ) -> Result<Word<F>, Error>;
+
+    /// `(e & f) ^ (!e & g)`
+    fn ch(
+        &self,
+        layouter: impl Layouter<F>,
+        e: &Word<F>,
+        f: &Word<F>,
+        g: &Word<F>,
+    ) -> Result<Word<F>, Error>;
+
+    /// `(a & b) ^ (a & c) ^ (b & c)`
+    fn maj(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &Word<F>,
+        b: &Word<F>,
+        c: &Word<F>,
+    ) -> Result<Word<F>, Error>;
+
+    /// The sum of 2 to 5 words mod `2^32`, with an optional round constant as
+    /// the last operand.
+    fn add(
+        &self,
+        layouter: impl Layouter<F>,
+        words: &[&Word<F>],
+        constant: Option<u32>,
+    ) -> Result<Word<F>, Error>;
+
+    /// The words as one field element, the first one most significant, e.g. a
+    /// digest truncated to fit in the field.
+    fn pack(&self, layouter: impl Layouter<F>, words: &[Word<F>])
+        -> Result<AssignedCell<F>, Error>;
+
+    /// Runs the compression function on one message block.
+    fn compress(
+        &self,
+        layouter: impl Layouter<F>,
+        state: &[Word<F>; 8],
+        block: &[Word<F>; 16],
+    ) -> Result<[Word<F>; 8], Error>;
+
+    /// Hashes already padded blocks, one private word per cell.
+    fn digest(
+        &self,
+        layouter: impl Layouter<F>,
+        blocks: &[Value<[u32; 16]>],
+    ) -> Result<[Word<F>; 8], Error>;
+}
+
+#[derive(Clone, Debug)]
+pub struct Sha256Config {
+    /// the bits of a word, least significant first
+    bits: [Column<Advice>; 32],
+    word: Column<Advice>,
+    /// the carry of an addition, in bits
+    carry: [Column<Advice>; 3],
+    q_word: Selector,
+    /// one selector per function, in the order of [`Sigma::ALL`]
+    q_sigma: [Selector; 4],
+    q_ch: Selector,
+    q_maj: Selector,
+    /// `q_add[n - 2]` adds `n` operands
+    q_add: [Selector; MAX_OPERANDS - 1],
+    q_pack_first: Selector,
+    q_pack: Selector,
+    /// `q_tail[n - 1]` fixes the last `n` bytes of a word row to the padding
+    /// `0x80 0x00 ..`
+    q_tail: [Selector; 3],
+}
+
+pub struct Sha256Chip<F: FieldExt> {
+    config: Sha256Config,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Chip<F> for Sha256Chip<F> {
+    type Config = Sha256Config;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+fn xor<F: FieldExt>(a: Expression<F>, b: Expression<F>) -> Expression<F> {
+    a.clone() + b.clone() - a * b * F::from(2)
+}
+
+impl<F: FieldExt> Sha256Chip<F> {
+    pub fn construct(config: Sha256Config) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// `constants` must be a fixed column enabled for constants, which holds the
+    /// round constants and the initial hash value.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        bits: [Column<Advice>; 32],
+        word: Column<Advice>,
+        carry: [Column<Advice>; 3],
+        constants: Column<Fixed>,
+    ) -> Sha256Config {
+        let q_word = meta.selector();
+        let q_sigma = [(); 4].map(|_| meta.selector());
+        let q_ch = meta.selector();
+        let q_maj = meta.selector();
+        let q_add = [(); MAX_OPERANDS - 1].map(|_| meta.selector());
+        let q_pack_first = meta.selector();
+        let q_pack = meta.selector();
+        let q_tail = [(); 3].map(|_| meta.selector());
+
+        meta.enable_equality(word);
+        meta.enable_equality(carry[0]);
+        meta.enable_constant(constants);
+
+        // bit_0 | ... | bit_31 | word | carry_0 | carry_1 | carry_2
+        //
+        // a word row: boolean bits that add up to the word
+        meta.create_gate("word", |meta| {
+            let q = meta.query_selector(q_word);
+            let word = meta.query_advice(word, Rotation::cur());
+
+            let mut constraints = vec![];
+            let mut sum = Expression::Constant(F::ZERO);
+            for (i, column) in bits.iter().enumerate() {
+                let bit = meta.query_advice(*column, Rotation::cur());
+                constraints
+                    .push(q.clone() * bit.clone() * (Expression::Constant(F::ONE) - bit.clone()));
+                sum = sum + bit * F::from_u128(1 << i);
+            }
+            constraints.push(q * (word - sum));
+            constraints
+        });
+
+        // x on the current row, Σ(x) or σ(x) on the next, as word rows
+        for (sigma, q_sigma) in Sigma::ALL.iter().zip(q_sigma) {
+            meta.create_gate(sigma.name(), |meta| {
+                let q = meta.query_selector(q_sigma);
+                let x: Vec<_> = bits
+                    .iter()
+                    .map(|column| meta.query_advice(*column, Rotation::cur()))
+                    .collect();
+
+                bits.iter()
+                    .enumerate()
+                    .map(|(i, column)| {
+                        let out = meta.query_advice(*column, Rotation::next());
+                        let [r0, r1] = sigma.rotations();
+                        let mut value = xor(x[(i + r0) % 32].clone(), x[(i + r1) % 32].clone());
+                        match sigma.third() {
+                            Shift::Rotate(r) => value = xor(value, x[(i + r) % 32].clone()),
+                            Shift::Shift(s) if i + s < 32 => value = xor(value, x[i + s].clone()),
+                            Shift::Shift(_) => {}
+                        }
+                        q.clone() * (out - value)
+                    })
+                    .collect::<Vec<_>>()
+            });
+        }
+
+        // three input word rows followed by the output word row
+        let bitwise3 =
+            |meta: &mut ConstraintSystem<F>,
+             name: &'static str,
+             selector: Selector,
+             f: fn(Expression<F>, Expression<F>, Expression<F>) -> Expression<F>| {
+                meta.create_gate(name, |meta| {
+                    let q = meta.query_selector(selector);
+                    bits.iter()
+                        .map(|column| {
+                            let a = meta.query_advice(*column, Rotation::cur());
+                            let b = meta.query_advice(*column, Rotation::next());
+                            let c = meta.query_advice(*column, Rotation(2));
+                            let out = meta.query_advice(*column, Rotation(3));
+                            q.clone() * (out - f(a, b, c))
+                        })
+                        .collect::<Vec<_>>()
+                });
+            };
+        // e ? f : g
+        bitwise3(meta, "ch", q_ch, |e, f, g| {
+            e.clone() * f + (Expression::Constant(F::ONE) - e) * g
+        });
+        // the majority of a, b, c
+        bitwise3(meta, "maj", q_maj, |a, b, c| {
+            a.clone() * b.clone() + a.clone() * c.clone() + b.clone() * c.clone()
+                - a * b * c * F::from(2)
+        });
+
+        // n operand rows followed by a word row with the sum mod 2^32 and its carry
+        for (i, q_add) in q_add.iter().enumerate() {
+            let n = i + 2;
+            meta.create_gate("add", |meta| {
+                let q = meta.query_selector(*q_add);
+                let sum = (0..n)
+                    .map(|j| meta.query_advice(word, Rotation(j as i32)))
+                    .fold(Expression::Constant(F::ZERO), |acc, w| acc + w);
+                let out = meta.query_advice(word, Rotation(n as i32));
+
+                let mut constraints = vec![];
+                let mut carry_value = Expression::Constant(F::ZERO);
+                for (j, column) in carry.iter().enumerate() {
+                    let c = meta.query_advice(*column, Rotation(n as i32));
+                    constraints
+                        .push(q.clone() * c.clone() * (Expression::Constant(F::ONE) - c.clone()));
+                    carry_value = carry_value + c * F::from(1 << j);
+                }
+                constraints.push(q * (sum - out - carry_value * F::from(1 << 32)));
+                constraints
+            });
+        }
+
+        // words, most significant first, and their running sum in the first
+        // carry column
+        meta.create_gate("first packed word", |meta| {
+            let q = meta.query_selector(q_pack_first);
+            let word = meta.query_advice(word, Rotation::cur());
+            let sum = meta.query_advice(carry[0], Rotation::cur());
+            vec![q * (sum - word)]
+        });
+
+        meta.create_gate("next packed word", |meta| {
+            let q = meta.query_selector(q_pack);
+            let word = meta.query_advice(word, Rotation::cur());
+            let sum = meta.query_advice(carry[0], Rotation::cur());
+            let prev = meta.query_advice(carry[0], Rotation::prev());
+            vec![q * (sum - (prev * F::from_u128(1 << 32) + word))]
+        });
+
+        // a word row ending in n padding bytes: the message continues in its
+        // high bytes, and the low bytes are 0x80 followed by zeros
+        for (i, q_tail) in q_tail.iter().enumerate() {
+            let n = i + 1;
+            meta.create_gate("tail word", |meta| {
+                let q = meta.query_selector(*q_tail);
+                let low = bits[..8 * n].iter().enumerate().fold(
+                    Expression::Constant(F::ZERO),
+                    |sum, (j, column)| {
+                        sum + meta.query_advice(*column, Rotation::cur()) * F::from_u128(1 << j)
+                    },
+                );
+                vec![q * (low - Expression::Constant(F::from(0x80 << (8 * (n - 1)))))]
+            });
+        }
+
+        Sha256Config {
+            bits,
+            word,
+            carry,
+            q_word,
+            q_sigma,
+            q_ch,
+            q_maj,
+            q_add,
+            q_pack_first,
+            q_pack,
+            q_tail,
+        }
+    }
+
+    /// Assigns a word row, which range checks `value` to 32 bits.
+    fn assign_word(
+        &self,
+        region: &mut Region<'_, F>,
+        row: usize,
+        value: Value<u32>,
+    ) -> Result<Word<F>, Error> {
+        self.config.q_word.enable(region, row)?;
+        for (i, column) in self.config.bits.iter().enumerate() {
+            region.assign(*column, row, value.map(|v| F::from(((v >> i) & 1) as u64)));
+        }
+        Ok(Word(region.assign(
+            self.config.word,
+            row,
+            value.map(|v| F::from(v as u64)),
+        )))
+    }
+
+    /// Copies `word` into a word row, to use its bits.
+    fn copy_word(
+        &self,
+        region: &mut Region<'_, F>,
+        row: usize,
+        word: &Word<F>,
+    ) -> Result<(), Error> {
+        let copy = self.assign_word(region, row, word.value())?;
+        region.constrain_equal(word.0.cell(), copy.0.cell());
+        Ok(())
+    }
+
+    fn bitwise3(
+        &self,
+        mut layouter: impl Layouter<F>,
+        name: &'static str,
+        selector: Selector,
+        inputs: [&Word<F>; 3],
+        f: fn(u32, u32, u32) -> u32,
+    ) -> Result<Word<F>, Error> {
+        layouter.assign_region(
+            || name,
+            |mut region| {
+                selector.enable(&mut region, 0)?;
+                for (row, word) in inputs.iter().enumerate() {
+                    self.copy_word(&mut region, row, word)?;
+                }
+                let [a, b, c] = inputs.map(Word::value);
+                let out = a.zip(b).zip(c).map(|((a, b), c)| f(a, b, c));
+                self.assign_word(&mut region, 3, out)
+            },
+        )
+    }
+
+    /// Extends a block to the 64 words of the message schedule.
+    fn schedule(
+        &self,
+        mut layouter: impl Layouter<F>,
+        block: &[Word<F>; 16],
+    ) -> Result<Vec<Word<F>>, Error> {
+        let mut w = block.to_vec();
+        for t in 16..64 {
+            let s0 = self.sigma(layouter.namespace(|| "σ0"), Sigma::LowerSigma0, &w[t - 15])?;
+            let s1 = self.sigma(layouter.namespace(|| "σ1"), Sigma::LowerSigma1, &w[t - 2])?;
+            let word = self.add(
+                layouter.namespace(|| format!("w{}", t)),
+                &[&s1, &w[t - 7], &s0, &w[t - 16]],
+                None,
+            )?;
+            w.push(word);
+        }
+        Ok(w)
+    }
+}
+
+impl<F: FieldExt> Sha256Instructions<F> for Sha256Chip<F> {
+    fn load_words(
+        &self,
+        mut layouter: impl Layouter<F>,
+        values: &[Value<u32>],
+    ) -> Result<Vec<Word<F>>, Error> {
+        layouter.assign_region(
+            || "words",
+            |mut region| {
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(row, value)| self.assign_word(&mut region, row, *value))
+                    .collect()
+            },
+        )
+    }
+
+    fn load_tail(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Value<u32>,
+        len: usize,
+    ) -> Result<Word<F>, Error> {
+        assert!((1..4).contains(&len), "a tail word holds 1 to 3 bytes");
+        layouter.assign_region(
+            || "tail word",
+            |mut region| {
+                self.config.q_tail[3 - len].enable(&mut region, 0)?;
+                self.assign_word(&mut region, 0, value)
+            },
+        )
+    }
+
+    fn load_constants(
+        &self,
+        mut layouter: impl Layouter<F>,
+        values: &[u32],
+    ) -> Result<Vec<Word<F>>, Error> {
+        layouter.assign_region(
+            || "constant words",
+            |mut region| {
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(row, value)| {
+                        let word = self.assign_word(&mut region, row, Value::known(*value))?;
+                        region.constrain_constant(word.0.cell(), F::from(*value as u64))?;
+                        Ok(word)
+                    })
+                    .collect()
+            },
+        )
+    }
+
+    fn initial_state(&self, layouter: impl Layouter<F>) -> Result<[Word<F>; 8], Error> {
+        let words = self.load_constants(layouter, &IV)?;
+        Ok(words.try_into().unwrap())
+    }
+
+    fn sigma(
+        &self,
+        mut layouter: impl Layouter<F>,
+        sigma: Sigma,
+        x: &Word<F>,
+    ) -> Result<Word<F>, Error> {
+        layouter.assign_region(
+            || sigma.name(),
+            |mut region| {
+                self.config.q_sigma[sigma.index()].enable(&mut region, 0)?;
+                self.copy_word(&mut region, 0, x)?;
+                self.assign_word(&mut region, 1, x.value().map(|x| sigma.apply(x)))
+            },
+        )
+    }
+
+    fn ch(
+        &self,
+        layouter: impl Layouter<F>,
+        e: &Word<F>,
+        f: &Word<F>,
+        g: &Word<F>,
+    ) -> Result<Word<F>, Error> {
+        self.bitwise3(layouter, "ch", self.config.q_ch, [e, f, g], |e, f, g| {
+            (e & f) ^ (!e & g)
+        })
+    }
+
+    fn maj(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &Word<F>,
+        b: &Word<F>,
+        c: &Word<F>,
+    ) -> Result<Word<F>, Error> {
+        self.bitwise3(layouter, "maj", self.config.q_maj, [a, b, c], |a, b, c| {
+            (a & b) ^ (a & c) ^ (b & c)
+        })
+    }
+
+    fn add(
+        &self,
+        mut layouter: impl Layouter<F>,
+        words: &[&Word<F>],
+        constant: Option<u32>,
+    ) -> Result<Word<F>, Error> {
+        let n = words.len() + constant.is_some() as usize;
+        assert!((2..=MAX_OPERANDS).contains(&n), "cannot add {} words", n);
+
+        let sum = words
+            .iter()
+            .fold(Value::known(constant.unwrap_or(0) as u64), |acc, w| {
+                acc.zip(w.value()).map(|(acc, v)| acc + v as u64)
+            });
+
+        layouter.assign_region(
+            || format!("add {}", n),
+            |mut region| {
+                self.config.q_add[n - 2].enable(&mut region, 0)?;
+                for (row, word) in words.iter().enumerate() {
+                    word.0.copy_to(&mut region, self.config.word, row);
+                }
+                if let Some(constant) = constant {
+                    region.assign_constant(
+                        self.config.word,
+                        words.len(),
+                        F::from(constant as u64),
+                    )?;
+                }
+
+                for (i, column) in self.config.carry.iter().enumerate() {
+                    region.assign(*column, n, sum.map(|s| F::from((s >> (32 + i)) & 1)));
+                }
+                self.assign_word(&mut region, n, sum.map(|s| s as u32))
+            },
+        )
+    }
+
+    fn pack(
+        &self,
+        mut layouter: impl Layouter<F>,
+        words: &[Word<F>],
+    ) -> Result<AssignedCell<F>, Error> {
+        assert!(
+            32 * words.len() <= F::CAPACITY as usize,
+            "{} words do not fit in a field element",
+            words.len()
+        );
+        layouter.assign_region(
+            || "pack",
+            |mut region| {
+                let mut sum = Value::known(F::ZERO);
+                let mut sum_cell = None;
+                for (row, word) in words.iter().enumerate() {
+                    match row {
+                        0 => self.config.q_pack_first.enable(&mut region, row)?,
+                        _ => self.config.q_pack.enable(&mut region, row)?,
+                    }
+                    word.0.copy_to(&mut region, self.config.word, row);
+                    sum = sum
+                        .zip(word.0.field_value())
+                        .map(|(sum, w)| sum * F::from_u128(1 << 32) + w);
+                    sum_cell = Some(region.assign(self.config.carry[0], row, sum));
+                }
+                Ok(sum_cell.expect("at least one word"))
+            },
+        )
+    }
+
+    fn compress(
+        &self,
+        mut layouter: impl Layouter<F>,
+        state: &[Word<F>; 8],
+        block: &[Word<F>; 16],
+    ) -> Result<[Word<F>; 8], Error> {
+        let w = self.schedule(layouter.namespace(|| "schedule"), block)?;
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state.clone();
+        for t in 0..64 {
+            let mut layouter = layouter.namespace(|| format!("round {}", t));
+
+            let s1 = self.sigma(layouter.namespace(|| "Σ1"), Sigma::UpperSigma1, &e)?;
+            let ch = self.ch(layouter.namespace(|| "ch"), &e, &f, &g)?;
+            let t1 = self.add(
+                layouter.namespace(|| "t1"),
+                &[&h, &s1, &ch, &w[t]],
+                Some(K[t]),
+            )?;
+
+            let s0 = self.sigma(layouter.namespace(|| "Σ0"), Sigma::UpperSigma0, &a)?;
+            let maj = self.maj(layouter.namespace(|| "maj"), &a, &b, &c)?;
+            let t2 = self.add(layouter.namespace(|| "t2"), &[&s0, &maj], None)?;
+
+            h = g;
+            g = f;
+            f = e;
+            e = self.add(layouter.namespace(|| "e"), &[&d, &t1], None)?;
+            d = c;
+            c = b;
+            b = a;
+            a = self.add(layouter.namespace(|| "a"), &[&t1, &t2], None)?;
+        }
+
+        let out = [a, b, c, d, e, f, g, h];
+        let mut next = vec![];
+        for (i, (word, out)) in state.iter().zip(&out).enumerate() {
+            next.push(self.add(layouter.namespace(|| format!("h{}", i)), &[word, out], None)?);
+        }
+        Ok(next.try_into().unwrap())
+    }
+
+    fn digest(
+        &self,
+        mut layouter: impl Layouter<F>,
+        blocks: &[Value<[u32; 16]>],
+    ) -> Result<[Word<F>; 8], Error> {
+        let mut state = self.initial_state(layouter.namespace(|| "iv"))?;
+        for (i, block) in blocks.iter().enumerate() {
+            let values: Vec<Value<u32>> = (0..16).map(|j| block.map(|b| b[j])).collect();
+            let words = self.load_words(layouter.namespace(|| format!("block {}", i)), &values)?;
+            state = self.compress(
+                layouter.namespace(|| format!("compress {}", i)),
+                &state,
+                &words.try_into().unwrap(),
+            )?;
+        }
+        Ok(state)
+    }
+}
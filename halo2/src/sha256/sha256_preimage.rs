@@ -0,0 +1,35 @@
+use std::env;
+
+use halo2_proofs::{dev::MockProver, halo2curves::pasta::Fp};
+use zkG::backend::{Backend, GenericCircuit};
+use zkG::sha256::Sha256PreimageInput;
+
+fn main() {
+    let backend: Backend = match env::args().nth(1) {
+        Some(name) => name.parse().unwrap_or_else(|e| panic!("{}", e)),
+        None => Backend::IpaVesta,
+    };
+
+    println!("Setting up the circuit...");
+    let input = Sha256PreimageInput {
+        preimage: *b"a secret preimage of 32 bytes!!!",
+    };
+
+    let circuit = input.circuit::<Fp>();
+    let public_input = input.instances::<Fp>();
+
+    let k = backend.min_k(&input).unwrap();
+    println!("The circuit fits in 2^{} rows", k);
+
+    println!("Checking the constraints...");
+    let prover = MockProver::run(k, &circuit, public_input).unwrap();
+    prover.assert_satisfied();
+
+    println!("Calculating the proof with {}...", backend);
+    let proof = backend.prove(&input, None).unwrap();
+
+    println!("Verifing proof...");
+    backend.verify(&input, &proof, None).unwrap();
+
+    println!("Proof is verified! ({} bytes)", proof.len());
+}
@@ -0,0 +1,216 @@
+//! Proves that a public vector of `u32`s is a private one, sorted.
+//!
+//! The output is non-decreasing if every difference between consecutive values
+//! is a 32-bit word: a decrease would wrap around to nearly `p`. It is a
+//! permutation of the input if
+//!
+//! ```text
+//!  Π (γ - input_i) = Π (γ - output_i)
+//! ```
+//!
+//! for a random `γ`: both sides are polynomials in `γ` with the values as
+//! roots, so different multisets give equal products for at most `n` values of
+//! `γ`. The products are accumulated as a running quotient `z` that starts and
+//! ends at one.
+//!
+//! `γ` must not be known before the prover has fixed both vectors, and the
+//! circuit is proven in a single phase, so it computes `γ` itself (Fiat-Shamir):
+//! it hashes the input and the output with SHA-256, and takes the first
+//! [`CHALLENGE_WORDS`] words of the digest. The SHA-256 chip also range checks
+//! every value to 32 bits.
+
+mod sort_chip;
+
+pub use self::sort_chip::{SortChip, SortConfig, SortInstructions};
+
+use halo2_proofs::circuit::{Layouter, Value};
+use halo2_proofs::plonk::{Circuit, ConstraintSystem, Error};
+use std::marker::PhantomData;
+
+use crate::field::FieldExt;
+use crate::instance::{InstanceColumns, InstanceLayout, NamedInstances};
+use crate::planner::SimpleFloorPlanner;
+use crate::sha256::{pack, sha256};
+
+// `merge_sort` is a binary crate, so its module is included as is
+#[cfg(test)]
+#[allow(clippy::ptr_arg, clippy::needless_range_loop)]
+#[path = "../../../learningRust/merge_sort/src/sort/merge_sort.rs"]
+mod merge_sort;
+
+/// The digest words that make up `γ`, 224 bits.
+pub const CHALLENGE_WORDS: usize = 7;
+
+/// The longest vector [`SortedPermutationCircuit`] takes.
+pub const MAX_LEN: usize = 16;
+
+#[rustfmt::skip]
+const OUTPUT_NAMES: [&str; MAX_LEN] = [
+    "out0", "out1", "out2", "out3", "out4", "out5", "out6", "out7",
+    "out8", "out9", "out10", "out11", "out12", "out13", "out14", "out15",
+];
+
+/// The challenge `γ` for `input` and `output`, as the circuit computes it.
+pub fn challenge<F: FieldExt>(input: &[u32], output: &[u32]) -> F {
+    let bytes: Vec<u8> = input
+        .iter()
+        .chain(output)
+        .flat_map(|value| value.to_be_bytes())
+        .collect();
+    pack(&sha256(&bytes)[..CHALLENGE_WORDS])
+}
+
+/// Proves that the public `output` is the private `input`, sorted.
+#[derive(Default)]
+pub struct SortedPermutationCircuit<F: FieldExt, const N: usize> {
+    pub input: Value<[u32; N]>,
+    pub output: Value<[u32; N]>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const N: usize> SortedPermutationCircuit<F, N> {
+    /// The public inputs: the output values.
+    pub const LAYOUT: InstanceLayout = InstanceLayout::new(&[OUTPUT_NAMES.split_at(N).0]);
+
+    /// Claims `output` to be `input` sorted, which it may not be.
+    pub fn new(input: [u32; N], output: [u32; N]) -> Self {
+        Self {
+            input: Value::known(input),
+            output: Value::known(output),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn instances(output: &[u32; N]) -> Vec<Vec<F>> {
+        let values: Vec<(&str, F)> = OUTPUT_NAMES
+            .iter()
+            .zip(output)
+            .map(|(name, value)| (*name, F::from(*value as u64)))
+            .collect();
+        Self::LAYOUT.assemble(&values).unwrap()
+    }
+}
+
+impl<F: FieldExt, const N: usize> NamedInstances for SortedPermutationCircuit<F, N> {
+    fn instance_layout() -> InstanceLayout {
+        Self::LAYOUT
+    }
+}
+
+impl<F: FieldExt, const N: usize> Circuit<F> for SortedPermutationCircuit<F, N> {
+    type Config = (SortConfig, InstanceColumns);
+    type FloorPlanner = SimpleFloorPlanner;
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let bits = [(); 32].map(|_| meta.advice_column());
+        let word = meta.advice_column();
+        let carry = [(); 3].map(|_| meta.advice_column());
+        let constants = meta.fixed_column();
+        let instance = InstanceColumns::configure(meta, Self::LAYOUT);
+
+        (
+            SortChip::configure(meta, bits, word, carry, constants),
+            instance,
+        )
+    }
+
+    fn synthesize(
+        &self,
+        (config, instance): Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = SortChip::construct(config);
+
+        let output = chip.sort(
+            layouter.namespace(|| "sort"),
+            &self.input.transpose_array(),
+            &self.output.transpose_array(),
+        )?;
+
+        for (word, name) in output.iter().zip(OUTPUT_NAMES) {
+            instance.expose(layouter.namespace(|| name), word.0.cell(), name)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::{dev::MockProver, halo2curves::pasta::Fp};
+
+    use super::merge_sort;
+    use super::*;
+
+    const K: u32 = 13;
+
+    fn prove<const N: usize>(input: [u32; N], output: [u32; N]) -> Result<(), ()> {
+        let circuit = SortedPermutationCircuit::<Fp, N>::new(input, output);
+        let instances = SortedPermutationCircuit::<Fp, N>::instances(&output);
+        MockProver::run(K, &circuit, instances)
+            .unwrap()
+            .verify()
+            .map_err(|_| ())
+    }
+
+    #[test]
+    fn merge_sort_test() {
+        let input = [
+            3_000_000_000,
+            17,
+            u32::MAX,
+            0,
+            42,
+            17,
+            65_536,
+            1,
+            9,
+            u32::MAX,
+            123_456_789,
+            2,
+            0,
+            77,
+            31_337,
+            5,
+        ];
+        let sorted = merge_sort::sort(&input.to_vec());
+        assert!(merge_sort::is_sorted(&sorted));
+
+        let output: [u32; MAX_LEN] = sorted.try_into().unwrap();
+        assert_eq!(prove(input, output), Ok(()));
+    }
+
+    #[test]
+    fn small_test() {
+        let input = [5, 3, 5, 1];
+        let output: [u32; 4] = merge_sort::sort(&input.to_vec()).try_into().unwrap();
+        assert_eq!(prove(input, output), Ok(()));
+        assert_eq!(prove([7], [7]), Ok(()));
+    }
+
+    #[test]
+    fn not_sorted_test() {
+        // a permutation, but not in order
+        assert!(prove([5, 3, 5, 1], [1, 5, 3, 5]).is_err());
+        assert!(prove([5, 3, 5, 1], [5, 5, 3, 1]).is_err());
+    }
+
+    #[test]
+    fn not_a_permutation_test() {
+        // sorted, but not the input
+        assert!(prove([5, 3, 5, 1], [1, 3, 3, 5]).is_err());
+        assert!(prove([5, 3, 5, 1], [1, 3, 5, 6]).is_err());
+        assert!(prove([5, 3, 5, 1], [0, 0, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn challenge_test() {
+        let gamma: Fp = challenge(&[5, 3, 5, 1], &[1, 3, 5, 5]);
+        assert_ne!(gamma, challenge(&[5, 3, 5, 1], &[1, 3, 3, 5]));
+        assert_ne!(gamma, challenge(&[3, 5, 5, 1], &[1, 3, 5, 5]));
+    }
+}
@@ -0,0 +1,221 @@
+use halo2_proofs::circuit::{Chip, Layouter, Value};
+use halo2_proofs::plonk::{Advice, Column, ConstraintSystem, Error, Fixed, Selector};
+use halo2_proofs::poly::Rotation;
+
+use super::CHALLENGE_WORDS;
+use crate::cell::{AssignedCell, RegionExt};
+use crate::field::FieldExt;
+use crate::sha256::{pad, Sha256Chip, Sha256Config, Sha256Instructions, Word};
+
+/// Sorting, checked as a sorted permutation.
+pub trait SortInstructions<F: FieldExt>: Chip<F> {
+    /// Constrains `output` to be `input` sorted, and returns the output words.
+    /// Both are range checked to 32 bits, and must be equally long.
+    fn sort(
+        &self,
+        layouter: impl Layouter<F>,
+        input: &[Value<u32>],
+        output: &[Value<u32>],
+    ) -> Result<Vec<Word<F>>, Error>;
+}
+
+#[derive(Clone, Debug)]
+pub struct SortConfig {
+    sha256: Sha256Config,
+    /// the input, the output, the running product, the challenge and the
+    /// differences between consecutive outputs
+    advice: [Column<Advice>; 5],
+    q_product: Selector,
+    q_order: Selector,
+}
+
+/// Checks that a vector of `u32`s is the sorted permutation of another.
+pub struct SortChip<F: FieldExt> {
+    config: SortConfig,
+    sha256: Sha256Chip<F>,
+}
+
+impl<F: FieldExt> Chip<F> for SortChip<F> {
+    type Config = SortConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> SortChip<F> {
+    pub fn construct(config: SortConfig) -> Self {
+        Self {
+            sha256: Sha256Chip::construct(config.sha256.clone()),
+            config,
+        }
+    }
+
+    /// Takes the columns of [`Sha256Chip::configure`], and reuses the first
+    /// bit columns for its own rows.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        bits: [Column<Advice>; 32],
+        word: Column<Advice>,
+        carry: [Column<Advice>; 3],
+        constants: Column<Fixed>,
+    ) -> SortConfig {
+        let sha256 = Sha256Chip::configure(meta, bits, word, carry, constants);
+        let advice = [bits[0], bits[1], bits[2], bits[3], bits[4]];
+        let [input, output, product, gamma, diff] = advice;
+        let q_product = meta.selector();
+        let q_order = meta.selector();
+
+        for column in advice {
+            meta.enable_equality(column);
+        }
+
+        // input | output | z | γ | diff
+        //
+        // z_{i+1}·(γ - output_i) = z_i·(γ - input_i), so z_n = Π (γ - input_i) / (γ - output_i)
+        meta.create_gate("product", |meta| {
+            let q_product = meta.query_selector(q_product);
+            let input = meta.query_advice(input, Rotation::cur());
+            let output = meta.query_advice(output, Rotation::cur());
+            let z = meta.query_advice(product, Rotation::cur());
+            let next_z = meta.query_advice(product, Rotation::next());
+            let gamma = meta.query_advice(gamma, Rotation::cur());
+            vec![q_product * (next_z * (gamma.clone() - output) - z * (gamma - input))]
+        });
+
+        // the difference is range checked as a word
+        meta.create_gate("order", |meta| {
+            let q_order = meta.query_selector(q_order);
+            let cur = meta.query_advice(output, Rotation::cur());
+            let next = meta.query_advice(output, Rotation::next());
+            let diff = meta.query_advice(diff, Rotation::cur());
+            vec![q_order * (next - cur - diff)]
+        });
+
+        SortConfig {
+            sha256,
+            advice,
+            q_product,
+            q_order,
+        }
+    }
+
+    /// The challenge of the grand product, from the SHA-256 digest of the input
+    /// and the output.
+    fn challenge(
+        &self,
+        mut layouter: impl Layouter<F>,
+        input: &[Word<F>],
+        output: &[Word<F>],
+    ) -> Result<AssignedCell<F>, Error> {
+        // the padding only depends on the length
+        let len = input.len() + output.len();
+        let padded = pad(&vec![0; 4 * len]).concat();
+        let mut words: Vec<Word<F>> = input.iter().chain(output).cloned().collect();
+        words.extend(
+            self.sha256
+                .load_constants(layouter.namespace(|| "padding"), &padded[len..])?,
+        );
+
+        let mut state = self.sha256.initial_state(layouter.namespace(|| "iv"))?;
+        for (i, block) in words.chunks(16).enumerate() {
+            state = self.sha256.compress(
+                layouter.namespace(|| format!("compress {}", i)),
+                &state,
+                &block.to_vec().try_into().unwrap(),
+            )?;
+        }
+
+        self.sha256
+            .pack(layouter.namespace(|| "γ"), &state[..CHALLENGE_WORDS])
+    }
+
+    fn assign_product(
+        &self,
+        mut layouter: impl Layouter<F>,
+        input: &[Word<F>],
+        output: &[Word<F>],
+        diffs: &[Word<F>],
+        gamma: &AssignedCell<F>,
+    ) -> Result<(), Error> {
+        let [input_column, output_column, product, gamma_column, diff_column] = self.config.advice;
+
+        layouter.assign_region(
+            || "grand product",
+            |mut region| {
+                let mut z = Value::known(F::ONE);
+                let mut z_cell = region.assign_constant(product, 0, F::ONE)?;
+
+                for (row, (a, b)) in input.iter().zip(output).enumerate() {
+                    self.config.q_product.enable(&mut region, row)?;
+                    a.0.copy_to(&mut region, input_column, row);
+                    b.0.copy_to(&mut region, output_column, row);
+                    gamma.copy_to(&mut region, gamma_column, row);
+                    if let Some(diff) = diffs.get(row) {
+                        self.config.q_order.enable(&mut region, row)?;
+                        diff.0.copy_to(&mut region, diff_column, row);
+                    }
+
+                    // an honest output only makes γ - output zero with negligible probability
+                    z = z
+                        .zip(gamma.field_value())
+                        .zip(a.0.field_value())
+                        .zip(b.0.field_value())
+                        .map(|(((z, gamma), a), b)| {
+                            z * (gamma - a) * (gamma - b).invert().unwrap_or(F::ZERO)
+                        });
+                    z_cell = region.assign(product, row + 1, z);
+                }
+
+                region.constrain_constant(z_cell.cell(), F::ONE)
+            },
+        )
+    }
+}
+
+impl<F: FieldExt> SortInstructions<F> for SortChip<F> {
+    fn sort(
+        &self,
+        mut layouter: impl Layouter<F>,
+        input: &[Value<u32>],
+        output: &[Value<u32>],
+    ) -> Result<Vec<Word<F>>, Error> {
+        assert_eq!(input.len(), output.len(), "the output is a permutation");
+
+        let input = self
+            .sha256
+            .load_words(layouter.namespace(|| "input"), input)?;
+        let output = self
+            .sha256
+            .load_words(layouter.namespace(|| "output"), output)?;
+        // an unsorted output has a difference that wraps around, which is no word
+        let diffs: Vec<Value<u32>> = output
+            .windows(2)
+            .map(|pair| {
+                pair[0]
+                    .value()
+                    .zip(pair[1].value())
+                    .map(|(a, b)| b.wrapping_sub(a))
+            })
+            .collect();
+        let diffs = self
+            .sha256
+            .load_words(layouter.namespace(|| "differences"), &diffs)?;
+
+        let gamma = self.challenge(layouter.namespace(|| "challenge"), &input, &output)?;
+        self.assign_product(
+            layouter.namespace(|| "product"),
+            &input,
+            &output,
+            &diffs,
+            &gamma,
+        )?;
+
+        Ok(output)
+    }
+}
@@ -0,0 +1,491 @@
+//! Lookup tables of any width, filled from a closure, an iterator or a CSV file.
+//!
+//! A [`TableChip`] owns `COLS` table columns. Chips look tuples of expressions
+//! up in it with [`TableConfig::lookup`], which takes exactly `COLS` inputs, and
+//! load it once per circuit with [`TableChip::load`]. A table configured with
+//! [`TableChip::configure_tagged`] has an extra tag column, so several logical
+//! tables share the same columns: every row is loaded with the tag of its
+//! table, and [`TableConfig::lookup_tagged`] only matches rows of one tag.
+//!
+//! Rows where the lookup's selector is off look up all zeros, tag included, so
+//! every table starts with an all-zero row tagged 0. A lookup whose selector is
+//! on looks up a nonzero tag and cannot match that row, so a table may leave out
+//! the all-zero tuple like any other value. An untagged table has a tag column
+//! too, with every row tagged [`UNTAGGED`]; tag 0 cannot be given to a table.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::marker::PhantomData;
+use std::path::Path;
+
+use halo2_proofs::{
+    circuit::{Chip, Layouter, Value},
+    plonk::{ConstraintSystem, Error, Expression, TableColumn},
+};
+
+use crate::field::FieldExt;
+use crate::input::FieldValue;
+
+/// The tag of the rows of an untagged table.
+pub const UNTAGGED: u64 = 1;
+
+/// Loads the rows of a lookup table.
+pub trait TableInstructions<F: FieldExt, const COLS: usize>: Chip<F> {
+    /// Loads the rows of an untagged table.
+    fn load(
+        &self,
+        layouter: impl Layouter<F>,
+        rows: impl IntoIterator<Item = [F; COLS]>,
+    ) -> Result<(), Error>;
+
+    /// Loads `len` rows of an untagged table, the `i`th row being `row(i)`.
+    fn load_fn(
+        &self,
+        layouter: impl Layouter<F>,
+        len: usize,
+        row: impl FnMut(usize) -> [F; COLS],
+    ) -> Result<(), Error>;
+
+    /// Loads several logical tables, each with its tag, into a tagged table.
+    fn load_tagged(
+        &self,
+        layouter: impl Layouter<F>,
+        tables: impl IntoIterator<Item = (u64, Vec<[F; COLS]>)>,
+    ) -> Result<(), Error>;
+}
+
+#[derive(Clone, Debug)]
+pub struct TableConfig<F: FieldExt, const COLS: usize> {
+    pub columns: [TableColumn; COLS],
+    pub tag: TableColumn,
+    /// Whether the table is loaded with [`TableChip::load_tagged`].
+    pub tagged: bool,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const COLS: usize> TableConfig<F, COLS> {
+    /// The arguments of `meta.lookup` that constrain `inputs` to be a row of an
+    /// untagged table wherever `selector` is 1.
+    pub fn lookup(
+        &self,
+        selector: Expression<F>,
+        inputs: &[Expression<F>; COLS],
+    ) -> Vec<(Expression<F>, TableColumn)> {
+        assert!(!self.tagged, "a tagged table needs `lookup_tagged`");
+        self.lookup_tag(selector, UNTAGGED, inputs)
+    }
+
+    /// Like [`TableConfig::lookup`], in the rows of the table tagged `tag`.
+    pub fn lookup_tagged(
+        &self,
+        selector: Expression<F>,
+        tag: u64,
+        inputs: &[Expression<F>; COLS],
+    ) -> Vec<(Expression<F>, TableColumn)> {
+        assert!(self.tagged, "an untagged table needs `lookup`");
+        assert_ne!(tag, 0, "tag 0 is reserved for the all-zero row");
+        self.lookup_tag(selector, tag, inputs)
+    }
+
+    fn lookup_tag(
+        &self,
+        selector: Expression<F>,
+        tag: u64,
+        inputs: &[Expression<F>; COLS],
+    ) -> Vec<(Expression<F>, TableColumn)> {
+        let mut lookup = vec![(
+            selector.clone() * Expression::Constant(F::from(tag)),
+            self.tag,
+        )];
+        lookup.extend(
+            inputs
+                .iter()
+                .zip(self.columns)
+                .map(|(input, column)| (selector.clone() * input.clone(), column)),
+        );
+        lookup
+    }
+}
+
+pub struct TableChip<F: FieldExt, const COLS: usize> {
+    config: TableConfig<F, COLS>,
+}
+
+impl<F: FieldExt, const COLS: usize> Chip<F> for TableChip<F, COLS> {
+    type Config = TableConfig<F, COLS>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt, const COLS: usize> TableChip<F, COLS> {
+    pub fn construct(config: TableConfig<F, COLS>) -> Self {
+        Self { config }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> TableConfig<F, COLS> {
+        TableConfig {
+            columns: [(); COLS].map(|_| meta.lookup_table_column()),
+            tag: meta.lookup_table_column(),
+            tagged: false,
+            _marker: PhantomData,
+        }
+    }
+
+    /// A table of several logical tables, to be loaded with
+    /// [`TableChip::load_tagged`].
+    pub fn configure_tagged(meta: &mut ConstraintSystem<F>) -> TableConfig<F, COLS> {
+        TableConfig {
+            tagged: true,
+            ..Self::configure(meta)
+        }
+    }
+
+    fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        rows: Vec<(u64, [F; COLS])>,
+    ) -> Result<(), Error> {
+        layouter.assign_table(
+            || "table",
+            |mut table| {
+                for (offset, (tag, row)) in rows.iter().enumerate() {
+                    table.assign_cell(
+                        || "tag",
+                        self.config.tag,
+                        offset,
+                        || Value::known(F::from(*tag)),
+                    )?;
+                    for (column, value) in self.config.columns.iter().zip(row) {
+                        table.assign_cell(|| "value", *column, offset, || Value::known(*value))?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+impl<F: FieldExt, const COLS: usize> TableInstructions<F, COLS> for TableChip<F, COLS> {
+    fn load(
+        &self,
+        layouter: impl Layouter<F>,
+        rows: impl IntoIterator<Item = [F; COLS]>,
+    ) -> Result<(), Error> {
+        assert!(!self.config.tagged, "a tagged table needs `load_tagged`");
+        let mut tagged = vec![(0, [F::ZERO; COLS])];
+        tagged.extend(rows.into_iter().map(|row| (UNTAGGED, row)));
+        self.assign(layouter, tagged)
+    }
+
+    fn load_fn(
+        &self,
+        layouter: impl Layouter<F>,
+        len: usize,
+        row: impl FnMut(usize) -> [F; COLS],
+    ) -> Result<(), Error> {
+        self.load(layouter, (0..len).map(row))
+    }
+
+    fn load_tagged(
+        &self,
+        layouter: impl Layouter<F>,
+        tables: impl IntoIterator<Item = (u64, Vec<[F; COLS]>)>,
+    ) -> Result<(), Error> {
+        assert!(self.config.tagged, "an untagged table needs `load`");
+        let mut rows = vec![(0, [F::ZERO; COLS])];
+        for (tag, table) in tables {
+            assert_ne!(tag, 0, "tag 0 is reserved for the all-zero row");
+            rows.extend(table.into_iter().map(|row| (tag, row)));
+        }
+        self.assign(layouter, rows)
+    }
+}
+
+#[derive(Debug)]
+pub enum TableError {
+    Io(io::Error),
+    /// A value that is not a number or does not fit in the field.
+    Value {
+        line: usize,
+        message: String,
+    },
+    /// A row with the wrong number of values.
+    Width {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for TableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TableError::Io(e) => write!(f, "cannot read table: {}", e),
+            TableError::Value { line, message } => write!(f, "line {}: {}", line, message),
+            TableError::Width {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {}: expected {} values, found {}",
+                line, expected, found
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TableError {}
+
+impl From<io::Error> for TableError {
+    fn from(e: io::Error) -> Self {
+        TableError::Io(e)
+    }
+}
+
+/// Reads the rows of a table from the CSV file at `path`, see [`parse_csv`].
+pub fn read_csv<F: FieldExt, const COLS: usize>(
+    path: impl AsRef<Path>,
+) -> Result<Vec<[F; COLS]>, TableError> {
+    parse_csv(&fs::read_to_string(path)?)
+}
+
+/// Parses rows of `COLS` comma-separated values, decimal or `0x`-prefixed hex
+/// as in input files. Blank lines and lines starting with `#` are skipped.
+pub fn parse_csv<F: FieldExt, const COLS: usize>(text: &str) -> Result<Vec<[F; COLS]>, TableError> {
+    let mut rows = vec![];
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let values: Vec<&str> = line.split(',').map(str::trim).collect();
+        if values.len() != COLS {
+            return Err(TableError::Width {
+                line: i + 1,
+                expected: COLS,
+                found: values.len(),
+            });
+        }
+
+        let mut row = [F::ZERO; COLS];
+        for (cell, value) in row.iter_mut().zip(values) {
+            let error = |message: String| TableError::Value {
+                line: i + 1,
+                message,
+            };
+            *cell = FieldValue::parse(value)
+                .map_err(error)?
+                .to_field("value")
+                .map_err(|e| error(e.to_string()))?;
+        }
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::{
+        arithmetic::Field,
+        dev::MockProver,
+        halo2curves::pasta::Fp,
+        plonk::{Advice, Circuit, Column, Selector},
+        poly::Rotation,
+    };
+
+    use super::*;
+
+    use crate::planner::SimpleFloorPlanner;
+
+    const SQUARES: u64 = 1;
+    const RANGE: u64 = 2;
+
+    /// Looks `(x, y)` up in the squares table and `x` in the range table, both
+    /// multiplexed into one tagged table.
+    #[derive(Default)]
+    struct TaggedCircuit {
+        x: Value<Fp>,
+        y: Value<Fp>,
+    }
+
+    impl Circuit<Fp> for TaggedCircuit {
+        type Config = ([Column<Advice>; 2], Selector, TableConfig<Fp, 2>);
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [meta.advice_column(), meta.advice_column()];
+            let selector = meta.complex_selector();
+            let table = TableChip::configure_tagged(meta);
+
+            meta.lookup("squares", |meta| {
+                let q = meta.query_selector(selector);
+                let [x, y] = advice.map(|column| meta.query_advice(column, Rotation::cur()));
+                table.lookup_tagged(q, SQUARES, &[x, y])
+            });
+            meta.lookup("range", |meta| {
+                let q = meta.query_selector(selector);
+                let x = meta.query_advice(advice[0], Rotation::cur());
+                table.lookup_tagged(q, RANGE, &[x, Expression::Constant(Fp::ZERO)])
+            });
+
+            (advice, selector, table)
+        }
+
+        fn synthesize(
+            &self,
+            (advice, selector, table): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            // the squares below 16, and the values below 10
+            let squares = (0..16).map(|x| [Fp::from(x), Fp::from(x * x)]).collect();
+            let range = (0..10).map(|x| [Fp::from(x), Fp::ZERO]).collect();
+            TableChip::construct(table).load_tagged(
+                layouter.namespace(|| "table"),
+                [(SQUARES, squares), (RANGE, range)],
+            )?;
+
+            layouter.assign_region(
+                || "lookup",
+                |mut region| {
+                    selector.enable(&mut region, 0)?;
+                    region.assign_advice(advice[0], 0, self.x);
+                    region.assign_advice(advice[1], 0, self.y);
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn prove(x: u64, y: u64) -> Result<(), ()> {
+        let circuit = TaggedCircuit {
+            x: Value::known(Fp::from(x)),
+            y: Value::known(Fp::from(y)),
+        };
+        MockProver::run(6, &circuit, vec![])
+            .unwrap()
+            .verify()
+            .map_err(|_| ())
+    }
+
+    #[test]
+    fn tagged_lookup_test() {
+        assert_eq!(prove(3, 9), Ok(()));
+        assert_eq!(prove(0, 0), Ok(()));
+
+        // not a square
+        assert!(prove(3, 8).is_err());
+        // a square, but out of range
+        assert!(prove(12, 144).is_err());
+        // in the range table, but looked up in the squares
+        assert!(prove(5, 0).is_err());
+    }
+
+    /// Looks `x` up in a table of 1 to 9, which leaves out zero.
+    #[derive(Default)]
+    struct NonzeroCircuit {
+        x: Value<Fp>,
+    }
+
+    impl Circuit<Fp> for NonzeroCircuit {
+        type Config = (Column<Advice>, Selector, TableConfig<Fp, 1>);
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = meta.advice_column();
+            let selector = meta.complex_selector();
+            let table = TableChip::configure(meta);
+
+            meta.lookup("table", |meta| {
+                let q = meta.query_selector(selector);
+                let x = meta.query_advice(advice, Rotation::cur());
+                table.lookup(q, &[x])
+            });
+
+            (advice, selector, table)
+        }
+
+        fn synthesize(
+            &self,
+            (advice, selector, table): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            TableChip::construct(table).load_fn(layouter.namespace(|| "table"), 9, |i| {
+                [Fp::from(i as u64 + 1)]
+            })?;
+
+            layouter.assign_region(
+                || "lookup",
+                |mut region| {
+                    selector.enable(&mut region, 0)?;
+                    region.assign_advice(advice, 0, self.x);
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn untagged_lookup_test() {
+        let prove = |x: u64| {
+            let circuit = NonzeroCircuit {
+                x: Value::known(Fp::from(x)),
+            };
+            MockProver::run(5, &circuit, vec![]).unwrap().verify()
+        };
+
+        assert_eq!(prove(1), Ok(()));
+        assert_eq!(prove(9), Ok(()));
+        assert!(prove(10).is_err());
+        // the all-zero row only matches where the selector is off
+        assert!(prove(0).is_err());
+    }
+
+    #[test]
+    fn parse_csv_test() {
+        let rows = parse_csv::<Fp, 2>("# x, x^2\n0, 0\n\n3,9\n0x10, 256\n").unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                [Fp::ZERO, Fp::ZERO],
+                [Fp::from(3), Fp::from(9)],
+                [Fp::from(16), Fp::from(256)]
+            ]
+        );
+
+        let err = parse_csv::<Fp, 2>("1, 1\n2, 4, 8\n").unwrap_err();
+        assert_eq!(err.to_string(), "line 2: expected 2 values, found 3");
+        assert!(matches!(
+            parse_csv::<Fp, 2>("1, one\n"),
+            Err(TableError::Value { line: 1, .. })
+        ));
+
+        let squares: Vec<[Fp; 2]> =
+            read_csv(concat!(env!("CARGO_MANIFEST_DIR"), "/inputs/squares.csv")).unwrap();
+        assert_eq!(squares.len(), 16);
+        assert_eq!(squares[15], [Fp::from(15), Fp::from(225)]);
+        assert!(matches!(
+            read_csv::<Fp, 2>("no/such/table.csv"),
+            Err(TableError::Io(_))
+        ));
+    }
+}
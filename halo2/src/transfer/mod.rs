@@ -0,0 +1,356 @@
+//! A private token transfer, as `ZkToken::transfer` in `helloAZ` does it.
+//!
+//! Balances and the amount are hidden behind hash commitments: the commitment
+//! to a 64-bit `value` with a random [`BLINDING_WORDS`]-word blinding is
+//!
+//! ```text
+//!  SHA-256(value_hi || value_lo || blinding)
+//! ```
+//!
+//! truncated to its first [`COMMITMENT_WORDS`] words, so it fits in a field
+//! element. [`PrivateTransferCircuit`] opens the sender's and the recipient's
+//! balance and the amount, checks that the sender can afford it, and commits to
+//! the new balances. The verifier only sees the five commitments.
+
+mod transfer_chip;
+
+pub use self::transfer_chip::{TransferChip, TransferConfig, TransferInstructions};
+
+use halo2_proofs::circuit::{Layouter, Value};
+use halo2_proofs::plonk::{Circuit, ConstraintSystem, Error};
+use rand_core::RngCore;
+use std::fmt;
+use std::marker::PhantomData;
+
+use crate::field::FieldExt;
+use crate::instance::{InstanceColumns, InstanceLayout, NamedInstances};
+use crate::planner::SimpleFloorPlanner;
+use crate::sha256::{pack, sha256};
+
+/// The words of a blinding, 192 bits.
+pub const BLINDING_WORDS: usize = 6;
+
+/// The digest words of a commitment, 224 bits.
+pub const COMMITMENT_WORDS: usize = 7;
+
+const COMMITMENT_NAMES: [&str; 5] = [
+    "from_balance",
+    "to_balance",
+    "amount",
+    "new_from_balance",
+    "new_to_balance",
+];
+
+/// A committed value and the blinding that hides it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Opening {
+    pub value: u64,
+    pub blinding: [u32; BLINDING_WORDS],
+}
+
+impl Opening {
+    /// Hides `value` with a random blinding.
+    pub fn new(value: u64, mut rng: impl RngCore) -> Self {
+        Self {
+            value,
+            blinding: [(); BLINDING_WORDS].map(|_| rng.next_u32()),
+        }
+    }
+
+    /// The commitment, as the circuit computes it.
+    pub fn commitment<F: FieldExt>(&self) -> F {
+        let bytes: Vec<u8> = self
+            .words()
+            .iter()
+            .flat_map(|word| word.to_be_bytes())
+            .collect();
+        pack(&sha256(&bytes)[..COMMITMENT_WORDS])
+    }
+
+    /// The hashed words: the value, high word first, then the blinding.
+    fn words(&self) -> [u32; 2 + BLINDING_WORDS] {
+        let mut words = [0; 2 + BLINDING_WORDS];
+        words[0] = (self.value >> 32) as u32;
+        words[1] = self.value as u32;
+        words[2..].copy_from_slice(&self.blinding);
+        words
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransferError {
+    /// The sender's balance is less than the amount.
+    InsufficientBalance,
+    /// The recipient's balance would not fit in a `u64`.
+    Overflow,
+}
+
+impl fmt::Display for TransferError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransferError::InsufficientBalance => write!(f, "insufficient balance"),
+            TransferError::Overflow => write!(f, "the recipient's balance overflows"),
+        }
+    }
+}
+
+impl std::error::Error for TransferError {}
+
+/// The new balances of the sender and the recipient, or why the transfer
+/// fails; the circuit is satisfiable exactly when this is `Ok`.
+pub fn transfer(
+    from_balance: u64,
+    to_balance: u64,
+    amount: u64,
+) -> Result<(u64, u64), TransferError> {
+    if amount > from_balance {
+        return Err(TransferError::InsufficientBalance);
+    }
+    let to_balance = to_balance
+        .checked_add(amount)
+        .ok_or(TransferError::Overflow)?;
+    Ok((from_balance - amount, to_balance))
+}
+
+/// The openings of a transfer: the balances before and after it, and the
+/// amount.
+#[derive(Clone, Copy, Debug)]
+pub struct Transfer {
+    pub from: Opening,
+    pub to: Opening,
+    pub amount: Opening,
+    pub new_from: Opening,
+    pub new_to: Opening,
+}
+
+impl Transfer {
+    /// Moves `amount.value` from `from` to `to`, hiding the new balances with
+    /// fresh blindings.
+    pub fn new(
+        from: Opening,
+        to: Opening,
+        amount: Opening,
+        mut rng: impl RngCore,
+    ) -> Result<Self, TransferError> {
+        let (new_from, new_to) = transfer(from.value, to.value, amount.value)?;
+        Ok(Self {
+            from,
+            to,
+            amount,
+            new_from: Opening::new(new_from, &mut rng),
+            new_to: Opening::new(new_to, &mut rng),
+        })
+    }
+
+    /// The public inputs: the commitments, in the order of the openings.
+    pub fn instances<F: FieldExt>(&self) -> Vec<Vec<F>> {
+        let openings = [self.from, self.to, self.amount, self.new_from, self.new_to];
+        let values: Vec<(&str, F)> = COMMITMENT_NAMES
+            .iter()
+            .zip(openings)
+            .map(|(name, opening)| (*name, opening.commitment()))
+            .collect();
+        PrivateTransferCircuit::<F>::LAYOUT
+            .assemble(&values)
+            .unwrap()
+    }
+}
+
+/// Proves that the committed new balances follow from a transfer between the
+/// committed balances.
+#[derive(Default)]
+pub struct PrivateTransferCircuit<F: FieldExt> {
+    pub transfer: Value<Transfer>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> PrivateTransferCircuit<F> {
+    /// The public inputs: the five commitments.
+    pub const LAYOUT: InstanceLayout = InstanceLayout::new(&[&COMMITMENT_NAMES]);
+
+    pub fn new(transfer: Transfer) -> Self {
+        Self {
+            transfer: Value::known(transfer),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: FieldExt> NamedInstances for PrivateTransferCircuit<F> {
+    fn instance_layout() -> InstanceLayout {
+        Self::LAYOUT
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for PrivateTransferCircuit<F> {
+    type Config = (TransferConfig<F>, InstanceColumns);
+    type FloorPlanner = SimpleFloorPlanner;
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let bits = [(); 32].map(|_| meta.advice_column());
+        let word = meta.advice_column();
+        let carry = [(); 3].map(|_| meta.advice_column());
+        let constants = meta.fixed_column();
+        let instance = InstanceColumns::configure(meta, Self::LAYOUT);
+
+        (
+            TransferChip::configure(meta, bits, word, carry, constants),
+            instance,
+        )
+    }
+
+    fn synthesize(
+        &self,
+        (config, instance): Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = TransferChip::construct(config);
+        chip.load_table(layouter.namespace(|| "bytes"))?;
+
+        let [from, to, amount, new_from, new_to] = COMMITMENT_NAMES;
+        let (from_balance, commitment) =
+            chip.open(layouter.namespace(|| from), self.transfer.map(|t| t.from))?;
+        instance.expose(layouter.namespace(|| from), commitment.cell(), from)?;
+        let (to_balance, commitment) =
+            chip.open(layouter.namespace(|| to), self.transfer.map(|t| t.to))?;
+        instance.expose(layouter.namespace(|| to), commitment.cell(), to)?;
+        let (value, commitment) = chip.open(
+            layouter.namespace(|| amount),
+            self.transfer.map(|t| t.amount),
+        )?;
+        instance.expose(layouter.namespace(|| amount), commitment.cell(), amount)?;
+
+        let (from_balance, to_balance) = chip.transfer(
+            layouter.namespace(|| "transfer"),
+            &from_balance,
+            &to_balance,
+            &value,
+        )?;
+
+        let commitment = chip.commit(
+            layouter.namespace(|| new_from),
+            &from_balance,
+            self.transfer.map(|t| t.new_from.blinding),
+        )?;
+        instance.expose(layouter.namespace(|| new_from), commitment.cell(), new_from)?;
+        let commitment = chip.commit(
+            layouter.namespace(|| new_to),
+            &to_balance,
+            self.transfer.map(|t| t.new_to.blinding),
+        )?;
+        instance.expose(layouter.namespace(|| new_to), commitment.cell(), new_to)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::{dev::MockProver, halo2curves::pasta::Fp};
+    use rand_core::OsRng;
+
+    use super::*;
+
+    const K: u32 = 14;
+
+    fn prove(transfer: Transfer, instances: Vec<Vec<Fp>>) -> Result<(), ()> {
+        let circuit = PrivateTransferCircuit::<Fp>::new(transfer);
+        MockProver::run(K, &circuit, instances)
+            .unwrap()
+            .verify()
+            .map_err(|_| ())
+    }
+
+    #[test]
+    fn native_test() {
+        assert_eq!(transfer(100, 0, 50), Ok((50, 50)));
+        assert_eq!(transfer(100, 7, 100), Ok((0, 107)));
+        assert_eq!(
+            transfer(100, 0, 101),
+            Err(TransferError::InsufficientBalance)
+        );
+        assert_eq!(
+            transfer(u64::MAX, 1, u64::MAX),
+            Err(TransferError::Overflow)
+        );
+
+        // the blinding hides the value
+        let opening = Opening::new(50, OsRng);
+        let other = Opening::new(50, OsRng);
+        assert_ne!(opening.commitment::<Fp>(), other.commitment::<Fp>());
+    }
+
+    #[test]
+    fn transfer_test() {
+        let transfer = Transfer::new(
+            Opening::new(100, OsRng),
+            Opening::new(0, OsRng),
+            Opening::new(50, OsRng),
+            OsRng,
+        )
+        .unwrap();
+        assert_eq!((transfer.new_from.value, transfer.new_to.value), (50, 50));
+        assert_eq!(prove(transfer, transfer.instances()), Ok(()));
+
+        // balances up to u64::MAX
+        let transfer = Transfer::new(
+            Opening::new(u64::MAX, OsRng),
+            Opening::new(u64::MAX - 1, OsRng),
+            Opening::new(1, OsRng),
+            OsRng,
+        )
+        .unwrap();
+        assert_eq!(prove(transfer, transfer.instances()), Ok(()));
+    }
+
+    #[test]
+    fn insufficient_balance_test() {
+        let from = Opening::new(100, OsRng);
+        let to = Opening::new(0, OsRng);
+        let amount = Opening::new(101, OsRng);
+        assert_eq!(
+            Transfer::new(from, to, amount, OsRng).unwrap_err(),
+            TransferError::InsufficientBalance
+        );
+
+        // a prover claiming the wrapped balance
+        let transfer = Transfer {
+            from,
+            to,
+            amount,
+            new_from: Opening::new(100u64.wrapping_sub(101), OsRng),
+            new_to: Opening::new(101, OsRng),
+        };
+        assert!(prove(transfer, transfer.instances()).is_err());
+    }
+
+    #[test]
+    fn wrong_opening_test() {
+        let transfer = Transfer::new(
+            Opening::new(100, OsRng),
+            Opening::new(0, OsRng),
+            Opening::new(50, OsRng),
+            OsRng,
+        )
+        .unwrap();
+        let instances = transfer.instances::<Fp>();
+
+        // new balances that do not follow from the transfer
+        let mut forged = transfer;
+        forged.new_to.value = 60;
+        assert!(prove(forged, forged.instances()).is_err());
+
+        // another blinding opens another commitment
+        let mut forged = transfer;
+        forged.from.blinding[0] ^= 1;
+        assert!(prove(forged, instances.clone()).is_err());
+
+        // a commitment that is not the one opened
+        let mut wrong = instances;
+        wrong[0][2] = Opening::new(50, OsRng).commitment();
+        assert!(prove(transfer, wrong).is_err());
+    }
+}
@@ -0,0 +1,200 @@
+use halo2_proofs::circuit::{Chip, Layouter, Value};
+use halo2_proofs::plonk::{Advice, Column, ConstraintSystem, Error, Fixed};
+
+use super::{Opening, BLINDING_WORDS, COMMITMENT_WORDS};
+use crate::cell::AssignedCell;
+use crate::field::FieldExt;
+use crate::integer::{
+    AssignedU64, AssignedUint, ByteTable, IntegerChip, IntegerConfig, IntegerInstructions, Overflow,
+};
+use crate::sha256::{pad, Sha256Chip, Sha256Config, Sha256Instructions, Word};
+
+/// Balance commitments and transfers between committed balances.
+pub trait TransferInstructions<F: FieldExt>: Chip<F> {
+    /// Loads the byte table of the range checks; call once per circuit.
+    fn load_table(&self, layouter: impl Layouter<F>) -> Result<(), Error>;
+
+    /// Assigns a private opening and returns its value, range checked to 64
+    /// bits, and its commitment.
+    fn open(
+        &self,
+        layouter: impl Layouter<F>,
+        opening: Value<Opening>,
+    ) -> Result<(AssignedU64<F>, AssignedCell<F>), Error>;
+
+    /// Commits to `value` with a private blinding.
+    fn commit(
+        &self,
+        layouter: impl Layouter<F>,
+        value: &AssignedU64<F>,
+        blinding: Value<[u32; BLINDING_WORDS]>,
+    ) -> Result<AssignedCell<F>, Error>;
+
+    /// The balances after moving `amount`, as `ZkToken::transfer` updates
+    /// them. The circuit is unsatisfiable if the sender's balance is
+    /// insufficient, or the recipient's overflows.
+    fn transfer(
+        &self,
+        layouter: impl Layouter<F>,
+        from_balance: &AssignedU64<F>,
+        to_balance: &AssignedU64<F>,
+        amount: &AssignedU64<F>,
+    ) -> Result<(AssignedU64<F>, AssignedU64<F>), Error>;
+}
+
+#[derive(Clone, Debug)]
+pub struct TransferConfig<F: FieldExt> {
+    sha256: Sha256Config,
+    integer: IntegerConfig<F>,
+    /// where a committed value is copied to be compared
+    value: Column<Advice>,
+}
+
+/// Opens and creates balance commitments, and moves an amount between two
+/// balances.
+pub struct TransferChip<F: FieldExt> {
+    config: TransferConfig<F>,
+    sha256: Sha256Chip<F>,
+    integer: IntegerChip<F>,
+}
+
+impl<F: FieldExt> Chip<F> for TransferChip<F> {
+    type Config = TransferConfig<F>;
+    type Loaded = ByteTable<F>;
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        self.integer.loaded()
+    }
+}
+
+impl<F: FieldExt> TransferChip<F> {
+    pub fn construct(config: TransferConfig<F>) -> Self {
+        Self {
+            sha256: Sha256Chip::construct(config.sha256.clone()),
+            integer: IntegerChip::construct(config.integer.clone()),
+            config,
+        }
+    }
+
+    /// Takes the columns of [`Sha256Chip::configure`], and reuses the first
+    /// bit columns for the [`IntegerChip`].
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        bits: [Column<Advice>; 32],
+        word: Column<Advice>,
+        carry: [Column<Advice>; 3],
+        constants: Column<Fixed>,
+    ) -> TransferConfig<F> {
+        let sha256 = Sha256Chip::configure(meta, bits, word, carry, constants);
+        let integer = IntegerChip::configure(
+            meta,
+            [bits[0], bits[1], bits[2], bits[3], bits[4], bits[5]],
+            constants,
+        );
+        TransferConfig {
+            sha256,
+            integer,
+            value: bits[0],
+        }
+    }
+
+    /// SHA-256 of one block of words, truncated to fit in a field element.
+    fn hash(
+        &self,
+        mut layouter: impl Layouter<F>,
+        words: &[Word<F>],
+    ) -> Result<AssignedCell<F>, Error> {
+        let padded = pad(&vec![0; 4 * words.len()]).concat();
+        let mut block = words.to_vec();
+        block.extend(
+            self.sha256
+                .load_constants(layouter.namespace(|| "padding"), &padded[words.len()..])?,
+        );
+
+        let iv = self.sha256.initial_state(layouter.namespace(|| "iv"))?;
+        let digest = self.sha256.compress(
+            layouter.namespace(|| "compress"),
+            &iv,
+            &block.try_into().unwrap(),
+        )?;
+        self.sha256
+            .pack(layouter.namespace(|| "pack"), &digest[..COMMITMENT_WORDS])
+    }
+}
+
+impl<F: FieldExt> TransferInstructions<F> for TransferChip<F> {
+    fn load_table(&self, layouter: impl Layouter<F>) -> Result<(), Error> {
+        self.integer.load_table(layouter)
+    }
+
+    fn open(
+        &self,
+        mut layouter: impl Layouter<F>,
+        opening: Value<Opening>,
+    ) -> Result<(AssignedU64<F>, AssignedCell<F>), Error> {
+        let values: Vec<Value<u32>> = (0..2 + BLINDING_WORDS)
+            .map(|i| opening.map(|opening| opening.words()[i]))
+            .collect();
+        let words = self
+            .sha256
+            .load_words(layouter.namespace(|| "opening"), &values)?;
+
+        // two words make a 64-bit value
+        let value = self
+            .sha256
+            .pack(layouter.namespace(|| "value"), &words[..2])?;
+        let commitment = self.hash(layouter.namespace(|| "commitment"), &words)?;
+        Ok((AssignedUint(value), commitment))
+    }
+
+    fn commit(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: &AssignedU64<F>,
+        blinding: Value<[u32; BLINDING_WORDS]>,
+    ) -> Result<AssignedCell<F>, Error> {
+        let opening = value
+            .value()
+            .zip(blinding)
+            .map(|(value, blinding)| Opening { value, blinding });
+        let (committed, commitment) = self.open(layouter.namespace(|| "open"), opening)?;
+
+        // the opening is of `value`
+        layouter.assign_region(
+            || "committed value",
+            |mut region| {
+                let copy = committed.0.copy_to(&mut region, self.config.value, 0);
+                region.constrain_equal(copy.cell(), value.0.cell());
+                Ok(())
+            },
+        )?;
+        Ok(commitment)
+    }
+
+    fn transfer(
+        &self,
+        mut layouter: impl Layouter<F>,
+        from_balance: &AssignedU64<F>,
+        to_balance: &AssignedU64<F>,
+        amount: &AssignedU64<F>,
+    ) -> Result<(AssignedU64<F>, AssignedU64<F>), Error> {
+        // a checked subtraction is `amount <= from_balance`
+        let from = self.integer.sub(
+            layouter.namespace(|| "from"),
+            Overflow::Checked,
+            from_balance,
+            amount,
+        )?;
+        let to = self.integer.add(
+            layouter.namespace(|| "to"),
+            Overflow::Checked,
+            to_balance,
+            amount,
+        )?;
+        Ok((from.value, to.value))
+    }
+}